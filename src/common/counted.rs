@@ -0,0 +1,55 @@
+use std::{cell::Cell, cmp::Ordering, rc::Rc};
+
+/// 测试用元素，存活的实例数记录在共享的计数器中
+/// 用于检查数据结构在drop、clear、remove之后是否释放了所有节点
+#[derive(Debug)]
+pub struct Counted {
+    _val: i32,
+    _live: Rc<Cell<usize>>,
+}
+
+impl Counted {
+    pub fn new(val: i32, live: &Rc<Cell<usize>>) -> Self {
+        live.set(live.get() + 1);
+        Self {
+            _val: val,
+            _live: live.clone(),
+        }
+    }
+    pub fn val(&self) -> i32 {
+        self._val
+    }
+}
+
+impl Clone for Counted {
+    fn clone(&self) -> Self {
+        Self::new(self._val, &self._live)
+    }
+}
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        self._live.set(self._live.get() - 1);
+    }
+}
+
+//只按值比较
+impl PartialEq for Counted {
+    fn eq(&self, other: &Self) -> bool {
+        self._val == other._val
+    }
+}
+
+impl Eq for Counted {}
+
+impl PartialOrd for Counted {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Counted {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self._val.cmp(&other._val)
+    }
+}
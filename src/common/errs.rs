@@ -1,2 +1,8 @@
 //下标越界
 pub const OUT_OF_RANGE: &'static str = "out of range";
+//区间为空(起点不小于终点)
+pub const EMPTY_INTERVAL: &str = "empty interval";
+//节点数超过下标所能表示的范围
+pub const CAPACITY_OVERFLOW: &str = "capacity overflow";
+//不满足从小到大的顺序
+pub const NOT_SORTED: &str = "not sorted";
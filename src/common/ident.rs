@@ -0,0 +1,20 @@
+use std::hash::{BuildHasherDefault, Hasher};
+
+/// 测试用哈希函数，以u64 key本身作为哈希，可以精确构造冲突和槽的位置
+/// 高7位另存一份key的低7位，SwissMap取高7位作为h2时与key对应
+#[derive(Default)]
+pub struct Ident(u64);
+
+pub type BuildIdent = BuildHasherDefault<Ident>;
+
+impl Hasher for Ident {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+    fn write(&mut self, _bytes: &[u8]) {
+        unreachable!()
+    }
+    fn write_u64(&mut self, i: u64) {
+        self.0 = i | i << 57;
+    }
+}
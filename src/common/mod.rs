@@ -1,3 +1,9 @@
+#[cfg(test)]
+pub mod counted;
 pub mod errs;
+#[cfg(test)]
+pub mod ident;
 pub mod node;
 pub mod pos;
+pub mod rand;
+pub mod viz;
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, collections::VecDeque, rc::Rc};
 
 //Option<Box<Node>>结构的节点
 pub trait BoxNode<T> {
@@ -13,3 +13,373 @@ pub trait BoxEntity<T> {
 
     fn get_node(&self, t: &T) -> Option<&Self::U>;
 }
+
+/// 二叉树的通用访问接口，节点以句柄表示(节点引用或数组下标)
+/// 实现root、left、right、value后即可用下面的迭代器遍历
+/// 迭代器都用显式栈或队列，退化成链表的深树也不会爆栈
+pub trait BinaryTree {
+    type Node<'a>: Copy
+    where
+        Self: 'a;
+    type Value;
+
+    fn root(&self) -> Option<Self::Node<'_>>;
+    fn left<'a>(&'a self, x: Self::Node<'a>) -> Option<Self::Node<'a>>;
+    fn right<'a>(&'a self, x: Self::Node<'a>) -> Option<Self::Node<'a>>;
+    //节点的值，空节点返回None，遍历时跳过
+    fn value<'a>(&'a self, x: Self::Node<'a>) -> Option<&'a Self::Value>;
+
+    //先序遍历
+    fn pre_order_iter(&self) -> PreOrder<'_, Self> {
+        PreOrder {
+            _tree: self,
+            _stack: self.root().into_iter().collect(),
+        }
+    }
+    //中序遍历
+    fn in_order_iter(&self) -> InOrder<'_, Self> {
+        let mut it = InOrder {
+            _tree: self,
+            _stack: vec![],
+        };
+        it._push_left(self.root());
+        it
+    }
+    //后序遍历
+    fn post_order_iter(&self) -> PostOrder<'_, Self> {
+        PostOrder {
+            _tree: self,
+            _stack: self.root().map(|x| (x, false)).into_iter().collect(),
+        }
+    }
+    //层序遍历
+    fn level_order_iter(&self) -> LevelOrder<'_, Self> {
+        LevelOrder {
+            _tree: self,
+            _queue: self.root().into_iter().collect(),
+        }
+    }
+}
+
+/// 先序遍历迭代器，栈中为待访问的节点
+pub struct PreOrder<'a, B: BinaryTree + ?Sized + 'a> {
+    _tree: &'a B,
+    _stack: Vec<B::Node<'a>>,
+}
+
+impl<'a, B: BinaryTree + ?Sized> Iterator for PreOrder<'a, B> {
+    type Item = &'a B::Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let x = self._stack.pop()?;
+            //先压右再压左，左子树先出栈
+            self._stack.extend(self._tree.right(x));
+            self._stack.extend(self._tree.left(x));
+            if let Some(v) = self._tree.value(x) {
+                return Some(v);
+            }
+        }
+    }
+}
+
+/// 中序遍历迭代器，栈中为左子树已访问完、自身尚未访问的祖先
+pub struct InOrder<'a, B: BinaryTree + ?Sized + 'a> {
+    _tree: &'a B,
+    _stack: Vec<B::Node<'a>>,
+}
+
+impl<'a, B: BinaryTree + ?Sized> InOrder<'a, B> {
+    //压入x及其左链
+    fn _push_left(&mut self, mut x: Option<B::Node<'a>>) {
+        while let Some(y) = x {
+            self._stack.push(y);
+            x = self._tree.left(y);
+        }
+    }
+}
+
+impl<'a, B: BinaryTree + ?Sized> Iterator for InOrder<'a, B> {
+    type Item = &'a B::Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let x = self._stack.pop()?;
+            self._push_left(self._tree.right(x));
+            if let Some(v) = self._tree.value(x) {
+                return Some(v);
+            }
+        }
+    }
+}
+
+/// 后序遍历迭代器，栈中记录节点及其子节点是否已压栈
+pub struct PostOrder<'a, B: BinaryTree + ?Sized + 'a> {
+    _tree: &'a B,
+    _stack: Vec<(B::Node<'a>, bool)>,
+}
+
+impl<'a, B: BinaryTree + ?Sized> Iterator for PostOrder<'a, B> {
+    type Item = &'a B::Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((x, expanded)) = self._stack.pop() {
+            if expanded {
+                if let Some(v) = self._tree.value(x) {
+                    return Some(v);
+                }
+                continue;
+            }
+            //子节点访问完后再访问自身
+            self._stack.push((x, true));
+            self._stack.extend(self._tree.right(x).map(|r| (r, false)));
+            self._stack.extend(self._tree.left(x).map(|l| (l, false)));
+        }
+        None
+    }
+}
+
+/// 层序遍历迭代器
+pub struct LevelOrder<'a, B: BinaryTree + ?Sized + 'a> {
+    _tree: &'a B,
+    _queue: VecDeque<B::Node<'a>>,
+}
+
+impl<'a, B: BinaryTree + ?Sized> Iterator for LevelOrder<'a, B> {
+    type Item = &'a B::Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let x = self._queue.pop_front()?;
+            self._queue.extend(self._tree.left(x));
+            self._queue.extend(self._tree.right(x));
+            if let Some(v) = self._tree.value(x) {
+                return Some(v);
+            }
+        }
+    }
+}
+
+/// 遍历时临时改写链接的钩子，所在模块只在crate内可见
+/// 外部无法调用这些钩子，只能通过各树的morris_in_order/reversal_in_order遍历
+pub(crate) mod hooks {
+    /// 节点以u32下标相连、可以临时改写右链接的二叉树，支持Morris遍历
+    pub trait MorrisTree {
+        type Value;
+
+        //把根和长度从树上摘下，树暂时变为空树
+        fn morris_detach(&mut self) -> (Option<u32>, usize);
+        //把摘下的根和长度放回
+        fn morris_attach(&mut self, root: Option<u32>, len: usize);
+        fn morris_left(&self, x: u32) -> Option<u32>;
+        fn morris_right(&self, x: u32) -> Option<u32>;
+        fn morris_set_right(&mut self, x: u32, right: Option<u32>);
+        fn morris_value(&self, x: u32) -> Option<&Self::Value>;
+    }
+
+    /// Option<Box<Node>>相连的节点，键互不相同且满足二叉搜索树的顺序
+    pub trait ReversalNode: Sized {
+        type Key: Ord + ?Sized;
+        type Value;
+
+        fn reversal_link(&mut self, left: bool) -> &mut Option<Box<Self>>;
+        fn reversal_key(&self) -> &Self::Key;
+        fn reversal_value(&self) -> Option<&Self::Value>;
+    }
+
+    /// 以Option<Box<Node>>为根的树
+    pub trait ReversalTree {
+        type Node: ReversalNode;
+
+        //把根和长度从树上摘下，树暂时变为空树
+        fn reversal_detach(&mut self) -> (Option<Box<Self::Node>>, usize);
+        //把摘下的根和长度放回
+        fn reversal_attach(&mut self, root: Option<Box<Self::Node>>, len: usize);
+    }
+}
+
+pub(crate) use hooks::{MorrisTree, ReversalNode, ReversalTree};
+
+/// Morris中序遍历迭代器，借用左子树最右节点的空右链接指回当前节点，额外空间O(1)
+/// 遍历期间根被摘下，drop时走完剩余步骤拆掉线索后再放回
+/// 迭代器被mem::forget时树只是变为空树，不会留下带环的结构
+pub struct MorrisInOrder<'a, M: MorrisTree + ?Sized> {
+    _tree: &'a mut M,
+    _cur: Option<u32>,
+    _root: Option<u32>,
+    _len: usize,
+}
+
+impl<'a, M: MorrisTree + ?Sized> MorrisInOrder<'a, M> {
+    pub(crate) fn new(tree: &'a mut M) -> Self {
+        let (root, len) = tree.morris_detach();
+        Self {
+            _tree: tree,
+            _cur: root,
+            _root: root,
+            _len: len,
+        }
+    }
+    //前进到下一个要访问的节点并返回，同时把_cur移到其后继
+    fn _step(&mut self) -> Option<u32> {
+        loop {
+            let x = self._cur?;
+            let l = match self._tree.morris_left(x) {
+                Some(l) => l,
+                None => {
+                    //没有左子树，访问x后沿右链接(可能是线索)前进
+                    self._cur = self._tree.morris_right(x);
+                    return Some(x);
+                }
+            };
+            //找左子树中的最右节点，即x的前驱
+            let mut pred = l;
+            while let Some(r) = self._tree.morris_right(pred) {
+                if r == x {
+                    break;
+                }
+                pred = r;
+            }
+            if self._tree.morris_right(pred) == Some(x) {
+                //第二次到达x，左子树已访问完，拆掉线索
+                self._tree.morris_set_right(pred, None);
+                self._cur = self._tree.morris_right(x);
+                return Some(x);
+            }
+            //第一次到达x，前驱的右链接指向x后进入左子树
+            self._tree.morris_set_right(pred, Some(x));
+            self._cur = Some(l);
+        }
+    }
+}
+
+impl<M: MorrisTree + ?Sized> Iterator for MorrisInOrder<'_, M>
+where
+    M::Value: Clone,
+{
+    type Item = M::Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let x = self._step()?;
+            if let Some(v) = self._tree.morris_value(x) {
+                return Some(v.clone());
+            }
+        }
+    }
+}
+
+impl<M: MorrisTree + ?Sized> Drop for MorrisInOrder<'_, M> {
+    fn drop(&mut self) {
+        while self._step().is_some() {}
+        self._tree.morris_attach(self._root, self._len);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Reversal {
+    //沿左链下行
+    Down,
+    //_cur刚被访问，接下来进入其右子树
+    Visited,
+    //_cur为已走完的子树，回到父节点
+    Up,
+}
+
+/// Box相连的树的O(1)额外空间中序遍历迭代器，与MorrisInOrder对应
+/// Box链接不能像Morris线索那样指回祖先(会形成所有权环)，改用链接反转:
+/// 下行时把父链存进腾出的子链接，上行时按键的大小判断来自哪一侧并恢复
+/// 遍历期间根被摘下，drop时走完剩余步骤恢复链接后再放回，被mem::forget时树只是变为空树
+pub struct ReversalInOrder<'a, R: ReversalTree + ?Sized> {
+    _tree: &'a mut R,
+    _cur: Option<Box<R::Node>>,
+    //反转后的父链，每个节点在下行那一侧的链接中存着它的父节点
+    _back: Option<Box<R::Node>>,
+    _state: Reversal,
+    _len: usize,
+}
+
+impl<'a, R: ReversalTree + ?Sized> ReversalInOrder<'a, R> {
+    pub(crate) fn new(tree: &'a mut R) -> Self {
+        let (root, len) = tree.reversal_detach();
+        Self {
+            _tree: tree,
+            _cur: root,
+            _back: None,
+            _state: Reversal::Down,
+            _len: len,
+        }
+    }
+    //前进到下一个要访问的节点，访问到时返回true，此时_cur即为该节点
+    //走完后_cur为恢复好的根
+    fn _step(&mut self) -> bool {
+        loop {
+            match self._state {
+                Reversal::Down => {
+                    let Some(x) = self._cur.as_mut() else {
+                        self._state = Reversal::Up;
+                        return false;
+                    };
+                    match x.reversal_link(true).take() {
+                        Some(l) => {
+                            *x.reversal_link(true) = self._back.take();
+                            self._back = self._cur.replace(l);
+                        }
+                        None => {
+                            self._state = Reversal::Visited;
+                            return true;
+                        }
+                    }
+                }
+                Reversal::Visited => {
+                    let x = self._cur.as_mut().unwrap();
+                    match x.reversal_link(false).take() {
+                        Some(r) => {
+                            *x.reversal_link(false) = self._back.take();
+                            self._back = self._cur.replace(r);
+                            self._state = Reversal::Down;
+                        }
+                        None => self._state = Reversal::Up,
+                    }
+                }
+                Reversal::Up => {
+                    let Some(mut p) = self._back.take() else {
+                        return false;
+                    };
+                    let x = self._cur.take();
+                    //键比父节点小说明来自左子树，左子树走完后访问父节点
+                    let left = x.as_ref().unwrap().reversal_key() < p.reversal_key();
+                    self._back = std::mem::replace(p.reversal_link(left), x);
+                    self._cur = Some(p);
+                    if left {
+                        self._state = Reversal::Visited;
+                        return true;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<R: ReversalTree + ?Sized> Iterator for ReversalInOrder<'_, R>
+where
+    <R::Node as ReversalNode>::Value: Clone,
+{
+    type Item = <R::Node as ReversalNode>::Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self._step() {
+            if let Some(v) = self._cur.as_ref().unwrap().reversal_value() {
+                return Some(v.clone());
+            }
+        }
+        None
+    }
+}
+
+impl<R: ReversalTree + ?Sized> Drop for ReversalInOrder<'_, R> {
+    fn drop(&mut self) {
+        while self._step() {}
+        self._tree.reversal_attach(self._cur.take(), self._len);
+    }
+}
@@ -4,6 +4,16 @@ pub enum Pos {
     Right,
 }
 
+impl Pos {
+    //相反的方向
+    pub fn opposite(self) -> Self {
+        match self {
+            Pos::Left => Pos::Right,
+            Pos::Right => Pos::Left,
+        }
+    }
+}
+
 impl Default for Pos {
     fn default() -> Self {
         Pos::Left
@@ -0,0 +1,52 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+/// xorshift64*伪随机数生成器
+/// 只用于跳表层数、树堆优先级等，不可用于加密
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XorShift {
+    _state: u64,
+}
+
+impl XorShift {
+    //以种子初始化，种子相同则序列相同
+    pub fn new(seed: u64) -> Self {
+        //状态为0时会一直输出0
+        let seed = if seed == 0 {
+            0x9E37_79B9_7F4A_7C15
+        } else {
+            seed
+        };
+        Self { _state: seed }
+    }
+    //以随机种子初始化
+    pub fn from_entropy() -> Self {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u8(0);
+        Self::new(hasher.finish())
+    }
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self._state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self._state = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+    //[0, 1)之间的浮点数
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+    //[0, n)之间的整数
+    pub fn gen_range(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+impl Default for XorShift {
+    fn default() -> Self {
+        Self::from_entropy()
+    }
+}
@@ -0,0 +1,124 @@
+use std::fmt::Write;
+
+use super::node::BinaryTree;
+
+/// 树的可视化视图，在BinaryTree的基础上给出节点的文字
+/// 实现view_label即可导出Graphviz DOT和横向的ASCII图
+/// 两种导出都用显式栈，退化成链表的树也不会爆栈
+pub trait TreeView: BinaryTree {
+    fn view_label(&self, x: Self::Node<'_>) -> String;
+    //DOT中节点的附加属性(如颜色)，以", "开头
+    fn view_attrs(&self, _x: Self::Node<'_>) -> String {
+        String::new()
+    }
+    //ASCII图中附在值后面的标记(如红黑树的颜色)
+    fn view_tag(&self, _x: Self::Node<'_>) -> Option<&'static str> {
+        None
+    }
+
+    //导出为Graphviz DOT，只有一个子节点时另一侧画成点，以区分左右
+    fn to_dot(&self) -> String {
+        let mut out = String::from("digraph {\n    node [shape=circle];\n");
+        let mut next_id = 0;
+        let mut stack = vec![];
+        if let Some(root) = self.root() {
+            stack.push((root, next_id));
+            next_id += 1;
+        }
+        while let Some((x, id)) = stack.pop() {
+            let label = _escape(&self.view_label(x));
+            let attrs = self.view_attrs(x);
+            let _ = writeln!(out, "    n{id} [label=\"{label}\"{attrs}];");
+            let children = [self.left(x), self.right(x)];
+            if children.iter().all(Option::is_none) {
+                continue;
+            }
+            for child in children {
+                let child_id = next_id;
+                next_id += 1;
+                match child {
+                    Some(c) => stack.push((c, child_id)),
+                    None => {
+                        let _ = writeln!(out, "    n{child_id} [shape=point];");
+                    }
+                }
+                let _ = writeln!(out, "    n{id} -> n{child_id};");
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    //横向打印，根在最左，右子树在上、左子树在下
+    //      ┌── 6
+    //  ┌── 5
+    //  4
+    //  └── 2
+    fn to_ascii(&self) -> String {
+        enum Task<N> {
+            Visit(N, String, Edge),
+            Line(String),
+        }
+        let mut out = String::new();
+        let mut stack = vec![];
+        if let Some(root) = self.root() {
+            stack.push(Task::Visit(root, String::new(), Edge::Root));
+        }
+        while let Some(task) = stack.pop() {
+            let (x, prefix, edge) = match task {
+                Task::Line(line) => {
+                    out.push_str(&line);
+                    out.push('\n');
+                    continue;
+                }
+                Task::Visit(x, prefix, edge) => (x, prefix, edge),
+            };
+            let (l, r) = (self.left(x), self.right(x));
+            //与父节点之间的连线经过另一侧子树时要画竖线
+            let bar = |side: Edge| match edge {
+                Edge::Root => "",
+                _ if edge == side => "│   ",
+                _ => "    ",
+            };
+            let connector = match edge {
+                Edge::Root => "",
+                Edge::Right => "┌── ",
+                Edge::Left => "└── ",
+            };
+            //后压先出：右子树、本节点、左子树
+            if let Some(l) = l {
+                stack.push(Task::Visit(
+                    l,
+                    format!("{prefix}{}", bar(Edge::Right)),
+                    Edge::Left,
+                ));
+            }
+            let mut line = format!("{prefix}{connector}{}", self.view_label(x));
+            if let Some(tag) = self.view_tag(x) {
+                let _ = write!(line, "[{tag}]");
+            }
+            stack.push(Task::Line(line));
+            if let Some(r) = r {
+                stack.push(Task::Visit(
+                    r,
+                    format!("{prefix}{}", bar(Edge::Left)),
+                    Edge::Right,
+                ));
+            }
+        }
+        out
+    }
+}
+
+//节点与父节点的关系
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Edge {
+    Root,
+    Left,
+    Right,
+}
+
+//转义DOT字符串中的引号和反斜杠
+fn _escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
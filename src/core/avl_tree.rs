@@ -0,0 +1,547 @@
+use std::{cmp::Ordering, fmt::Debug};
+
+use crate::common::node::{
+    BinaryTree, InOrder, LevelOrder, PostOrder, PreOrder, ReversalInOrder, ReversalNode,
+    ReversalTree,
+};
+
+/// AVL树，任一节点左右子树的高度差不超过1
+/// 插入、删除后沿路径自底向上重新计算高度并旋转
+#[derive(Debug, Clone)]
+pub struct AVLTree<T> {
+    _root: InnerAVLNode<T>,
+    _len: usize,
+    //累计旋转次数
+    _rotations: usize,
+}
+
+type InnerAVLNode<T> = Option<Box<AVLNode<T>>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AVLNode<T> {
+    _left: InnerAVLNode<T>,
+    _right: InnerAVLNode<T>,
+    _val: T,
+    //以该节点为根的子树的高度，叶子为1
+    _height: usize,
+}
+
+impl<T> AVLNode<T> {
+    pub fn new(val: T) -> Self {
+        Self {
+            _left: None,
+            _right: None,
+            _val: val,
+            _height: 1,
+        }
+    }
+    pub fn get_val(&self) -> &T {
+        &self._val
+    }
+    pub fn left(&self) -> Option<&Self> {
+        self._left.as_deref()
+    }
+    pub fn right(&self) -> Option<&Self> {
+        self._right.as_deref()
+    }
+    //左子树高度 - 右子树高度
+    fn _balance_factor(&self) -> isize {
+        _height(&self._left) as isize - _height(&self._right) as isize
+    }
+    fn _update_height(&mut self) {
+        self._height = 1 + _height(&self._left).max(_height(&self._right));
+    }
+}
+
+fn _height<T>(node: &InnerAVLNode<T>) -> usize {
+    node.as_ref().map_or(0, |x| x._height)
+}
+
+impl<T> Default for AVLTree<T> {
+    fn default() -> Self {
+        Self {
+            _root: None,
+            _len: 0,
+            _rotations: 0,
+        }
+    }
+}
+
+//按中序比较值，与插入顺序形成的树形及旋转次数无关
+impl<T: PartialEq> PartialEq for AVLTree<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for AVLTree<T> {}
+
+impl<T> AVLTree<T> {
+    //初始化并设置根节点
+    pub fn new(val: T) -> Self {
+        Self {
+            _root: Some(Box::new(AVLNode::new(val))),
+            _len: 1,
+            _rotations: 0,
+        }
+    }
+    //长度
+    pub fn len(&self) -> usize {
+        self._len
+    }
+    //为空返回true
+    pub fn is_empty(&self) -> bool {
+        self._len == 0
+    }
+    //层数，空树为0
+    pub fn height(&self) -> usize {
+        _height(&self._root)
+    }
+    //累计旋转次数
+    pub fn rotations(&self) -> usize {
+        self._rotations
+    }
+    //清空
+    pub fn clear(&mut self) {
+        self._root = None;
+        self._len = 0;
+    }
+    //最小值
+    pub fn min(&self) -> Option<&T> {
+        let mut cur = self._root.as_deref()?;
+        while let Some(l) = cur.left() {
+            cur = l;
+        }
+        Some(&cur._val)
+    }
+    //最大值
+    pub fn max(&self) -> Option<&T> {
+        let mut cur = self._root.as_deref()?;
+        while let Some(r) = cur.right() {
+            cur = r;
+        }
+        Some(&cur._val)
+    }
+    //先序遍历
+    pub fn pre_order(&self) -> PreOrder<'_, Self> {
+        self.pre_order_iter()
+    }
+    //中序遍历，即从小到大
+    pub fn in_order(&self) -> InOrder<'_, Self> {
+        self.in_order_iter()
+    }
+    //后序遍历
+    pub fn post_order(&self) -> PostOrder<'_, Self> {
+        self.post_order_iter()
+    }
+    //层序遍历
+    pub fn level_order(&self) -> LevelOrder<'_, Self> {
+        self.level_order_iter()
+    }
+    //从小到大遍历
+    pub fn iter(&self) -> InOrder<'_, Self> {
+        self.in_order_iter()
+    }
+
+    ///////////////////////////////////////////////
+    ///      2              左旋              4
+    /// 1        4         -->       2             5
+    ///        3   5      <--     1  3
+    ///                     右旋
+    //////////////////////////////////////////////
+    fn _rotate_left(&mut self, mut x: Box<AVLNode<T>>) -> Box<AVLNode<T>> {
+        self._rotations += 1;
+        let mut r = x._right.take().unwrap();
+        x._right = r._left.take();
+        x._update_height();
+        r._left = Some(x);
+        r._update_height();
+        r
+    }
+    fn _rotate_right(&mut self, mut x: Box<AVLNode<T>>) -> Box<AVLNode<T>> {
+        self._rotations += 1;
+        let mut l = x._left.take().unwrap();
+        x._left = l._right.take();
+        x._update_height();
+        l._right = Some(x);
+        l._update_height();
+        l
+    }
+    //子树高度变化后重新计算x的高度，失衡时旋转，返回新的子树根
+    //LL:右旋 LR:左子先左旋再右旋 RR:左旋 RL:右子先右旋再左旋
+    fn _rebalance(&mut self, mut x: Box<AVLNode<T>>) -> Box<AVLNode<T>> {
+        x._update_height();
+        let bf = x._balance_factor();
+        if bf > 1 {
+            let l = x._left.take().unwrap();
+            x._left = Some(if l._balance_factor() < 0 {
+                self._rotate_left(l)
+            } else {
+                l
+            });
+            return self._rotate_right(x);
+        }
+        if bf < -1 {
+            let r = x._right.take().unwrap();
+            x._right = Some(if r._balance_factor() > 0 {
+                self._rotate_right(r)
+            } else {
+                r
+            });
+            return self._rotate_left(x);
+        }
+        x
+    }
+    //摘下子树中的最小节点，返回(剩余子树, 最小节点)
+    fn _take_min(&mut self, mut x: Box<AVLNode<T>>) -> (InnerAVLNode<T>, Box<AVLNode<T>>) {
+        match x._left.take() {
+            None => (x._right.take(), x),
+            Some(l) => {
+                let (rest, min) = self._take_min(l);
+                x._left = rest;
+                (Some(self._rebalance(x)), min)
+            }
+        }
+    }
+}
+
+impl<T: Ord> AVLTree<T> {
+    //额外空间O(1)的中序遍历，遍历期间临时反转链接，返回值的拷贝
+    //不是Morris遍历: 上行时靠比较值的大小判断来自哪一侧，依赖树中的值互不相同(insert不插入重复值)
+    pub fn reversal_in_order(&mut self) -> ReversalInOrder<'_, Self> {
+        ReversalInOrder::new(self)
+    }
+    fn _get_node(&self, t: &T) -> Option<(&AVLNode<T>, usize)> {
+        let mut depth = 0;
+        let mut cur = self._root.as_deref();
+        while let Some(x) = cur {
+            cur = match t.cmp(&x._val) {
+                Ordering::Equal => return Some((x, depth)),
+                Ordering::Less => x.left(),
+                Ordering::Greater => x.right(),
+            };
+            depth += 1;
+        }
+        None
+    }
+    //是否包含t
+    pub fn contains(&self, t: &T) -> bool {
+        self._get_node(t).is_some()
+    }
+    //树中与t相等的值
+    pub fn get(&self, t: &T) -> Option<&T> {
+        self._get_node(t).map(|(x, _)| &x._val)
+    }
+    //t所在节点的深度(根为0)，不存在时返回None
+    pub fn depth(&self, t: &T) -> Option<usize> {
+        self._get_node(t).map(|(_, d)| d)
+    }
+    //插入，已存在时返回false
+    pub fn insert(&mut self, val: T) -> bool {
+        let root = self._root.take();
+        let (root, inserted) = self._insert(root, val);
+        self._root = Some(root);
+        if inserted {
+            self._len += 1;
+        }
+        inserted
+    }
+    fn _insert(&mut self, node: InnerAVLNode<T>, val: T) -> (Box<AVLNode<T>>, bool) {
+        let mut x = match node {
+            None => return (Box::new(AVLNode::new(val)), true),
+            Some(x) => x,
+        };
+        let inserted = match val.cmp(&x._val) {
+            Ordering::Equal => false,
+            Ordering::Less => {
+                let (l, inserted) = self._insert(x._left.take(), val);
+                x._left = Some(l);
+                inserted
+            }
+            Ordering::Greater => {
+                let (r, inserted) = self._insert(x._right.take(), val);
+                x._right = Some(r);
+                inserted
+            }
+        };
+        if !inserted {
+            return (x, false);
+        }
+        (self._rebalance(x), true)
+    }
+    //删除，返回删除的值
+    pub fn remove(&mut self, t: &T) -> Option<T> {
+        let root = self._root.take();
+        let (root, removed) = self._remove(root, t);
+        self._root = root;
+        if removed.is_some() {
+            self._len -= 1;
+        }
+        removed
+    }
+    //有两个子节点时用右子树的最小节点代替被删除的节点
+    fn _remove(&mut self, node: InnerAVLNode<T>, t: &T) -> (InnerAVLNode<T>, Option<T>) {
+        let mut x = match node {
+            None => return (None, None),
+            Some(x) => x,
+        };
+        let removed = match t.cmp(&x._val) {
+            Ordering::Less => {
+                let (l, removed) = self._remove(x._left.take(), t);
+                x._left = l;
+                removed
+            }
+            Ordering::Greater => {
+                let (r, removed) = self._remove(x._right.take(), t);
+                x._right = r;
+                removed
+            }
+            Ordering::Equal => {
+                let AVLNode {
+                    _left,
+                    _right,
+                    _val,
+                    ..
+                } = *x;
+                return match (_left, _right) {
+                    (None, r) => (r, Some(_val)),
+                    (l, None) => (l, Some(_val)),
+                    (l, Some(r)) => {
+                        let (rest, mut min) = self._take_min(r);
+                        min._left = l;
+                        min._right = rest;
+                        (Some(self._rebalance(min)), Some(_val))
+                    }
+                };
+            }
+        };
+        if removed.is_none() {
+            return (Some(x), None);
+        }
+        (Some(self._rebalance(x)), removed)
+    }
+}
+
+impl<T> BinaryTree for AVLTree<T> {
+    type Node<'a>
+        = &'a AVLNode<T>
+    where
+        Self: 'a;
+    type Value = T;
+
+    fn root(&self) -> Option<&AVLNode<T>> {
+        self._root.as_deref()
+    }
+    fn left<'a>(&'a self, x: &'a AVLNode<T>) -> Option<&'a AVLNode<T>> {
+        x.left()
+    }
+    fn right<'a>(&'a self, x: &'a AVLNode<T>) -> Option<&'a AVLNode<T>> {
+        x.right()
+    }
+    fn value<'a>(&'a self, x: &'a AVLNode<T>) -> Option<&'a T> {
+        Some(&x._val)
+    }
+}
+
+impl<T: Ord> ReversalNode for AVLNode<T> {
+    type Key = T;
+    type Value = T;
+
+    fn reversal_link(&mut self, left: bool) -> &mut InnerAVLNode<T> {
+        if left {
+            &mut self._left
+        } else {
+            &mut self._right
+        }
+    }
+    fn reversal_key(&self) -> &T {
+        &self._val
+    }
+    fn reversal_value(&self) -> Option<&T> {
+        Some(&self._val)
+    }
+}
+
+impl<T: Ord> ReversalTree for AVLTree<T> {
+    type Node = AVLNode<T>;
+
+    fn reversal_detach(&mut self) -> (InnerAVLNode<T>, usize) {
+        (self._root.take(), std::mem::take(&mut self._len))
+    }
+    fn reversal_attach(&mut self, root: InnerAVLNode<T>, len: usize) {
+        self._root = root;
+        self._len = len;
+    }
+}
+
+impl<'a, T> IntoIterator for &'a AVLTree<T> {
+    type Item = &'a T;
+    type IntoIter = InOrder<'a, AVLTree<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+//检查平衡因子、高度和有序性，返回子树高度
+fn _check<T: Ord + Debug>(node: Option<&AVLNode<T>>, lo: Option<&T>, hi: Option<&T>) -> usize {
+    let x = match node {
+        None => return 0,
+        Some(x) => x,
+    };
+    assert!(
+        lo.is_none_or(|lo| lo < &x._val),
+        "{:?} out of order",
+        x._val
+    );
+    assert!(
+        hi.is_none_or(|hi| &x._val < hi),
+        "{:?} out of order",
+        x._val
+    );
+    let lh = _check(x.left(), lo, Some(&x._val));
+    let rh = _check(x.right(), Some(&x._val), hi);
+    assert!(lh.abs_diff(rh) <= 1, "{:?} unbalanced", x._val);
+    assert_eq!(x._height, 1 + lh.max(rh));
+    x._height
+}
+
+#[test]
+fn test() {
+    use crate::common::rand::XorShift;
+    use std::collections::BTreeSet;
+
+    let mut avl = AVLTree::default();
+    let mut set = BTreeSet::new();
+    let mut rng = XorShift::new(14);
+    for _ in 0..5000 {
+        let v = rng.gen_range(1000) as i32;
+        if rng.gen_range(3) == 0 {
+            assert_eq!(avl.remove(&v), set.take(&v));
+        } else {
+            assert_eq!(avl.insert(v), set.insert(v));
+        }
+        assert_eq!(avl.len(), set.len());
+    }
+    _check(avl._root.as_deref(), None, None);
+    assert!(avl.iter().eq(set.iter()));
+    assert_eq!(avl.min(), set.first());
+    assert_eq!(avl.max(), set.last());
+    for v in 0..1000 {
+        assert_eq!(avl.get(&v), set.get(&v));
+    }
+
+    //  1 2 3 4 5 6 顺序插入后
+    //      4
+    //    2   5
+    //   1 3    6
+    let mut avl = AVLTree::new(1);
+    for v in 2..=6 {
+        avl.insert(v);
+    }
+    assert_eq!(avl.rotations(), 3);
+    assert_eq!(
+        avl.pre_order().copied().collect::<Vec<_>>(),
+        [4, 2, 1, 3, 5, 6]
+    );
+    assert_eq!(
+        avl.in_order().copied().collect::<Vec<_>>(),
+        [1, 2, 3, 4, 5, 6]
+    );
+    assert_eq!(
+        avl.post_order().copied().collect::<Vec<_>>(),
+        [1, 3, 2, 6, 5, 4]
+    );
+    assert_eq!(
+        avl.level_order().copied().collect::<Vec<_>>(),
+        [4, 2, 5, 1, 3, 6]
+    );
+    assert_eq!(avl.depth(&3), Some(2));
+    assert_eq!(avl.depth(&7), None);
+    //按中序比较，与旋转次数、树形无关
+    let mut other = AVLTree::default();
+    for v in [4, 2, 5, 1, 3, 6] {
+        other.insert(v);
+    }
+    assert_eq!(other.rotations(), 0);
+    assert_eq!(avl, other);
+    other.remove(&6);
+    assert_ne!(avl, other);
+    let (mut a, mut b) = (AVLTree::new(2), AVLTree::new(3));
+    for (x, y) in [(1, 2), (3, 4), (4, 1)] {
+        a.insert(x);
+        b.insert(y);
+    }
+    assert!(!a.pre_order().eq(b.pre_order()));
+    assert_eq!(a, b);
+    //删除根，右子树最小值5上来
+    assert_eq!(avl.remove(&4), Some(4));
+    assert_eq!(
+        avl.level_order().copied().collect::<Vec<_>>(),
+        [5, 2, 6, 1, 3]
+    );
+    _check(avl._root.as_deref(), None, None);
+
+    //链接反转的中序遍历，中途停止后树不变
+    let copy = avl.clone();
+    assert!(avl.reversal_in_order().eq([1, 2, 3, 5, 6]));
+    assert_eq!(avl.reversal_in_order().nth(2), Some(3));
+    assert!(avl.pre_order().eq(copy.pre_order()));
+    //迭代器被forget时树变为空树
+    let mut it = avl.reversal_in_order();
+    assert_eq!(it.next(), Some(1));
+    std::mem::forget(it);
+    assert!(avl.is_empty() && avl.iter().next().is_none());
+    avl.insert(7);
+    assert!(avl.reversal_in_order().eq([7]));
+}
+
+#[test]
+fn test_compare() {
+    use super::{bst::BST, rb_tree::RBTree};
+    use crate::common::rand::XorShift;
+
+    //同一组数据分别插入三种树，比较旋转次数和查找深度
+    let n = 4095;
+    let mut rng = XorShift::new(7);
+    let workloads: [(&str, Vec<i32>); 2] = [
+        ("sorted", (0..n).collect()),
+        (
+            "random",
+            (0..n).map(|_| rng.gen_range(1 << 20) as i32).collect(),
+        ),
+    ];
+    for (name, vals) in workloads {
+        let mut bst = BST::default();
+        let mut avl = AVLTree::default();
+        let mut rb = RBTree::default();
+        for &v in &vals {
+            bst.insert(v);
+            avl.insert(v);
+            rb.insert(v);
+        }
+        let depth = |f: &dyn Fn(&i32) -> Option<usize>| {
+            let depths: Vec<usize> = vals.iter().map(|v| f(v).unwrap()).collect();
+            let avg = depths.iter().sum::<usize>() as f64 / depths.len() as f64;
+            (avg, *depths.iter().max().unwrap())
+        };
+        let (bst_avg, bst_max) = depth(&|v| bst.depth(v));
+        let (avl_avg, avl_max) = depth(&|v| avl.depth(v));
+        let (rb_avg, rb_max) = depth(&|v| rb.depth(v));
+        println!("{name}: bst depth avg {bst_avg:.2} max {bst_max}");
+        println!(
+            "{name}: avl depth avg {avl_avg:.2} max {avl_max}, rotations {}",
+            avl.rotations()
+        );
+        println!(
+            "{name}: rb  depth avg {rb_avg:.2} max {rb_max}, rotations {}",
+            rb.rotations()
+        );
+        //AVL树高小于1.4405*log2(n+2)-0.3277，红黑树不超过2*log2(n+1)
+        let avl_bound = 1.4405 * (n as f64 + 2.0).log2() - 0.3277;
+        let rb_bound = 2.0 * (n as f64 + 1.0).log2();
+        assert!(((avl_max + 1) as f64) < avl_bound);
+        assert!(((rb_max + 1) as f64) <= rb_bound);
+    }
+}
@@ -0,0 +1,658 @@
+use std::{
+    fmt::Debug,
+    mem,
+    ops::{Bound, RangeBounds, RangeFull},
+};
+
+use crate::common::errs::{CAPACITY_OVERFLOW, NOT_SORTED};
+
+/// B+树，值只存放在叶子中，内部节点只存分隔key
+/// 叶子按key顺序双向相连，范围查询定位到起点后顺着叶子链表走
+/// 节点存放在_nodes中，以u32下标相连，删除的节点下标放入_free复用
+/// 除根外每个节点有B-1..=2B-1个key
+pub struct BPlusTree<K, V, const B: usize = 16> {
+    _nodes: Vec<BPNode<K, V>>,
+    _root: u32,
+    _free: Vec<u32>,
+    _len: usize,
+}
+
+//空下标
+const NIL: u32 = u32::MAX;
+
+#[derive(Debug, Clone)]
+pub enum BPNode<K, V> {
+    Internal(BPInternal<K>),
+    Leaf(BPLeaf<K, V>),
+}
+
+/// 内部节点，_children[i]中的key都小于_keys[i]，_children[i+1]中的key都不小于_keys[i]
+#[derive(Debug, Clone)]
+pub struct BPInternal<K> {
+    _keys: Vec<K>,
+    _children: Vec<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BPLeaf<K, V> {
+    _keys: Vec<K>,
+    _vals: Vec<V>,
+    _prev: u32,
+    _next: u32,
+}
+
+impl<K, V> BPLeaf<K, V> {
+    fn new(prev: u32, next: u32) -> Self {
+        Self {
+            _keys: vec![],
+            _vals: vec![],
+            _prev: prev,
+            _next: next,
+        }
+    }
+    pub fn keys(&self) -> &[K] {
+        &self._keys
+    }
+    pub fn vals(&self) -> &[V] {
+        &self._vals
+    }
+}
+
+impl<K, V> BPNode<K, V> {
+    fn _keys(&self) -> &[K] {
+        match self {
+            BPNode::Internal(x) => &x._keys,
+            BPNode::Leaf(x) => &x._keys,
+        }
+    }
+}
+
+impl<K: Clone, V: Clone, const B: usize> Clone for BPlusTree<K, V, B> {
+    fn clone(&self) -> Self {
+        Self {
+            _nodes: self._nodes.clone(),
+            _root: self._root,
+            _free: self._free.clone(),
+            _len: self._len,
+        }
+    }
+}
+
+impl<K, V, const B: usize> Default for BPlusTree<K, V, B> {
+    fn default() -> Self {
+        let () = Self::_CHECK;
+        Self {
+            _nodes: vec![BPNode::Leaf(BPLeaf::new(NIL, NIL))],
+            _root: 0,
+            _free: vec![],
+            _len: 0,
+        }
+    }
+}
+
+impl<K, V, const B: usize> BPlusTree<K, V, B> {
+    //每个节点最多的key数
+    pub const CAPACITY: usize = 2 * B - 1;
+    const _CHECK: () = assert!(B >= 2, "B must be at least 2");
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+    //长度
+    pub fn len(&self) -> usize {
+        self._len
+    }
+    //为空返回true
+    pub fn is_empty(&self) -> bool {
+        self._len == 0
+    }
+    //清空
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+    //层数，只有根时为1
+    pub fn height(&self) -> usize {
+        let mut height = 1;
+        let mut x = self._root;
+        while let BPNode::Internal(n) = &self._nodes[x as usize] {
+            x = n._children[0];
+            height += 1;
+        }
+        height
+    }
+    //按key从小到大遍历
+    pub fn iter(&self) -> Range<'_, K, V, B> {
+        Range {
+            _tree: self,
+            _leaf: self._first_leaf(),
+            _index: 0,
+            _range: ..,
+        }
+    }
+
+    fn _node(&self, x: u32) -> &BPNode<K, V> {
+        &self._nodes[x as usize]
+    }
+    fn _leaf(&self, x: u32) -> &BPLeaf<K, V> {
+        match self._node(x) {
+            BPNode::Leaf(l) => l,
+            BPNode::Internal(_) => unreachable!("not a leaf"),
+        }
+    }
+    fn _leaf_mut(&mut self, x: u32) -> &mut BPLeaf<K, V> {
+        match &mut self._nodes[x as usize] {
+            BPNode::Leaf(l) => l,
+            BPNode::Internal(_) => unreachable!("not a leaf"),
+        }
+    }
+    fn _internal(&self, x: u32) -> &BPInternal<K> {
+        match self._node(x) {
+            BPNode::Internal(n) => n,
+            BPNode::Leaf(_) => unreachable!("not an internal node"),
+        }
+    }
+    fn _internal_mut(&mut self, x: u32) -> &mut BPInternal<K> {
+        match &mut self._nodes[x as usize] {
+            BPNode::Internal(n) => n,
+            BPNode::Leaf(_) => unreachable!("not an internal node"),
+        }
+    }
+    //放入节点，优先复用_free中的位置
+    fn _alloc(&mut self, node: BPNode<K, V>) -> u32 {
+        if let Some(x) = self._free.pop() {
+            self._nodes[x as usize] = node;
+            return x;
+        }
+        let x = self._nodes.len();
+        assert!(x < NIL as usize, "{}", CAPACITY_OVERFLOW);
+        self._nodes.push(node);
+        x as u32
+    }
+    //取出节点，位置放入_free
+    fn _dealloc(&mut self, x: u32) -> BPNode<K, V> {
+        self._free.push(x);
+        mem::replace(
+            &mut self._nodes[x as usize],
+            BPNode::Leaf(BPLeaf::new(NIL, NIL)),
+        )
+    }
+    fn _first_leaf(&self) -> u32 {
+        let mut x = self._root;
+        while let BPNode::Internal(n) = self._node(x) {
+            x = n._children[0];
+        }
+        x
+    }
+}
+
+impl<K: Ord, V, const B: usize> BPlusTree<K, V, B> {
+    //key所在的叶子，path记下经过的(内部节点, 子节点下标)
+    fn _find_leaf(&self, key: &K, mut path: Option<&mut Vec<(u32, usize)>>) -> u32 {
+        let mut x = self._root;
+        while let BPNode::Internal(n) = self._node(x) {
+            let i = n._keys.partition_point(|s| s <= key);
+            if let Some(path) = path.as_mut() {
+                path.push((x, i));
+            }
+            x = n._children[i];
+        }
+        x
+    }
+    //是否包含key
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+    //查找
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let leaf = self._leaf(self._find_leaf(key, None));
+        let i = leaf._keys.binary_search(key).ok()?;
+        Some(&leaf._vals[i])
+    }
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let x = self._find_leaf(key, None);
+        let leaf = self._leaf_mut(x);
+        let i = leaf._keys.binary_search(key).ok()?;
+        Some(&mut leaf._vals[i])
+    }
+    //按范围从小到大遍历，定位到起点后顺着叶子链表走
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, B, R> {
+        let (leaf, index) = match range.start_bound() {
+            Bound::Included(s) => {
+                let x = self._find_leaf(s, None);
+                (x, self._leaf(x)._keys.partition_point(|k| k < s))
+            }
+            Bound::Excluded(s) => {
+                let x = self._find_leaf(s, None);
+                (x, self._leaf(x)._keys.partition_point(|k| k <= s))
+            }
+            Bound::Unbounded => (self._first_leaf(), 0),
+        };
+        Range {
+            _tree: self,
+            _leaf: leaf,
+            _index: index,
+            _range: range,
+        }
+    }
+}
+
+impl<K: Ord + Clone, V, const B: usize> BPlusTree<K, V, B> {
+    //由从小到大排好序的(key, 值)批量构造，O(n)，相邻的相等key只保留最后一个
+    //自底向上逐层构造，每层节点的key数尽量平均
+    pub fn from_sorted_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut entries: Vec<(K, V)> = vec![];
+        for (k, v) in iter {
+            match entries.last_mut() {
+                Some(last) if last.0 == k => last.1 = v,
+                Some(last) => {
+                    assert!(last.0 < k, "{}", NOT_SORTED);
+                    entries.push((k, v));
+                }
+                None => entries.push((k, v)),
+            }
+        }
+        let mut tree = Self::default();
+        if entries.is_empty() {
+            return tree;
+        }
+        tree._nodes.clear();
+        tree._len = entries.len();
+        //叶子层，每个节点记下(下标, 子树最小key)
+        let mut level: Vec<(u32, K)> = vec![];
+        let mut entries = entries.into_iter();
+        for size in _chunk_sizes(tree._len, Self::CAPACITY) {
+            let prev = level.last().map_or(NIL, |x| x.0);
+            let mut leaf = BPLeaf::new(prev, NIL);
+            for (k, v) in entries.by_ref().take(size) {
+                leaf._keys.push(k);
+                leaf._vals.push(v);
+            }
+            let first = leaf._keys[0].clone();
+            let x = tree._alloc(BPNode::Leaf(leaf));
+            if prev != NIL {
+                tree._leaf_mut(prev)._next = x;
+            }
+            level.push((x, first));
+        }
+        //内部节点层，每个节点最多有CAPACITY+1个子节点
+        while level.len() > 1 {
+            let mut children = level.into_iter();
+            let mut upper = vec![];
+            for size in _chunk_sizes(children.len(), Self::CAPACITY + 1) {
+                let mut group = children.by_ref().take(size);
+                let (first_child, first) = group.next().unwrap();
+                let mut node = BPInternal {
+                    _keys: vec![],
+                    _children: vec![first_child],
+                };
+                for (child, key) in group {
+                    node._keys.push(key);
+                    node._children.push(child);
+                }
+                upper.push((tree._alloc(BPNode::Internal(node)), first));
+            }
+            level = upper;
+        }
+        tree._root = level[0].0;
+        tree
+    }
+    //插入，key已存在时替换并返回旧值
+    pub fn insert(&mut self, key: K, val: V) -> Option<V> {
+        let mut path = vec![];
+        let x = self._find_leaf(&key, Some(&mut path));
+        let leaf = self._leaf_mut(x);
+        let i = match leaf._keys.binary_search(&key) {
+            Ok(i) => return Some(mem::replace(&mut leaf._vals[i], val)),
+            Err(i) => i,
+        };
+        leaf._keys.insert(i, key);
+        leaf._vals.insert(i, val);
+        let overflow = leaf._keys.len() > Self::CAPACITY;
+        self._len += 1;
+        if !overflow {
+            return None;
+        }
+        //叶子溢出，后一半移到新叶子，新叶子的最小key复制一份到父节点
+        let leaf = self._leaf_mut(x);
+        let next = leaf._next;
+        let mut right = BPLeaf::new(x, next);
+        right._keys = leaf._keys.split_off(B);
+        right._vals = leaf._vals.split_off(B);
+        let mut sep = right._keys[0].clone();
+        let mut new = self._alloc(BPNode::Leaf(right));
+        self._leaf_mut(x)._next = new;
+        if next != NIL {
+            self._leaf_mut(next)._prev = new;
+        }
+        //沿路径向上插入分隔key，内部节点溢出时中间的key上移
+        let mut left = x;
+        while let Some((p, i)) = path.pop() {
+            let parent = self._internal_mut(p);
+            parent._keys.insert(i, sep);
+            parent._children.insert(i + 1, new);
+            if parent._keys.len() <= Self::CAPACITY {
+                return None;
+            }
+            let keys = parent._keys.split_off(B + 1);
+            let children = parent._children.split_off(B + 1);
+            sep = parent._keys.pop().unwrap();
+            new = self._alloc(BPNode::Internal(BPInternal {
+                _keys: keys,
+                _children: children,
+            }));
+            left = p;
+        }
+        //根分裂，树高加1
+        self._root = self._alloc(BPNode::Internal(BPInternal {
+            _keys: vec![sep],
+            _children: vec![left, new],
+        }));
+        None
+    }
+    //删除，返回删除的值
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let mut path = vec![];
+        let x = self._find_leaf(key, Some(&mut path));
+        let leaf = self._leaf_mut(x);
+        let i = leaf._keys.binary_search(key).ok()?;
+        leaf._keys.remove(i);
+        let val = leaf._vals.remove(i);
+        self._len -= 1;
+        //沿路径向上处理不足B-1个key的节点
+        let mut x = x;
+        while let Some((p, i)) = path.pop() {
+            if self._node(x)._keys().len() >= B - 1 {
+                break;
+            }
+            self._rebalance(p, i);
+            x = p;
+        }
+        //根的最后一个key被合并下去后，唯一的子节点成为新根，树高减1
+        if let BPNode::Internal(root) = self._node(self._root) {
+            if root._keys.is_empty() {
+                let child = root._children[0];
+                self._dealloc(self._root);
+                self._root = child;
+            }
+        }
+        Some(val)
+    }
+    //parent的第i个子节点不足B-1个key，从兄弟借一个，兄弟都不够借时与兄弟合并
+    fn _rebalance(&mut self, parent: u32, i: usize) {
+        let p = self._internal(parent);
+        let x = p._children[i];
+        let left = if i > 0 { p._children[i - 1] } else { NIL };
+        let right = p._children.get(i + 1).copied().unwrap_or(NIL);
+        let spare = |s: u32| s != NIL && self._node(s)._keys().len() >= B;
+        if spare(left) {
+            self._borrow_from_left(parent, i, left, x);
+        } else if spare(right) {
+            self._borrow_from_right(parent, i, x, right);
+        } else if left != NIL {
+            self._merge(parent, i - 1, left, x);
+        } else {
+            self._merge(parent, i, x, right);
+        }
+    }
+    fn _borrow_from_left(&mut self, parent: u32, i: usize, left: u32, x: u32) {
+        match self._node(x) {
+            BPNode::Leaf(_) => {
+                let l = self._leaf_mut(left);
+                let (k, v) = (l._keys.pop().unwrap(), l._vals.pop().unwrap());
+                self._internal_mut(parent)._keys[i - 1] = k.clone();
+                let leaf = self._leaf_mut(x);
+                leaf._keys.insert(0, k);
+                leaf._vals.insert(0, v);
+            }
+            //左兄弟的最大key上移，父节点的分隔key下移
+            BPNode::Internal(_) => {
+                let l = self._internal_mut(left);
+                let (k, c) = (l._keys.pop().unwrap(), l._children.pop().unwrap());
+                let sep = mem::replace(&mut self._internal_mut(parent)._keys[i - 1], k);
+                let n = self._internal_mut(x);
+                n._keys.insert(0, sep);
+                n._children.insert(0, c);
+            }
+        }
+    }
+    fn _borrow_from_right(&mut self, parent: u32, i: usize, x: u32, right: u32) {
+        match self._node(x) {
+            BPNode::Leaf(_) => {
+                let r = self._leaf_mut(right);
+                let (k, v) = (r._keys.remove(0), r._vals.remove(0));
+                let sep = r._keys[0].clone();
+                self._internal_mut(parent)._keys[i] = sep;
+                let leaf = self._leaf_mut(x);
+                leaf._keys.push(k);
+                leaf._vals.push(v);
+            }
+            //右兄弟的最小key上移，父节点的分隔key下移
+            BPNode::Internal(_) => {
+                let r = self._internal_mut(right);
+                let (k, c) = (r._keys.remove(0), r._children.remove(0));
+                let sep = mem::replace(&mut self._internal_mut(parent)._keys[i], k);
+                let n = self._internal_mut(x);
+                n._keys.push(sep);
+                n._children.push(c);
+            }
+        }
+    }
+    //把parent的第i+1个子节点right并入第i个子节点left，并删掉二者之间的分隔key
+    fn _merge(&mut self, parent: u32, i: usize, left: u32, right: u32) {
+        let p = self._internal_mut(parent);
+        let sep = p._keys.remove(i);
+        p._children.remove(i + 1);
+        match self._dealloc(right) {
+            BPNode::Leaf(r) => {
+                let l = self._leaf_mut(left);
+                l._keys.extend(r._keys);
+                l._vals.extend(r._vals);
+                l._next = r._next;
+                if r._next != NIL {
+                    self._leaf_mut(r._next)._prev = left;
+                }
+            }
+            BPNode::Internal(r) => {
+                let l = self._internal_mut(left);
+                l._keys.push(sep);
+                l._keys.extend(r._keys);
+                l._children.extend(r._children);
+            }
+        }
+    }
+}
+
+//把n个元素分成尽量平均的若干组，每组不超过cap个
+fn _chunk_sizes(n: usize, cap: usize) -> impl Iterator<Item = usize> {
+    let groups = n.div_ceil(cap);
+    (0..groups).map(move |i| n / groups + usize::from(i < n % groups))
+}
+
+/// 范围迭代器，沿叶子链表前进，终点直接借用调用者给出的范围
+pub struct Range<'a, K, V, const B: usize = 16, R = RangeFull> {
+    _tree: &'a BPlusTree<K, V, B>,
+    _leaf: u32,
+    //当前叶子中下一个要访问的位置
+    _index: usize,
+    _range: R,
+}
+
+impl<'a, K: Ord, V, const B: usize, R: RangeBounds<K>> Iterator for Range<'a, K, V, B, R> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self._leaf != NIL {
+            let leaf = self._tree._leaf(self._leaf);
+            if self._index == leaf._keys.len() {
+                self._leaf = leaf._next;
+                self._index = 0;
+                continue;
+            }
+            let key = &leaf._keys[self._index];
+            let in_range = match self._range.end_bound() {
+                Bound::Included(e) => key <= e,
+                Bound::Excluded(e) => key < e,
+                Bound::Unbounded => true,
+            };
+            if !in_range {
+                self._leaf = NIL;
+                return None;
+            }
+            self._index += 1;
+            return Some((key, &leaf._vals[self._index - 1]));
+        }
+        None
+    }
+}
+
+impl<'a, K: Ord, V, const B: usize> IntoIterator for &'a BPlusTree<K, V, B> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Range<'a, K, V, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Ord + Debug, V: Debug, const B: usize> Debug for BPlusTree<K, V, B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+impl<K: Ord + Debug, V, const B: usize> BPlusTree<K, V, B> {
+    //检查key数、分隔key、叶子同层以及叶子链表，返回树高
+    fn _check(&self) -> usize {
+        let mut leaves = vec![];
+        let height = self._check_node(self._root, true, None, None, &mut leaves);
+        let mut prev = NIL;
+        for &x in &leaves {
+            assert_eq!(self._leaf(x)._prev, prev);
+            if prev != NIL {
+                assert_eq!(self._leaf(prev)._next, x);
+            }
+            prev = x;
+        }
+        assert_eq!(self._leaf(prev)._next, NIL);
+        let len: usize = leaves.iter().map(|&x| self._leaf(x)._keys.len()).sum();
+        assert_eq!(len, self._len);
+        height
+    }
+    fn _check_node(
+        &self,
+        x: u32,
+        is_root: bool,
+        lo: Option<&K>,
+        hi: Option<&K>,
+        leaves: &mut Vec<u32>,
+    ) -> usize {
+        let keys = self._node(x)._keys();
+        assert!(keys.len() <= Self::CAPACITY);
+        assert!(is_root || keys.len() >= B - 1, "underflow: {:?}", keys);
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+        for k in keys {
+            assert!(lo.is_none_or(|lo| lo <= k) && hi.is_none_or(|hi| k < hi));
+        }
+        let n = match self._node(x) {
+            BPNode::Leaf(l) => {
+                assert_eq!(l._vals.len(), keys.len());
+                leaves.push(x);
+                return 1;
+            }
+            BPNode::Internal(n) => n,
+        };
+        assert!(!keys.is_empty());
+        assert_eq!(n._children.len(), keys.len() + 1);
+        let heights: Vec<usize> = n
+            ._children
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| {
+                let lo = if i == 0 { lo } else { Some(&keys[i - 1]) };
+                let hi = keys.get(i).or(hi);
+                self._check_node(c, false, lo, hi, leaves)
+            })
+            .collect();
+        assert!(heights.windows(2).all(|w| w[0] == w[1]));
+        heights[0] + 1
+    }
+}
+
+#[test]
+fn test() {
+    use crate::common::rand::XorShift;
+    use std::collections::BTreeMap;
+
+    fn run<const B: usize>() {
+        let mut tree: BPlusTree<usize, usize, B> = BPlusTree::new();
+        let mut map = BTreeMap::new();
+        let mut rng = XorShift::new(B as u64 + 100);
+        for i in 0..20_000 {
+            let k = rng.gen_range(3000);
+            if rng.gen_range(3) == 0 {
+                assert_eq!(tree.remove(&k), map.remove(&k));
+            } else {
+                assert_eq!(tree.insert(k, i), map.insert(k, i));
+            }
+            assert_eq!(tree.len(), map.len());
+        }
+        tree._check();
+        assert!(tree.iter().eq(map.iter()));
+        for k in 0..3000 {
+            assert_eq!(tree.get(&k), map.get(&k));
+        }
+        for _ in 0..100 {
+            let x = rng.gen_range(3100);
+            let y = rng.gen_range(3100);
+            let (a, b) = (x.min(y), x.max(y));
+            assert!(tree.range(a..b).eq(map.range(a..b)));
+            assert!(tree.range(a..=b).eq(map.range(a..=b)));
+            assert!(tree
+                .range((Bound::Excluded(a), Bound::Unbounded))
+                .eq(map.range((Bound::Excluded(a), Bound::Unbounded))));
+        }
+        //全部删除后树高回到1，节点位置都被回收
+        for k in 0..3000 {
+            assert_eq!(tree.remove(&k), map.remove(&k));
+        }
+        tree._check();
+        assert_eq!(tree.height(), 1);
+        assert_eq!(tree._nodes.len() - tree._free.len(), 1);
+    }
+    run::<2>();
+    run::<3>();
+    run::<16>();
+}
+
+#[test]
+fn test_bulk_load() {
+    for n in [0, 1, 3, 4, 5, 31, 32, 33, 1000] {
+        let tree: BPlusTree<usize, usize, 2> =
+            BPlusTree::from_sorted_iter((0..n).map(|k| (k, k * 2)));
+        tree._check();
+        assert_eq!(tree.len(), n);
+        assert!(tree
+            .iter()
+            .map(|(k, v)| (*k, *v))
+            .eq((0..n).map(|k| (k, k * 2))));
+    }
+    //相邻的相等key保留最后一个，构造后仍可正常增删
+    let mut tree: BPlusTree<i32, &str> =
+        BPlusTree::from_sorted_iter([(1, "a"), (1, "b"), (2, "c")]);
+    assert_eq!(tree.get(&1), Some(&"b"));
+    for k in 3..500 {
+        tree.insert(k, "d");
+    }
+    for k in (1..500).step_by(2) {
+        tree.remove(&k);
+    }
+    tree._check();
+    assert_eq!(tree.len(), 249);
+}
+
+#[test]
+#[should_panic(expected = "not sorted")]
+fn test_bulk_load_unsorted() {
+    let _: BPlusTree<i32, ()> = BPlusTree::from_sorted_iter([(2, ()), (1, ())]);
+}
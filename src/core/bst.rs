@@ -1,24 +1,27 @@
-use std::{
-    cell::RefCell,
-    fmt::{Debug, Display},
-    rc::Rc,
-};
+use std::fmt::{Debug, Display};
 
-use crate::common::node::{BoxEntity, BoxNode};
+use crate::common::{
+    node::{
+        BinaryTree, BoxEntity, BoxNode, InOrder, LevelOrder, PostOrder, PreOrder, ReversalInOrder,
+        ReversalNode, ReversalTree,
+    },
+    viz::TreeView,
+};
 
-//二叉搜索树
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct BST<T> {
+/// 二叉搜索树(不做平衡)，作为有序集合使用
+/// Clone、PartialEq、Debug都不递归，树退化成链表时也不会爆栈
+pub struct BST<T> {
     _root: InnerBSTNode<T>,
+    _len: usize,
 }
 
 type InnerBSTNode<T> = Option<Box<BSTNode<T>>>;
 
-#[derive(Clone, Eq, PartialEq)]
+//节点只持有子节点，父节点由BST从根向下查找(见get_parent)
+//之前的_parent是插入时父节点的一份拷贝，既不随树变化，又使每次插入都复制整棵子树
 pub struct BSTNode<T> {
     _left: InnerBSTNode<T>,
     _right: InnerBSTNode<T>,
-    _parent: Option<Rc<RefCell<BSTNode<T>>>>,
     _val: Option<T>,
 }
 
@@ -27,7 +30,6 @@ impl<T> BSTNode<T> {
         Self {
             _left: None,
             _right: None,
-            _parent: None,
             _val: Some(val),
         }
     }
@@ -35,7 +37,6 @@ impl<T> BSTNode<T> {
         Self {
             _left: None,
             _right: None,
-            _parent: None,
             _val: opt_val,
         }
     }
@@ -47,28 +48,6 @@ impl<T> BSTNode<T> {
     }
 }
 
-impl<T: PartialEq> BSTNode<T> {
-    //修剪掉当前节点
-    pub fn prune(&self) {
-        //获取父节点，对比父节点的左和右节点，哪个相等就删除哪个
-        match &self._parent {
-            Some(p) => {
-                let p_clone = p.clone();
-                //若左边为空，说明右边肯定不为空
-                if let Some(l) = &p.borrow_mut()._left {
-                    //若左边不等，说明右边也不为空
-                    if *self == **l {
-                        p_clone.borrow_mut()._left = None;
-                    }
-                } else {
-                    p_clone.borrow_mut()._right = None;
-                }
-            }
-            None => return,
-        }
-    }
-}
-
 impl<T: Clone + Ord> BSTNode<T> {
     //嫁接node
     pub fn graft(&mut self, node: Self) {
@@ -77,22 +56,16 @@ impl<T: Clone + Ord> BSTNode<T> {
             self._insert_node(child);
         }
     }
-    fn _insert_node(&mut self, mut node: Self) {
+    fn _insert_node(&mut self, node: Self) {
         match node._val.cmp(&self._val) {
             std::cmp::Ordering::Equal => return,
             std::cmp::Ordering::Less => match self._left.as_mut() {
                 Some(x) => x._insert_node(node),
-                None => {
-                    node._parent = Some(Rc::new(RefCell::new(self.clone())));
-                    self._left = Some(Box::new(node));
-                }
+                None => self._left = Some(Box::new(node)),
             },
             std::cmp::Ordering::Greater => match self._right.as_mut() {
                 Some(x) => x._insert_node(node),
-                None => {
-                    node._parent = Some(Rc::new(RefCell::new(self.clone())));
-                    self._right = Some(Box::new(node));
-                }
+                None => self._right = Some(Box::new(node)),
             },
         }
     }
@@ -125,49 +98,6 @@ impl<T: Clone> BSTNode<T> {
         self._get_children(&mut res);
         res
     }
-    //获取父节点
-    pub fn get_parent(&self) -> Option<Self> {
-        match &self._parent {
-            Some(v) => Some(v.borrow().clone()),
-            None => None,
-        }
-    }
-    //先序遍历
-    pub fn pre_order(&self, res: &mut Vec<T>) {
-        if let Some(v) = &self._val {
-            res.push(v.clone());
-        }
-        if let Some(v) = &self._left {
-            v.pre_order(res);
-        }
-        if let Some(v) = &self._right {
-            v.pre_order(res);
-        }
-    }
-    //中序遍历
-    pub fn in_order(&self, res: &mut Vec<T>) {
-        if let Some(v) = &self._left {
-            v.pre_order(res);
-        }
-        if let Some(v) = &self._val {
-            res.push(v.clone());
-        }
-        if let Some(v) = &self._right {
-            v.pre_order(res);
-        }
-    }
-    //后续遍历
-    pub fn post_order(&self, res: &mut Vec<T>) {
-        if let Some(v) = &self._left {
-            v.pre_order(res);
-        }
-        if let Some(v) = &self._right {
-            v.pre_order(res);
-        }
-        if let Some(v) = &self._val {
-            res.push(v.clone());
-        }
-    }
 }
 
 impl<T> BST<T> {
@@ -175,7 +105,71 @@ impl<T> BST<T> {
     pub fn new(val: T) -> Self {
         Self {
             _root: Some(Box::new(BSTNode::new(val))),
+            _len: 1,
+        }
+    }
+    //长度
+    pub fn len(&self) -> usize {
+        self._len
+    }
+    //为空返回true
+    pub fn is_empty(&self) -> bool {
+        self._len == 0
+    }
+    //清空
+    pub fn clear(&mut self) {
+        _drop_nodes(self._root.take());
+        self._len = 0;
+    }
+    //层数，空树为0
+    pub fn height(&self) -> usize {
+        let mut height = 0;
+        let mut level: Vec<&BSTNode<T>> = self._root.as_deref().into_iter().collect();
+        while !level.is_empty() {
+            height += 1;
+            level = level
+                .iter()
+                .flat_map(|x| [x._left.as_deref(), x._right.as_deref()])
+                .flatten()
+                .collect();
+        }
+        height
+    }
+    //最小值
+    pub fn min(&self) -> Option<&T> {
+        let mut cur = self._root.as_deref()?;
+        while let Some(l) = cur._left.as_deref() {
+            cur = l;
         }
+        cur.get_val()
+    }
+    //最大值
+    pub fn max(&self) -> Option<&T> {
+        let mut cur = self._root.as_deref()?;
+        while let Some(r) = cur._right.as_deref() {
+            cur = r;
+        }
+        cur.get_val()
+    }
+    //先序遍历
+    pub fn pre_order(&self) -> PreOrder<'_, Self> {
+        self.pre_order_iter()
+    }
+    //中序遍历，即从小到大
+    pub fn in_order(&self) -> InOrder<'_, Self> {
+        self.in_order_iter()
+    }
+    //后序遍历
+    pub fn post_order(&self) -> PostOrder<'_, Self> {
+        self.post_order_iter()
+    }
+    //层序遍历
+    pub fn level_order(&self) -> LevelOrder<'_, Self> {
+        self.level_order_iter()
+    }
+    //从小到大遍历
+    pub fn iter(&self) -> InOrder<'_, Self> {
+        self.in_order_iter()
     }
 }
 impl<T: Debug> BST<T> {
@@ -187,72 +181,227 @@ impl<T: Debug> BST<T> {
     }
 }
 impl<T: Clone + Ord> BST<T> {
-    //嫁接
+    //嫁接node及其子孙中的所有值
     pub fn graft(&mut self, node: BSTNode<T>) {
-        if let Some(x) = self._root.as_mut() {
-            x.graft(node);
+        for child in node.get_children() {
+            if let Some(v) = child._val {
+                self.insert(v);
+            }
         }
     }
-    //插入数据
-    pub fn insert(&mut self, val: T) {
-        let node = BSTNode::new(val);
-        if let Some(x) = self._root.as_mut() {
-            x._insert_node(node);
+}
+impl<T: Ord> BST<T> {
+    //额外空间O(1)的中序遍历，遍历期间临时反转链接，返回值的拷贝
+    //不是Morris遍历: 上行时靠比较值的大小判断来自哪一侧，依赖树中的值互不相同(insert不插入重复值)
+    pub fn reversal_in_order(&mut self) -> ReversalInOrder<'_, Self> {
+        ReversalInOrder::new(self)
+    }
+    //t所在的链接(父节点的_left/_right或_root)，不存在时为指向空位置的链接
+    fn _link_mut(&mut self, t: &T) -> &mut InnerBSTNode<T> {
+        let mut cur = &mut self._root;
+        while let Some(ord) = cur.as_ref().map(|x| Some(t).cmp(&x._val.as_ref())) {
+            cur = match ord {
+                std::cmp::Ordering::Equal => break,
+                std::cmp::Ordering::Less => &mut cur.as_mut().unwrap()._left,
+                std::cmp::Ordering::Greater => &mut cur.as_mut().unwrap()._right,
+            };
+        }
+        cur
+    }
+    //是否包含t
+    pub fn contains(&self, t: &T) -> bool {
+        self.get(t).is_some()
+    }
+    //树中与t相等的值
+    pub fn get(&self, t: &T) -> Option<&T> {
+        let mut cur = self._root.as_deref();
+        while let Some(x) = cur {
+            cur = match Some(t).cmp(&x._val.as_ref()) {
+                std::cmp::Ordering::Equal => return x.get_val(),
+                std::cmp::Ordering::Less => x._left.as_deref(),
+                std::cmp::Ordering::Greater => x._right.as_deref(),
+            };
+        }
+        None
+    }
+    //t所在节点的深度(根为0)，不存在时返回None
+    pub fn depth(&self, t: &T) -> Option<usize> {
+        let mut depth = 0;
+        let mut cur = self._root.as_deref();
+        while let Some(x) = cur {
+            cur = match Some(t).cmp(&x._val.as_ref()) {
+                std::cmp::Ordering::Equal => return Some(depth),
+                std::cmp::Ordering::Less => x._left.as_deref(),
+                std::cmp::Ordering::Greater => x._right.as_deref(),
+            };
+            depth += 1;
+        }
+        None
+    }
+    //插入数据，已存在时返回false
+    pub fn insert(&mut self, val: T) -> bool {
+        let link = self._link_mut(&val);
+        if link.is_some() {
+            return false;
+        }
+        *link = Some(Box::new(BSTNode::new(val)));
+        self._len += 1;
+        true
+    }
+    //删除，返回删除的值
+    //有两个子节点时用右子树的最小节点代替被删除的节点
+    pub fn remove(&mut self, t: &T) -> Option<T> {
+        let link = self._link_mut(t);
+        let mut node = link.take()?;
+        *link = match (node._left.take(), node._right.take()) {
+            (None, r) => r,
+            (l, None) => l,
+            (l, mut r) => {
+                let mut min = &mut r;
+                while min.as_ref().unwrap()._left.is_some() {
+                    min = &mut min.as_mut().unwrap()._left;
+                }
+                let mut m = min.take().unwrap();
+                *min = m._right.take();
+                m._left = l;
+                m._right = r;
+                Some(m)
+            }
+        };
+        self._len -= 1;
+        node._val
+    }
+    //t所在节点的父节点，t为根或不存在时返回None
+    pub fn get_parent(&self, t: &T) -> Option<&BSTNode<T>> {
+        let mut parent = None;
+        let mut cur = self._root.as_deref();
+        while let Some(x) = cur {
+            cur = match Some(t).cmp(&x._val.as_ref()) {
+                std::cmp::Ordering::Equal => return parent,
+                std::cmp::Ordering::Less => x._left.as_deref(),
+                std::cmp::Ordering::Greater => x._right.as_deref(),
+            };
+            parent = Some(x);
+        }
+        None
+    }
+    //修剪掉t所在的节点(连同其子树)
+    pub fn prune(&mut self, t: &T) {
+        let pruned = self._link_mut(t).take();
+        self._len -= _drop_nodes(pruned);
+    }
+}
+
+//逐个释放节点，返回释放的个数，避免树退化成链表时递归drop爆栈
+fn _drop_nodes<T>(root: InnerBSTNode<T>) -> usize {
+    let mut count = 0;
+    let mut stack: Vec<Box<BSTNode<T>>> = root.into_iter().collect();
+    while let Some(mut x) = stack.pop() {
+        stack.extend(x._left.take());
+        stack.extend(x._right.take());
+        count += 1;
+    }
+    count
+}
+
+//后序遍历复制子树，子节点的拷贝先压入done，处理父节点时弹出
+fn _clone_nodes<T: Clone>(root: Option<&BSTNode<T>>) -> Option<BSTNode<T>> {
+    let mut stack: Vec<(&BSTNode<T>, bool)> = root.map(|x| (x, false)).into_iter().collect();
+    let mut done: Vec<BSTNode<T>> = vec![];
+    while let Some((x, visited)) = stack.pop() {
+        if visited {
+            let right = x._right.as_ref().and_then(|_| done.pop()).map(Box::new);
+            let left = x._left.as_ref().and_then(|_| done.pop()).map(Box::new);
+            done.push(BSTNode {
+                _left: left,
+                _right: right,
+                _val: x._val.clone(),
+            });
         } else {
-            self._root = Some(Box::new(node));
+            stack.push((x, true));
+            stack.extend(x._right.as_deref().map(|r| (r, false)));
+            stack.extend(x._left.as_deref().map(|l| (l, false)));
         }
     }
-    //删除
-    pub fn delete(&mut self, val: &T) {
-        let mut datas = self.pre_order();
-        //排序方便找到合适的根节点
-        datas.sort();
-        //新根节点数据
-        let len = datas.len();
-        let r_data = datas[len / 2].clone();
-        //删除val元素和根节点数据(即保留不等于val并且不等于r_data的)
-        datas.retain(|x| x != val && x != &r_data);
-        //若没有此元素
-        if datas.len() == len {
-            return;
-        }
-        let mut new_tree = Self::new(r_data);
-        for data in datas {
-            new_tree.insert(data);
+    done.pop()
+}
+
+//用栈成对比较两棵子树的形状和值
+fn _eq_nodes<T: PartialEq>(a: Option<&BSTNode<T>>, b: Option<&BSTNode<T>>) -> bool {
+    let mut stack = vec![(a, b)];
+    while let Some(pair) = stack.pop() {
+        match pair {
+            (None, None) => {}
+            (Some(x), Some(y)) => {
+                if x._val != y._val {
+                    return false;
+                }
+                stack.push((x._left.as_deref(), y._left.as_deref()));
+                stack.push((x._right.as_deref(), y._right.as_deref()));
+            }
+            _ => return false,
         }
-        //使用新生成的树复制给self(相当于重排序)
-        *self = new_tree;
     }
+    true
 }
-impl<T: PartialEq> BST<T> {
-    pub fn prune(&mut self) {
-        // self._root
+
+impl<T: Clone> Clone for BSTNode<T> {
+    fn clone(&self) -> Self {
+        _clone_nodes(Some(self)).unwrap()
     }
 }
-impl<T: Clone> BST<T> {
-    //先序遍历
-    pub fn pre_order(&self) -> Vec<T> {
-        let mut res = vec![];
-        if let Some(v) = &self._root {
-            v.pre_order(&mut res);
+
+impl<T: Clone> Clone for BST<T> {
+    fn clone(&self) -> Self {
+        Self {
+            _root: _clone_nodes(self._root.as_deref()).map(Box::new),
+            _len: self._len,
         }
-        res
     }
-    //中序遍历
-    pub fn in_order(&self) -> Vec<T> {
-        let mut res = vec![];
-        if let Some(v) = &self._root {
-            v.in_order(&mut res);
-        }
-        res
+}
+
+//形状和值都相同才相等
+impl<T: PartialEq> PartialEq for BSTNode<T> {
+    fn eq(&self, other: &Self) -> bool {
+        _eq_nodes(Some(self), Some(other))
     }
-    //后续遍历
-    pub fn post_order(&self) -> Vec<T> {
-        let mut res = vec![];
-        if let Some(v) = &self._root {
-            v.post_order(&mut res);
-        }
-        res
+}
+
+impl<T: Eq> Eq for BSTNode<T> {}
+
+impl<T: PartialEq> PartialEq for BST<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self._len == other._len && _eq_nodes(self._root.as_deref(), other._root.as_deref())
+    }
+}
+
+impl<T: Eq> Eq for BST<T> {}
+
+//按中序输出子树的值
+impl<T: Debug> Debug for BSTNode<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.in_order_iter()).finish()
+    }
+}
+
+impl<T: Debug> Debug for BST<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a BST<T> {
+    type Item = &'a T;
+    type IntoIter = InOrder<'a, BST<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> Drop for BST<T> {
+    fn drop(&mut self) {
+        _drop_nodes(self._root.take());
     }
 }
 
@@ -291,25 +440,11 @@ impl<T: Ord> BoxEntity<T> for BST<T> {
     }
 }
 
-//只打印parent
-impl<T: Debug> Debug for BSTNode<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?} => ", self._val)?;
-        if let Some(x) = &self._parent {
-            return Debug::fmt(&x.borrow(), f);
-        }
-        write!(f, "None")
-    }
-}
 //打印子节点
 impl<T: Debug> Display for BSTNode<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?} => ", self._val)?;
-        //打印父节点，需注释下面
-        // if let Some(x) = &self._parent {
-        //     return Display::fmt(&x.borrow(), f);
-        // }
-        //打印左右节点，需注释上面
+        //打印左右节点
         if let Some(x) = &self._left {
             Display::fmt(x.as_ref(), f);
         }
@@ -329,9 +464,95 @@ impl<T: Debug> Display for BST<T> {
     }
 }
 
+impl<T> BinaryTree for BST<T> {
+    type Node<'a>
+        = &'a BSTNode<T>
+    where
+        Self: 'a;
+    type Value = T;
+
+    fn root(&self) -> Option<&BSTNode<T>> {
+        self._root.as_deref()
+    }
+    fn left<'a>(&'a self, x: &'a BSTNode<T>) -> Option<&'a BSTNode<T>> {
+        x._left.as_deref()
+    }
+    fn right<'a>(&'a self, x: &'a BSTNode<T>) -> Option<&'a BSTNode<T>> {
+        x._right.as_deref()
+    }
+    fn value<'a>(&'a self, x: &'a BSTNode<T>) -> Option<&'a T> {
+        x.get_val()
+    }
+}
+
+//以该节点为根的子树
+impl<T> BinaryTree for BSTNode<T> {
+    type Node<'a>
+        = &'a BSTNode<T>
+    where
+        Self: 'a;
+    type Value = T;
+
+    fn root(&self) -> Option<&BSTNode<T>> {
+        Some(self)
+    }
+    fn left<'a>(&'a self, x: &'a BSTNode<T>) -> Option<&'a BSTNode<T>> {
+        x._left.as_deref()
+    }
+    fn right<'a>(&'a self, x: &'a BSTNode<T>) -> Option<&'a BSTNode<T>> {
+        x._right.as_deref()
+    }
+    fn value<'a>(&'a self, x: &'a BSTNode<T>) -> Option<&'a T> {
+        x.get_val()
+    }
+}
+
+impl<T: Ord> ReversalNode for BSTNode<T> {
+    type Key = Option<T>;
+    type Value = T;
+
+    fn reversal_link(&mut self, left: bool) -> &mut Option<Box<Self>> {
+        if left {
+            &mut self._left
+        } else {
+            &mut self._right
+        }
+    }
+    fn reversal_key(&self) -> &Option<T> {
+        &self._val
+    }
+    fn reversal_value(&self) -> Option<&T> {
+        self.get_val()
+    }
+}
+
+impl<T: Ord> ReversalTree for BST<T> {
+    type Node = BSTNode<T>;
+
+    fn reversal_detach(&mut self) -> (InnerBSTNode<T>, usize) {
+        (self._root.take(), std::mem::take(&mut self._len))
+    }
+    fn reversal_attach(&mut self, root: InnerBSTNode<T>, len: usize) {
+        self._root = root;
+        self._len = len;
+    }
+}
+
+impl<T: Debug> TreeView for BST<T> {
+    fn view_label(&self, x: &BSTNode<T>) -> String {
+        match &x._val {
+            Some(v) => format!("{:?}", v),
+            None => "None".to_string(),
+        }
+    }
+}
+
 impl<T> Default for BST<T> {
     fn default() -> Self {
-        Self { _root: None }
+        Self {
+            _root: None,
+            _len: 0,
+        }
     }
 }
 
@@ -349,3 +570,182 @@ fn test() {
     // let datas = bst.post_order();
     // println!("datas: {:?}", datas);
 }
+
+#[test]
+fn test_leak() {
+    use crate::common::counted::Counted;
+    use std::{cell::Cell, rc::Rc};
+
+    let live = Rc::new(Cell::new(0));
+    let mut bst = BST::new(Counted::new(50, &live));
+    for i in [30, 70, 20, 40, 60, 80] {
+        bst.insert(Counted::new(i, &live));
+    }
+    //插入时不再复制父节点
+    assert_eq!(live.get(), 7);
+    let parent = bst.get_parent(&Counted::new(40, &live));
+    assert_eq!(parent.and_then(|p| p.get_val()).map(|v| v.val()), Some(30));
+    assert!(bst.get_parent(&Counted::new(50, &live)).is_none());
+    bst.prune(&Counted::new(70, &live));
+    assert_eq!(live.get(), 4);
+    bst.remove(&Counted::new(20, &live));
+    assert_eq!(live.get(), 3);
+    drop(bst);
+    assert_eq!(live.get(), 0);
+}
+
+#[test]
+fn test_ops() {
+    use crate::common::rand::XorShift;
+    use std::collections::BTreeSet;
+
+    let mut bst = BST::default();
+    let mut set = BTreeSet::new();
+    let mut rng = XorShift::new(13);
+    for _ in 0..2000 {
+        let v = rng.gen_range(500) as i32;
+        if rng.gen_range(3) == 0 {
+            assert_eq!(bst.remove(&v), set.take(&v));
+        } else {
+            assert_eq!(bst.insert(v), set.insert(v));
+        }
+        assert_eq!(bst.len(), set.len());
+    }
+    assert!(bst.iter().eq(set.iter()));
+    assert_eq!(bst.min(), set.first());
+    assert_eq!(bst.max(), set.last());
+    for v in 0..500 {
+        assert_eq!(bst.contains(&v), set.contains(&v));
+        assert_eq!(bst.get(&v), set.get(&v));
+    }
+
+    //      4
+    //    2   6
+    //   1 3 5
+    let mut bst = BST::new(4);
+    for v in [2, 6, 1, 3, 5] {
+        bst.insert(v);
+    }
+    assert_eq!(
+        bst.pre_order().copied().collect::<Vec<_>>(),
+        [4, 2, 1, 3, 6, 5]
+    );
+    assert_eq!(
+        bst.in_order().copied().collect::<Vec<_>>(),
+        [1, 2, 3, 4, 5, 6]
+    );
+    assert_eq!(
+        bst.post_order().copied().collect::<Vec<_>>(),
+        [1, 3, 2, 5, 6, 4]
+    );
+    assert_eq!(
+        bst.level_order().copied().collect::<Vec<_>>(),
+        [4, 2, 6, 1, 3, 5]
+    );
+    assert_eq!(bst.height(), 3);
+    //以某个节点为根的子树同样可以遍历
+    let node = bst.get_node(&2).unwrap();
+    assert_eq!(node.post_order_iter().copied().collect::<Vec<_>>(), [1, 3, 2]);
+    assert_eq!(bst.remove(&4), Some(4));
+    assert_eq!(
+        bst.level_order().copied().collect::<Vec<_>>(),
+        [5, 2, 6, 1, 3]
+    );
+    bst.clear();
+    assert!(bst.is_empty());
+    assert_eq!(bst.height(), 0);
+
+    //顺序插入退化为链表，drop不应爆栈
+    let mut bst = BST::default();
+    for v in 0..20_000 {
+        bst.insert(v);
+    }
+    assert_eq!(bst.height(), 20_000);
+    assert_eq!(bst.post_order().next(), Some(&19_999));
+}
+
+#[test]
+fn test_deep_chain() {
+    //顺序插入退化为链表，Clone、PartialEq、Debug都不应爆栈
+    let mut bst = BST::default();
+    for v in 0..20_000 {
+        bst.insert(v);
+    }
+
+    let copy = bst.clone();
+    assert_eq!(copy.len(), 20_000);
+    assert_eq!(copy.height(), 20_000);
+    assert!(copy.iter().copied().eq(0..20_000));
+    //节点的拷贝放回树中，借助树的drop释放
+    let node = BST {
+        _root: Some(Box::new(bst._root.as_deref().unwrap().clone())),
+        _len: 20_000,
+    };
+    assert_eq!(node.height(), 20_000);
+
+    assert!(bst == copy);
+    assert!(bst == node);
+    assert!(bst._root == copy._root);
+    let mut other = copy.clone();
+    other.remove(&19_999);
+    other.insert(20_000);
+    assert!(bst != other);
+    assert!(bst._root != other._root);
+    //值相同但形状不同
+    let mut rev = BST::default();
+    for v in (0..20_000).rev() {
+        rev.insert(v);
+    }
+    assert!(bst != rev);
+
+    let s = format!("{:?}", bst);
+    assert!(s.starts_with("{0, 1, 2, "));
+    assert!(s.ends_with(", 19998, 19999}"));
+    assert_eq!(format!("{:?}", bst._root.as_deref().unwrap()), s);
+
+    //链接反转遍历同样不递归，中途停止后链接被恢复
+    assert!(bst.reversal_in_order().eq(0..20_000));
+    assert_eq!(bst.reversal_in_order().nth(10_000), Some(10_000));
+    assert!(bst == copy);
+    assert!(rev.reversal_in_order().eq(0..20_000));
+    assert!(rev.iter().copied().eq(0..20_000));
+}
+
+#[test]
+fn test_reversal() {
+    let mut bst = BST::default();
+    assert_eq!(bst.reversal_in_order().next(), None);
+    for v in [5, 2, 8, 1, 3, 7, 9, 4, 6] {
+        bst.insert(v);
+    }
+    let copy = bst.clone();
+    assert!(bst.reversal_in_order().eq(1..=9));
+    for n in 0..9 {
+        assert_eq!(bst.reversal_in_order().nth(n), Some(n as i32 + 1));
+        assert!(bst == copy);
+    }
+    //迭代器被forget时树变为空树，仍可继续使用
+    let mut it = bst.reversal_in_order();
+    assert_eq!(it.nth(3), Some(4));
+    std::mem::forget(it);
+    assert!(bst.is_empty() && bst.iter().next().is_none());
+    bst.insert(1);
+    assert_eq!(bst.reversal_in_order().collect::<Vec<_>>(), [1]);
+}
+
+#[test]
+fn test_viz() {
+    let mut bst = BST::new(4);
+    for v in [2, 6, 1, 5, 7] {
+        bst.insert(v);
+    }
+    let expected = ["    ┌── 7", "┌── 6", "│   └── 5", "4", "└── 2", "    └── 1"];
+    assert_eq!(bst.to_ascii(), expected.join("\n") + "\n");
+    let dot = bst.to_dot();
+    assert!(dot.starts_with("digraph {"));
+    //6个节点，2只有左子节点，右侧补一个点
+    assert_eq!(dot.matches("[label=").count(), 6);
+    assert_eq!(dot.matches("[shape=point]").count(), 1);
+    assert_eq!(dot.matches(" -> ").count(), 6);
+    assert_eq!(BST::<i32>::default().to_ascii(), "");
+}
@@ -0,0 +1,530 @@
+use std::{
+    fmt::Debug,
+    mem,
+    ops::{Bound, RangeBounds, RangeFull},
+};
+
+/// B树，MIN_DEGREE为最小度数t：除根外每个节点有t-1..=2t-1个key，内部节点有key数+1个子节点
+/// 扇出(子节点数的上限)为2t，默认t为6即每个节点最多12个子节点
+/// 同一节点的key连续存放，一次比较多个key，比每个元素一个节点的树更利于缓存
+/// 插入时自上而下预先分裂满节点，删除时自上而下预先补足将要进入的子节点，都只需一趟
+pub struct BTree<K, V, const MIN_DEGREE: usize = 6> {
+    _root: BTreeNode<K, V>,
+    _len: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct BTreeNode<K, V> {
+    _keys: Vec<K>,
+    _vals: Vec<V>,
+    //叶子节点为空
+    _children: Vec<BTreeNode<K, V>>,
+}
+
+impl<K, V> BTreeNode<K, V> {
+    fn _with_capacity(cap: usize) -> Self {
+        Self {
+            _keys: Vec::with_capacity(cap),
+            _vals: Vec::with_capacity(cap),
+            _children: vec![],
+        }
+    }
+    pub fn keys(&self) -> &[K] {
+        &self._keys
+    }
+    pub fn children(&self) -> impl Iterator<Item = &Self> {
+        self._children.iter()
+    }
+    pub fn is_leaf(&self) -> bool {
+        self._children.is_empty()
+    }
+}
+
+impl<K: Clone, V: Clone, const MIN_DEGREE: usize> Clone for BTree<K, V, MIN_DEGREE> {
+    fn clone(&self) -> Self {
+        Self {
+            _root: self._root.clone(),
+            _len: self._len,
+        }
+    }
+}
+
+impl<K, V, const MIN_DEGREE: usize> Default for BTree<K, V, MIN_DEGREE> {
+    fn default() -> Self {
+        let () = Self::_CHECK;
+        Self {
+            _root: BTreeNode::_with_capacity(Self::CAPACITY),
+            _len: 0,
+        }
+    }
+}
+
+impl<K, V, const MIN_DEGREE: usize> BTree<K, V, MIN_DEGREE> {
+    //每个节点最多的key数
+    pub const CAPACITY: usize = 2 * MIN_DEGREE - 1;
+    const _CHECK: () = assert!(MIN_DEGREE >= 2, "MIN_DEGREE must be at least 2");
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+    //长度
+    pub fn len(&self) -> usize {
+        self._len
+    }
+    //为空返回true
+    pub fn is_empty(&self) -> bool {
+        self._len == 0
+    }
+    //清空
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+    //根节点
+    pub fn root(&self) -> &BTreeNode<K, V> {
+        &self._root
+    }
+    //层数，只有根时为1
+    pub fn height(&self) -> usize {
+        let mut height = 1;
+        let mut x = &self._root;
+        while let Some(c) = x._children.first() {
+            x = c;
+            height += 1;
+        }
+        height
+    }
+    //按key从小到大遍历
+    pub fn iter(&self) -> Range<'_, K, V> {
+        let mut it = Range {
+            _stack: vec![],
+            _range: ..,
+        };
+        it._push_left(&self._root, |_| false);
+        it
+    }
+
+    //分裂x的第i个子节点(须已满)，中间的key上移到x
+    fn _split_child(x: &mut BTreeNode<K, V>, i: usize) {
+        let child = &mut x._children[i];
+        let mut right = BTreeNode::_with_capacity(Self::CAPACITY);
+        right._keys.extend(child._keys.drain(MIN_DEGREE..));
+        right._vals.extend(child._vals.drain(MIN_DEGREE..));
+        if !child.is_leaf() {
+            right._children.extend(child._children.drain(MIN_DEGREE..));
+        }
+        let key = child._keys.pop().unwrap();
+        let val = child._vals.pop().unwrap();
+        x._keys.insert(i, key);
+        x._vals.insert(i, val);
+        x._children.insert(i + 1, right);
+    }
+    //合并x的第i、i+1个子节点(都只有MIN_DEGREE-1个key)，x的第i个key下移到中间
+    fn _merge_children(x: &mut BTreeNode<K, V>, i: usize) {
+        let right = x._children.remove(i + 1);
+        let key = x._keys.remove(i);
+        let val = x._vals.remove(i);
+        let left = &mut x._children[i];
+        left._keys.push(key);
+        left._vals.push(val);
+        left._keys.extend(right._keys);
+        left._vals.extend(right._vals);
+        left._children.extend(right._children);
+    }
+    //保证x的第i个子节点至少有B个key，返回补足后要进入的子节点下标
+    //优先从左右兄弟借一个，兄弟都只有MIN_DEGREE-1个时与兄弟合并
+    fn _fill_child(x: &mut BTreeNode<K, V>, i: usize) -> usize {
+        if x._children[i]._keys.len() >= MIN_DEGREE {
+            return i;
+        }
+        if i > 0 && x._children[i - 1]._keys.len() >= MIN_DEGREE {
+            //左兄弟的最大key上移，x的第i-1个key下移到子节点最前
+            let (left, right) = x._children.split_at_mut(i);
+            let (left, child) = (&mut left[i - 1], &mut right[0]);
+            let key = mem::replace(&mut x._keys[i - 1], left._keys.pop().unwrap());
+            let val = mem::replace(&mut x._vals[i - 1], left._vals.pop().unwrap());
+            child._keys.insert(0, key);
+            child._vals.insert(0, val);
+            if let Some(c) = left._children.pop() {
+                child._children.insert(0, c);
+            }
+            return i;
+        }
+        if i < x._keys.len() && x._children[i + 1]._keys.len() >= MIN_DEGREE {
+            //右兄弟的最小key上移，x的第i个key下移到子节点最后
+            let (left, right) = x._children.split_at_mut(i + 1);
+            let (child, right) = (&mut left[i], &mut right[0]);
+            let key = mem::replace(&mut x._keys[i], right._keys.remove(0));
+            let val = mem::replace(&mut x._vals[i], right._vals.remove(0));
+            child._keys.push(key);
+            child._vals.push(val);
+            if !right.is_leaf() {
+                child._children.push(right._children.remove(0));
+            }
+            return i;
+        }
+        if i < x._keys.len() {
+            Self::_merge_children(x, i);
+            i
+        } else {
+            Self::_merge_children(x, i - 1);
+            i - 1
+        }
+    }
+    //删除子树中最小(first为true)或最大的key
+    fn _remove_end(mut x: &mut BTreeNode<K, V>, first: bool) -> (K, V) {
+        while !x.is_leaf() {
+            let i = if first { 0 } else { x._keys.len() };
+            let i = Self::_fill_child(x, i);
+            x = &mut x._children[i];
+        }
+        if first {
+            (x._keys.remove(0), x._vals.remove(0))
+        } else {
+            (x._keys.pop().unwrap(), x._vals.pop().unwrap())
+        }
+    }
+}
+
+impl<K: Ord, V, const MIN_DEGREE: usize> BTree<K, V, MIN_DEGREE> {
+    //是否包含key
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+    //查找
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut x = &self._root;
+        loop {
+            match x._keys.binary_search(key) {
+                Ok(i) => return Some(&x._vals[i]),
+                Err(i) => x = x._children.get(i)?,
+            }
+        }
+    }
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut x = &mut self._root;
+        loop {
+            match x._keys.binary_search(key) {
+                Ok(i) => return Some(&mut x._vals[i]),
+                Err(i) => x = x._children.get_mut(i)?,
+            }
+        }
+    }
+    //插入，key已存在时替换并返回旧值
+    pub fn insert(&mut self, key: K, val: V) -> Option<V> {
+        //根满时先分裂，树高加1
+        if self._root._keys.len() == Self::CAPACITY {
+            let root = BTreeNode::_with_capacity(Self::CAPACITY);
+            let old = mem::replace(&mut self._root, root);
+            self._root._children.push(old);
+            Self::_split_child(&mut self._root, 0);
+        }
+        let mut x = &mut self._root;
+        loop {
+            let mut i = match x._keys.binary_search(&key) {
+                Ok(i) => return Some(mem::replace(&mut x._vals[i], val)),
+                Err(i) => i,
+            };
+            if x.is_leaf() {
+                x._keys.insert(i, key);
+                x._vals.insert(i, val);
+                self._len += 1;
+                return None;
+            }
+            //要进入的子节点已满则先分裂，上移的key可能正好等于key
+            if x._children[i]._keys.len() == Self::CAPACITY {
+                Self::_split_child(x, i);
+                match key.cmp(&x._keys[i]) {
+                    std::cmp::Ordering::Equal => {
+                        return Some(mem::replace(&mut x._vals[i], val));
+                    }
+                    std::cmp::Ordering::Greater => i += 1,
+                    std::cmp::Ordering::Less => {}
+                }
+            }
+            x = &mut x._children[i];
+        }
+    }
+    //删除，返回删除的值
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let removed = Self::_remove(&mut self._root, key);
+        //根的key被合并下去后，唯一的子节点成为新根，树高减1
+        if self._root._keys.is_empty() {
+            if let Some(child) = self._root._children.pop() {
+                self._root = child;
+            }
+        }
+        if removed.is_some() {
+            self._len -= 1;
+        }
+        removed
+    }
+    fn _remove(mut x: &mut BTreeNode<K, V>, key: &K) -> Option<V> {
+        loop {
+            match x._keys.binary_search(key) {
+                Ok(i) if x.is_leaf() => {
+                    x._keys.remove(i);
+                    return Some(x._vals.remove(i));
+                }
+                //内部节点用前驱或后继替换，子节点都不够时合并后在合并的节点中继续删除
+                Ok(i) => {
+                    if x._children[i]._keys.len() >= MIN_DEGREE {
+                        let (k, v) = Self::_remove_end(&mut x._children[i], false);
+                        x._keys[i] = k;
+                        return Some(mem::replace(&mut x._vals[i], v));
+                    }
+                    if x._children[i + 1]._keys.len() >= MIN_DEGREE {
+                        let (k, v) = Self::_remove_end(&mut x._children[i + 1], true);
+                        x._keys[i] = k;
+                        return Some(mem::replace(&mut x._vals[i], v));
+                    }
+                    Self::_merge_children(x, i);
+                    x = &mut x._children[i];
+                }
+                Err(_) if x.is_leaf() => return None,
+                Err(i) => {
+                    let i = Self::_fill_child(x, i);
+                    x = &mut x._children[i];
+                }
+            }
+        }
+    }
+    //按范围从小到大遍历
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, R> {
+        let mut it = Range {
+            _stack: vec![],
+            _range: ..,
+        };
+        match range.start_bound() {
+            Bound::Included(s) => it._push_left(&self._root, |k| k < s),
+            Bound::Excluded(s) => it._push_left(&self._root, |k| k <= s),
+            Bound::Unbounded => it._push_left(&self._root, |_| false),
+        }
+        Range {
+            _stack: it._stack,
+            _range: range,
+        }
+    }
+}
+
+/// 范围迭代器，栈中为(节点, 该节点下一个要访问的key下标)，子节点在父节点之上
+/// 持有调用者给出的范围，终点直接借用，key不必Clone
+pub struct Range<'a, K, V, R = RangeFull> {
+    _stack: Vec<(&'a BTreeNode<K, V>, usize)>,
+    _range: R,
+}
+
+impl<'a, K, V, R> Range<'a, K, V, R> {
+    //从x往下，每层跳过before为true的key，压入起始位置
+    fn _push_left<F: Fn(&K) -> bool>(&mut self, mut x: &'a BTreeNode<K, V>, before: F) {
+        loop {
+            let i = x._keys.partition_point(&before);
+            self._stack.push((x, i));
+            match x._children.get(i) {
+                Some(c) => x = c,
+                None => return,
+            }
+        }
+    }
+}
+
+impl<'a, K: Ord, V, R: RangeBounds<K>> Iterator for Range<'a, K, V, R> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (x, i) = self._stack.pop()?;
+            if i == x._keys.len() {
+                continue;
+            }
+            let key = &x._keys[i];
+            let in_range = match self._range.end_bound() {
+                Bound::Included(e) => key <= e,
+                Bound::Excluded(e) => key < e,
+                Bound::Unbounded => true,
+            };
+            if !in_range {
+                self._stack.clear();
+                return None;
+            }
+            //先压回本节点的下一个位置，再压入右边子树的最左链
+            self._stack.push((x, i + 1));
+            if let Some(c) = x._children.get(i + 1) {
+                self._push_left(c, |_| false);
+            }
+            return Some((key, &x._vals[i]));
+        }
+    }
+}
+
+impl<'a, K: Ord, V, const MIN_DEGREE: usize> IntoIterator for &'a BTree<K, V, MIN_DEGREE> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Range<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Ord + Debug, V: Debug, const MIN_DEGREE: usize> Debug for BTree<K, V, MIN_DEGREE> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+//检查key数、有序性和所有叶子同层，返回子树高度
+fn _check<K: Ord + Debug, V, const MIN_DEGREE: usize>(
+    x: &BTreeNode<K, V>,
+    is_root: bool,
+    lo: Option<&K>,
+    hi: Option<&K>,
+) -> usize {
+    let n = x._keys.len();
+    assert!(n <= BTree::<K, V, MIN_DEGREE>::CAPACITY);
+    assert!(is_root || n >= MIN_DEGREE - 1, "underflow: {:?}", x._keys);
+    assert_eq!(x._vals.len(), n);
+    assert!(x._keys.windows(2).all(|w| w[0] < w[1]));
+    assert!(lo.is_none_or(|lo| lo < &x._keys[0]));
+    assert!(hi.is_none_or(|hi| &x._keys[n - 1] < hi));
+    if x.is_leaf() {
+        return 1;
+    }
+    assert_eq!(x._children.len(), n + 1);
+    let heights: Vec<usize> = (0..=n)
+        .map(|i| {
+            let lo = if i == 0 { lo } else { Some(&x._keys[i - 1]) };
+            let hi = if i == n { hi } else { Some(&x._keys[i]) };
+            _check::<K, V, MIN_DEGREE>(&x._children[i], false, lo, hi)
+        })
+        .collect();
+    assert!(heights.windows(2).all(|w| w[0] == w[1]));
+    heights[0] + 1
+}
+
+#[test]
+fn test() {
+    use crate::common::rand::XorShift;
+    use std::collections::BTreeMap;
+
+    fn run<const MIN_DEGREE: usize>() {
+        let mut tree: BTree<usize, usize, MIN_DEGREE> = BTree::new();
+        let mut map = BTreeMap::new();
+        let mut rng = XorShift::new(MIN_DEGREE as u64);
+        for i in 0..20_000 {
+            let k = rng.gen_range(3000);
+            if rng.gen_range(3) == 0 {
+                assert_eq!(tree.remove(&k), map.remove(&k));
+            } else {
+                assert_eq!(tree.insert(k, i), map.insert(k, i));
+            }
+            assert_eq!(tree.len(), map.len());
+        }
+        if !tree.is_empty() {
+            _check::<usize, usize, MIN_DEGREE>(tree.root(), true, None, None);
+        }
+        assert!(tree.iter().eq(map.iter()));
+        for k in 0..3000 {
+            assert_eq!(tree.get(&k), map.get(&k));
+        }
+        for _ in 0..100 {
+            let x = rng.gen_range(3100);
+            let y = rng.gen_range(3100);
+            let (a, b) = (x.min(y), x.max(y));
+            assert!(tree.range(a..b).eq(map.range(a..b)));
+            assert!(tree.range(a..=b).eq(map.range(a..=b)));
+            assert!(tree
+                .range((Bound::Excluded(a), Bound::Unbounded))
+                .eq(map.range((Bound::Excluded(a), Bound::Unbounded))));
+        }
+        //全部删除后树高回到1
+        for k in 0..3000 {
+            assert_eq!(tree.remove(&k), map.remove(&k));
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.height(), 1);
+    }
+    run::<2>();
+    run::<3>();
+    run::<6>();
+    run::<32>();
+
+    let mut tree: BTree<i32, &str> = BTree::new();
+    tree.insert(1, "a");
+    *tree.get_mut(&1).unwrap() = "b";
+    assert_eq!(tree.get(&1), Some(&"b"));
+    assert_eq!(format!("{:?}", tree), r#"{1: "b"}"#);
+
+    //范围查询只借用终点，key不必Clone
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct Key(i32);
+    let mut tree: BTree<Key, (), 2> = BTree::new();
+    for k in 0..20 {
+        tree.insert(Key(k), ());
+    }
+    assert!(tree.range(Key(5)..Key(8)).map(|(k, _)| k.0).eq(5..8));
+}
+
+//cargo test --release bench_btree -- --ignored --nocapture
+//100万个随机u64，单线程，release下的一次结果:
+//  RBTree:            insert 1.84s,  lookup 1.28s,  delete 1.87s
+//  BTree(扇出12):     insert 789ms,  lookup 785ms,  delete 893ms
+//  BTreeMap:          insert 457ms,  lookup 557ms,  delete 568ms
+#[test]
+#[ignore]
+fn bench_btree() {
+    use super::rb_tree::RBTree;
+    use crate::common::rand::XorShift;
+    use std::{collections::BTreeMap, time::Instant};
+
+    const N: usize = 1_000_000;
+    let mut rng = XorShift::new(1);
+    let keys = (0..N).map(|_| rng.next_u64()).collect::<Vec<_>>();
+
+    let start = Instant::now();
+    let mut rbt = RBTree::default();
+    for k in &keys {
+        rbt.insert(*k);
+    }
+    let insert = start.elapsed();
+    let start = Instant::now();
+    assert!(keys.iter().all(|k| rbt.contains(k)));
+    let lookup = start.elapsed();
+    let start = Instant::now();
+    for k in &keys {
+        rbt.delete(k);
+    }
+    let delete = start.elapsed();
+    println!("RBTree:            insert {insert:?}, lookup {lookup:?}, delete {delete:?}");
+
+    let start = Instant::now();
+    let mut tree: BTree<u64, ()> = BTree::new();
+    for k in &keys {
+        tree.insert(*k, ());
+    }
+    let insert = start.elapsed();
+    let start = Instant::now();
+    assert!(keys.iter().all(|k| tree.contains_key(k)));
+    let lookup = start.elapsed();
+    let start = Instant::now();
+    for k in &keys {
+        tree.remove(k);
+    }
+    let delete = start.elapsed();
+    println!("BTree(扇出12):     insert {insert:?}, lookup {lookup:?}, delete {delete:?}");
+
+    let start = Instant::now();
+    let mut map = BTreeMap::new();
+    for k in &keys {
+        map.insert(*k, ());
+    }
+    let insert = start.elapsed();
+    let start = Instant::now();
+    assert!(keys.iter().all(|k| map.contains_key(k)));
+    let lookup = start.elapsed();
+    let start = Instant::now();
+    for k in &keys {
+        map.remove(k);
+    }
+    let delete = start.elapsed();
+    println!("BTreeMap:          insert {insert:?}, lookup {lookup:?}, delete {delete:?}");
+}
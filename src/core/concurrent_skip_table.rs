@@ -0,0 +1,775 @@
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    fmt::Debug,
+    hint,
+    marker::PhantomData,
+    ptr,
+    sync::atomic::{
+        fence, AtomicBool, AtomicPtr, AtomicU64, AtomicUsize, Ordering as AtomicOrdering,
+    },
+    thread,
+};
+
+//最大层数
+const _MAX_LEVEL: usize = 16;
+//每退休这么多个节点尝试回收一次
+const _COLLECT_EVERY: usize = 64;
+
+/// 无锁并发跳表(有序集合)，可在多线程间共享(&self即可插入删除)
+/// 按Herlihy&Shavit的LockFreeSkipList实现：
+/// - next指针的最低位作为删除标记，删除时自顶向下标记各层，标记最底层成功的线程即删除成功
+/// - 被标记的节点由之后经过的查找用CAS摘除
+/// - 节点记录自己还挂在几层上，全部摘除且插入线程放手后交给epoch回收
+///
+/// 需要裸指针(unsafe)，只在开启lockfree feature时编译
+pub struct ConcurrentSkipTable<T> {
+    //头哨兵，每层都有
+    _head: Box<CNode<T>>,
+    _len: AtomicUsize,
+    //生成随机层数的状态
+    _seed: AtomicU64,
+    _epoch: Collector<T>,
+    //屏蔽自动推导的Send/Sync，按T手动实现
+    _marker: PhantomData<*mut T>,
+}
+
+struct CNode<T> {
+    //头哨兵为None
+    _val: Option<T>,
+    //第i层的下一个节点，最低位为删除标记，长度即节点层数
+    _next: Box<[AtomicPtr<CNode<T>>]>,
+    //已链接的层数，加上插入线程持有的1
+    _refs: AtomicUsize,
+}
+
+impl<T> CNode<T> {
+    fn new(val: Option<T>, level: usize) -> Self {
+        Self {
+            _val: val,
+            _next: (0..level)
+                .map(|_| AtomicPtr::new(ptr::null_mut()))
+                .collect(),
+            _refs: AtomicUsize::new(1),
+        }
+    }
+    //第0层被标记即已逻辑删除
+    fn marked(&self) -> bool {
+        _is_marked(self._next[0].load(AtomicOrdering::Acquire))
+    }
+}
+
+// SAFETY: 节点间共享的只有原子指针，T在线程间移动、共享，分别要求Send、Send+Sync
+unsafe impl<T: Send> Send for ConcurrentSkipTable<T> {}
+unsafe impl<T: Send + Sync> Sync for ConcurrentSkipTable<T> {}
+
+impl<T> ConcurrentSkipTable<T> {
+    pub fn new() -> Self {
+        Self::with_seed(0x2545_F491_4F6C_DD1D)
+    }
+    //设置随机种子
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            _head: Box::new(CNode::new(None, _MAX_LEVEL)),
+            _len: AtomicUsize::new(0),
+            _seed: AtomicU64::new(seed),
+            _epoch: Collector::new(),
+            _marker: PhantomData,
+        }
+    }
+    //长度(并发修改时为近似值)
+    pub fn len(&self) -> usize {
+        self._len.load(AtomicOrdering::Acquire)
+    }
+    //为空返回true
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    //随机层数，晋升概率为1/2
+    fn _random_level(&self) -> usize {
+        //splitmix64
+        let mut z = self
+            ._seed
+            .fetch_add(0x9E37_79B9_7F4A_7C15, AtomicOrdering::Relaxed);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z.trailing_ones() as usize + 1).min(_MAX_LEVEL)
+    }
+
+    fn _head_ptr(&self) -> *mut CNode<T> {
+        &*self._head as *const CNode<T> as *mut CNode<T>
+    }
+
+    //节点少挂了一层(或插入线程放手)，归零时交给epoch回收
+    //调用者需保证node是guard期间从跳表读到(或本线程插入)的节点，且确实摘掉了一层(或放手)
+    unsafe fn _release(&self, node: *mut CNode<T>, guard: &Guard<'_>) {
+        // SAFETY: 由调用者保证node在guard期间有效
+        let n = unsafe { _deref(node, guard) };
+        if n._refs.fetch_sub(1, AtomicOrdering::AcqRel) == 1 {
+            self._epoch.retire(node);
+        }
+    }
+}
+
+impl<T: Ord> ConcurrentSkipTable<T> {
+    //自顶向下查找每层的前驱与后继，途中摘除已标记的节点，返回最底层是否找到t
+    fn _find(
+        &self,
+        t: &T,
+        preds: &mut [*mut CNode<T>; _MAX_LEVEL],
+        succs: &mut [*mut CNode<T>; _MAX_LEVEL],
+        guard: &Guard<'_>,
+    ) -> bool {
+        let mut backoff = Backoff::new();
+        'retry: loop {
+            //pred是头哨兵或本次查找在guard期间从链上读到的节点
+            let mut pred = self._head_ptr();
+            for level in (0.._MAX_LEVEL).rev() {
+                // SAFETY: 见pred
+                let p = unsafe { _deref(pred, guard) };
+                let mut cur = _unmark(p._next[level].load(AtomicOrdering::Acquire));
+                while !cur.is_null() {
+                    // SAFETY: cur在guard期间从pred或已摘除节点冻结的next读到，读到时仍在链上
+                    let c = unsafe { _deref(cur, guard) };
+                    let succ = c._next[level].load(AtomicOrdering::Acquire);
+                    if _is_marked(succ) {
+                        //cur已被删除，从这一层摘除；前驱也被删除时CAS失败，从头再来
+                        // SAFETY: 见pred
+                        if unsafe { _deref(pred, guard) }._next[level]
+                            .compare_exchange(
+                                cur,
+                                _unmark(succ),
+                                AtomicOrdering::AcqRel,
+                                AtomicOrdering::Acquire,
+                            )
+                            .is_err()
+                        {
+                            backoff.spin();
+                            continue 'retry;
+                        }
+                        // SAFETY: 本线程的CAS把cur从这一层摘掉，每层只会摘一次
+                        unsafe { self._release(cur, guard) };
+                        cur = _unmark(succ);
+                        continue;
+                    }
+                    if _cmp(c, t) == Ordering::Less {
+                        pred = cur;
+                        cur = succ;
+                    } else {
+                        break;
+                    }
+                }
+                preds[level] = pred;
+                succs[level] = cur;
+            }
+            // SAFETY: succs[0]在guard期间从链上读到
+            return !succs[0].is_null()
+                && _cmp(unsafe { _deref(succs[0], guard) }, t) == Ordering::Equal;
+        }
+    }
+
+    //是否包含t
+    pub fn contains(&self, t: &T) -> bool {
+        let guard = self._epoch.pin();
+        let (mut preds, mut succs) = ([ptr::null_mut(); _MAX_LEVEL], [ptr::null_mut(); _MAX_LEVEL]);
+        self._find(t, &mut preds, &mut succs, &guard)
+    }
+
+    //插入，已存在时返回false
+    pub fn insert(&self, val: T) -> bool {
+        let guard = self._epoch.pin();
+        let (mut preds, mut succs) = ([ptr::null_mut(); _MAX_LEVEL], [ptr::null_mut(); _MAX_LEVEL]);
+        let level = self._random_level();
+        let node = Box::into_raw(Box::new(CNode::new(Some(val), level)));
+        // SAFETY: node刚由Box分配，发布前只有本线程持有；
+        // 发布后即使被删除，也要等节点计数中本线程持有的1放手、且guard放手后才会释放
+        let n = unsafe { _deref(node, &guard) };
+        let t = n._val.as_ref().unwrap();
+        //先计入第0层，链接失败时不必撤回
+        n._refs.fetch_add(1, AtomicOrdering::Relaxed);
+        //先计数，避免并发删除先减导致下溢
+        self._len.fetch_add(1, AtomicOrdering::AcqRel);
+        let mut backoff = Backoff::new();
+        loop {
+            if self._find(t, &mut preds, &mut succs, &guard) {
+                self._len.fetch_sub(1, AtomicOrdering::AcqRel);
+                // SAFETY: node还未发布，只有本线程持有
+                drop(unsafe { Box::from_raw(node) });
+                return false;
+            }
+            n._next[0].store(succs[0], AtomicOrdering::Relaxed);
+            //最底层链接成功即插入成功
+            // SAFETY: preds由本次_find在guard期间得到
+            if unsafe { _deref(preds[0], &guard) }._next[0]
+                .compare_exchange(
+                    succs[0],
+                    node,
+                    AtomicOrdering::AcqRel,
+                    AtomicOrdering::Acquire,
+                )
+                .is_ok()
+            {
+                break;
+            }
+            backoff.spin();
+        }
+
+        //逐层向上链接，节点被删除(next被标记)时停止
+        'link: for l in 1..level {
+            loop {
+                let old = n._next[l].load(AtomicOrdering::Acquire);
+                if _is_marked(old)
+                    || n._next[l]
+                        .compare_exchange(
+                            old,
+                            succs[l],
+                            AtomicOrdering::AcqRel,
+                            AtomicOrdering::Acquire,
+                        )
+                        .is_err()
+                {
+                    break 'link;
+                }
+                n._refs.fetch_add(1, AtomicOrdering::Relaxed);
+                // SAFETY: preds由_find在guard期间得到
+                if unsafe { _deref(preds[l], &guard) }._next[l]
+                    .compare_exchange(
+                        succs[l],
+                        node,
+                        AtomicOrdering::AcqRel,
+                        AtomicOrdering::Acquire,
+                    )
+                    .is_ok()
+                {
+                    break;
+                }
+                //持有插入线程的1，不会归零
+                n._refs.fetch_sub(1, AtomicOrdering::Relaxed);
+                backoff.spin();
+                self._find(t, &mut preds, &mut succs, &guard);
+                if n.marked() {
+                    break 'link;
+                }
+            }
+        }
+        //链接期间被删除时，可能在删除线程摘除之后又挂上了某层，再查找一遍摘掉
+        if n.marked() {
+            self._find(t, &mut preds, &mut succs, &guard);
+        }
+        // SAFETY: node由本线程插入，放手插入时持有的1
+        unsafe { self._release(node, &guard) };
+        true
+    }
+}
+
+impl<T: Ord + Clone> ConcurrentSkipTable<T> {
+    //获取与t相等的值
+    pub fn get(&self, t: &T) -> Option<T> {
+        let guard = self._epoch.pin();
+        let (mut preds, mut succs) = ([ptr::null_mut(); _MAX_LEVEL], [ptr::null_mut(); _MAX_LEVEL]);
+        if self._find(t, &mut preds, &mut succs, &guard) {
+            // SAFETY: _find返回true时succs[0]是guard期间从链上读到的节点
+            unsafe { _deref(succs[0], &guard) }._val.clone()
+        } else {
+            None
+        }
+    }
+
+    //删除t，返回删除的值
+    pub fn remove(&self, t: &T) -> Option<T> {
+        let guard = self._epoch.pin();
+        let (mut preds, mut succs) = ([ptr::null_mut(); _MAX_LEVEL], [ptr::null_mut(); _MAX_LEVEL]);
+        if !self._find(t, &mut preds, &mut succs, &guard) {
+            return None;
+        }
+        // SAFETY: _find返回true时succs[0]是guard期间从链上读到的节点
+        let n = unsafe { _deref(succs[0], &guard) };
+        //自顶向下标记上层，此后插入线程不能再链接这些层
+        for l in (1..n._next.len()).rev() {
+            let mut next = n._next[l].load(AtomicOrdering::Acquire);
+            while !_is_marked(next) {
+                match n._next[l].compare_exchange_weak(
+                    next,
+                    _mark(next),
+                    AtomicOrdering::AcqRel,
+                    AtomicOrdering::Acquire,
+                ) {
+                    Ok(_) => break,
+                    Err(x) => next = x,
+                }
+            }
+        }
+        //标记最底层成功的线程完成删除
+        let mut next = n._next[0].load(AtomicOrdering::Acquire);
+        while !_is_marked(next) {
+            match n._next[0].compare_exchange_weak(
+                next,
+                _mark(next),
+                AtomicOrdering::AcqRel,
+                AtomicOrdering::Acquire,
+            ) {
+                Ok(_) => {
+                    self._len.fetch_sub(1, AtomicOrdering::AcqRel);
+                    let val = n._val.clone();
+                    //摘除各层
+                    self._find(t, &mut preds, &mut succs, &guard);
+                    return val;
+                }
+                Err(x) => next = x,
+            }
+        }
+        None
+    }
+
+    //从小到大遍历，只保证弱一致性(可能看到遍历期间的部分修改)
+    pub fn iter(&self) -> Iter<'_, T> {
+        let guard = self._epoch.pin();
+        let cur = _unmark(self._head._next[0].load(AtomicOrdering::Acquire));
+        Iter {
+            _guard: guard,
+            _cur: cur,
+        }
+    }
+}
+
+/// 并发跳表迭代器，返回值的拷贝
+/// 遍历期间一直pin住epoch，经过的节点不会被释放
+pub struct Iter<'a, T> {
+    _guard: Guard<'a>,
+    _cur: *mut CNode<T>,
+}
+
+impl<T: Clone> Iterator for Iter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self._cur.is_null() {
+            // SAFETY: _cur在_guard期间从头哨兵或上一个节点的next读到
+            let cur = unsafe { _deref(self._cur, &self._guard) };
+            let next = cur._next[0].load(AtomicOrdering::Acquire);
+            self._cur = _unmark(next);
+            if !_is_marked(next) {
+                return cur._val.clone();
+            }
+        }
+        None
+    }
+}
+
+//节点值与t比较
+fn _cmp<T: Ord>(node: &CNode<T>, t: &T) -> Ordering {
+    node._val.as_ref().expect("head node has no value").cmp(t)
+}
+
+fn _is_marked<T>(p: *mut T) -> bool {
+    p.addr() & 1 == 1
+}
+
+fn _mark<T>(p: *mut T) -> *mut T {
+    p.map_addr(|a| a | 1)
+}
+
+fn _unmark<T>(p: *mut T) -> *mut T {
+    p.map_addr(|a| a & !1)
+}
+
+//把节点指针转为引用，生命周期限制在guard内
+//调用者需保证p非空、未标记，且是头哨兵、本线程分配的节点，或guard期间从跳表读到的节点：
+//节点在所有层都摘除后才退休，退休后要等全局epoch前进两次才释放，
+//而guard存活期间全局epoch最多前进一次，所以读到时可达的节点在guard期间不会被释放
+unsafe fn _deref<'g, T>(p: *mut CNode<T>, _guard: &'g Guard<'_>) -> &'g CNode<T> {
+    // SAFETY: 由调用者保证
+    unsafe { &*p }
+}
+
+impl<T> Default for ConcurrentSkipTable<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//释放仍挂在任意一层上的节点(可能有已标记、尚未摘除的)，已退休的节点由Collector释放
+impl<T> Drop for ConcurrentSkipTable<T> {
+    fn drop(&mut self) {
+        let mut nodes = HashSet::new();
+        for l in 0.._MAX_LEVEL {
+            let mut cur = _unmark(self._head._next[l].load(AtomicOrdering::Relaxed));
+            while !cur.is_null() {
+                nodes.insert(cur);
+                // SAFETY: &mut self，没有其他线程访问，挂在链上的节点都未释放
+                cur = _unmark(unsafe { &*cur }._next[l].load(AtomicOrdering::Relaxed));
+            }
+        }
+        for node in nodes {
+            // SAFETY: 每个节点只收集一次
+            drop(unsafe { Box::from_raw(node) });
+        }
+    }
+}
+
+impl<T: Debug> Debug for ConcurrentSkipTable<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let guard = self._epoch.pin();
+        let mut set = f.debug_set();
+        let mut cur = _unmark(self._head._next[0].load(AtomicOrdering::Acquire));
+        while !cur.is_null() {
+            // SAFETY: cur在guard期间从头哨兵或上一个节点的next读到
+            let x = unsafe { _deref(cur, &guard) };
+            let next = x._next[0].load(AtomicOrdering::Acquire);
+            if !_is_marked(next) {
+                if let Some(v) = &x._val {
+                    set.entry(v);
+                }
+            }
+            cur = _unmark(next);
+        }
+        set.finish()
+    }
+}
+
+/// CAS失败后的退避：先自旋，次数逐次翻倍，超过上限后让出CPU
+struct Backoff {
+    _step: u32,
+}
+
+impl Backoff {
+    const _SPIN_LIMIT: u32 = 6;
+
+    fn new() -> Self {
+        Self { _step: 0 }
+    }
+    fn spin(&mut self) {
+        if self._step <= Self::_SPIN_LIMIT {
+            for _ in 0..1 << self._step {
+                hint::spin_loop();
+            }
+            self._step += 1;
+        } else {
+            thread::yield_now();
+        }
+    }
+}
+
+/// 基于epoch的内存回收
+/// 线程读节点前先pin住当前全局epoch，退休的节点记下当时的epoch，
+/// 全局epoch只有在所有pin住的线程都追上后才前进，前进两次后就不会再有线程持有该节点
+struct Collector<T> {
+    _global: AtomicUsize,
+    //参与者链表，只增不减，drop时统一释放
+    _locals: AtomicPtr<Local>,
+    //等待释放的节点，无锁栈
+    _garbage: AtomicPtr<Retired<T>>,
+    _retired: AtomicUsize,
+}
+
+struct Local {
+    //是否被某个guard占用
+    _in_use: AtomicBool,
+    //pin住的epoch左移一位，最低位为1表示pin住
+    _state: AtomicUsize,
+    //发布后不再修改
+    _next: *mut Local,
+}
+
+struct Retired<T> {
+    _node: *mut CNode<T>,
+    _epoch: usize,
+    _next: *mut Retired<T>,
+}
+
+/// pin住epoch的凭证，drop时放手
+struct Guard<'a> {
+    _local: &'a Local,
+}
+
+impl Drop for Guard<'_> {
+    fn drop(&mut self) {
+        self._local._state.store(0, AtomicOrdering::Release);
+        self._local._in_use.store(false, AtomicOrdering::Release);
+    }
+}
+
+impl<T> Collector<T> {
+    fn new() -> Self {
+        Self {
+            _global: AtomicUsize::new(0),
+            _locals: AtomicPtr::new(ptr::null_mut()),
+            _garbage: AtomicPtr::new(ptr::null_mut()),
+            _retired: AtomicUsize::new(0),
+        }
+    }
+
+    fn pin(&self) -> Guard<'_> {
+        let local = self._acquire_local();
+        let e = self._global.load(AtomicOrdering::SeqCst);
+        local._state.store(e << 1 | 1, AtomicOrdering::SeqCst);
+        // SAFETY(内存序): 之后读链表指针用的是Acquire，Acquire读可以被重排到
+        // 前面对另一个地址的SeqCst写之前。不加fence时线程可能先读到节点指针、
+        // 后让pin可见，_try_advance看到它未pin就连推两次epoch，_collect随即
+        // 释放它马上要解引用的节点。SeqCst fence保证pin先于后续所有读可见，
+        // 与crossbeam-epoch的做法一致
+        fence(AtomicOrdering::SeqCst);
+        Guard { _local: local }
+    }
+
+    //占用一个空闲的参与者，没有时新建
+    fn _acquire_local(&self) -> &Local {
+        let mut p = self._locals.load(AtomicOrdering::Acquire);
+        while !p.is_null() {
+            // SAFETY: 参与者只在Collector drop时释放
+            let l = unsafe { &*p };
+            if l._in_use
+                .compare_exchange(
+                    false,
+                    true,
+                    AtomicOrdering::Acquire,
+                    AtomicOrdering::Relaxed,
+                )
+                .is_ok()
+            {
+                return l;
+            }
+            p = l._next;
+        }
+        let l = Box::into_raw(Box::new(Local {
+            _in_use: AtomicBool::new(true),
+            _state: AtomicUsize::new(0),
+            _next: ptr::null_mut(),
+        }));
+        let mut head = self._locals.load(AtomicOrdering::Acquire);
+        loop {
+            // SAFETY: l还未发布
+            unsafe { (*l)._next = head };
+            match self._locals.compare_exchange_weak(
+                head,
+                l,
+                AtomicOrdering::AcqRel,
+                AtomicOrdering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(x) => head = x,
+            }
+        }
+        // SAFETY: 同上
+        unsafe { &*l }
+    }
+
+    //节点已从所有层摘除，等没有线程能持有它时释放
+    fn retire(&self, node: *mut CNode<T>) {
+        let r = Box::into_raw(Box::new(Retired {
+            _node: node,
+            _epoch: self._global.load(AtomicOrdering::SeqCst),
+            _next: ptr::null_mut(),
+        }));
+        self._push(r);
+        if self._retired.fetch_add(1, AtomicOrdering::Relaxed) % _COLLECT_EVERY
+            == _COLLECT_EVERY - 1
+        {
+            self._collect();
+        }
+    }
+
+    fn _push(&self, r: *mut Retired<T>) {
+        let mut head = self._garbage.load(AtomicOrdering::Acquire);
+        loop {
+            // SAFETY: r只被本线程持有
+            unsafe { (*r)._next = head };
+            match self._garbage.compare_exchange_weak(
+                head,
+                r,
+                AtomicOrdering::AcqRel,
+                AtomicOrdering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(x) => head = x,
+            }
+        }
+    }
+
+    //所有pin住的线程都在当前epoch时前进一次
+    fn _try_advance(&self) {
+        let e = self._global.load(AtomicOrdering::SeqCst);
+        let mut p = self._locals.load(AtomicOrdering::Acquire);
+        while !p.is_null() {
+            // SAFETY: 参与者只在Collector drop时释放
+            let l = unsafe { &*p };
+            let s = l._state.load(AtomicOrdering::SeqCst);
+            if s & 1 == 1 && s >> 1 != e {
+                return;
+            }
+            p = l._next;
+        }
+        let _ =
+            self._global
+                .compare_exchange(e, e + 1, AtomicOrdering::SeqCst, AtomicOrdering::SeqCst);
+    }
+
+    //整个取下待释放栈，释放够旧的节点，其余放回
+    fn _collect(&self) {
+        self._try_advance();
+        let e = self._global.load(AtomicOrdering::SeqCst);
+        let mut p = self._garbage.swap(ptr::null_mut(), AtomicOrdering::AcqRel);
+        while !p.is_null() {
+            // SAFETY: 取下的栈只有本线程持有
+            let r = unsafe { &mut *p };
+            let next = r._next;
+            if r._epoch + 2 <= e {
+                // SAFETY: 全局epoch已前进两次，没有线程还持有该节点
+                unsafe {
+                    drop(Box::from_raw(r._node));
+                    drop(Box::from_raw(p));
+                }
+            } else {
+                self._push(p);
+            }
+            p = next;
+        }
+    }
+}
+
+impl<T> Drop for Collector<T> {
+    fn drop(&mut self) {
+        let mut p = *self._garbage.get_mut();
+        while !p.is_null() {
+            // SAFETY: &mut self，没有线程再pin住
+            let r = unsafe { Box::from_raw(p) };
+            drop(unsafe { Box::from_raw(r._node) });
+            p = r._next;
+        }
+        let mut p = *self._locals.get_mut();
+        while !p.is_null() {
+            // SAFETY: 同上
+            let l = unsafe { Box::from_raw(p) };
+            p = l._next;
+        }
+    }
+}
+
+#[test]
+fn test() {
+    let st = ConcurrentSkipTable::new();
+    for i in [5, 3, 9, 1, 7] {
+        assert!(st.insert(i));
+    }
+    assert!(!st.insert(5));
+    println!("st={:?}", st);
+    assert_eq!(st.len(), 5);
+    assert!(st.contains(&7));
+    assert_eq!(st.get(&9), Some(9));
+    assert_eq!(st.remove(&7), Some(7));
+    assert_eq!(st.remove(&7), None);
+    assert_eq!(st.iter().collect::<Vec<_>>(), vec![1, 3, 5, 9]);
+}
+
+#[test]
+fn test_stress() {
+    const THREADS: usize = 8;
+    const N: usize = 2000;
+
+    let st = ConcurrentSkipTable::with_seed(11);
+    let inserted = AtomicUsize::new(0);
+    let removed = AtomicUsize::new(0);
+    thread::scope(|s| {
+        for id in 0..THREADS {
+            let (st, inserted, removed) = (&st, &inserted, &removed);
+            s.spawn(move || {
+                //相邻线程的键有一半重叠，插入删除互相竞争
+                let base = id * N / 2;
+                for k in base..base + N {
+                    if st.insert(k) {
+                        inserted.fetch_add(1, AtomicOrdering::Relaxed);
+                    }
+                    if k % 3 == 0 && st.remove(&k).is_some() {
+                        removed.fetch_add(1, AtomicOrdering::Relaxed);
+                    }
+                }
+                for k in (base..base + N).rev().step_by(5) {
+                    if st.remove(&k).is_some() {
+                        removed.fetch_add(1, AtomicOrdering::Relaxed);
+                    }
+                }
+            });
+        }
+    });
+
+    let vals = st.iter().collect::<Vec<_>>();
+    assert!(vals.windows(2).all(|w| w[0] < w[1]));
+    let expected = inserted.load(AtomicOrdering::Relaxed) - removed.load(AtomicOrdering::Relaxed);
+    assert_eq!(vals.len(), expected);
+    assert_eq!(st.len(), expected);
+    for v in &vals {
+        assert!(st.contains(v));
+        assert!(v % 3 != 0);
+    }
+}
+
+#[test]
+fn test_reclaim() {
+    use std::sync::Arc;
+
+    //同common::counted::Counted，存活数用原子计数以便跨线程
+    struct Tracked(usize, Arc<AtomicUsize>);
+    impl Tracked {
+        fn new(val: usize, live: &Arc<AtomicUsize>) -> Self {
+            live.fetch_add(1, AtomicOrdering::Relaxed);
+            Self(val, live.clone())
+        }
+    }
+    impl Clone for Tracked {
+        fn clone(&self) -> Self {
+            Self::new(self.0, &self.1)
+        }
+    }
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.1.fetch_sub(1, AtomicOrdering::Relaxed);
+        }
+    }
+    impl PartialEq for Tracked {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+    impl Eq for Tracked {}
+    impl PartialOrd for Tracked {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+    impl Ord for Tracked {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
+
+    const THREADS: usize = 4;
+    const N: usize = 1000;
+    let live = Arc::new(AtomicUsize::new(0));
+    let st = ConcurrentSkipTable::new();
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            let (st, live) = (&st, &live);
+            s.spawn(move || {
+                //所有线程争抢同一批键，反复插入删除
+                for round in 0..4 {
+                    for k in 0..N {
+                        st.insert(Tracked::new(k, live));
+                    }
+                    for k in (round % 2..N).step_by(2) {
+                        st.remove(&Tracked::new(k, live));
+                    }
+                }
+            });
+        }
+    });
+    let vals = st.iter().map(|x| x.0).collect::<Vec<_>>();
+    assert!(vals.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(vals.len(), st.len());
+    //已退休的节点延迟释放，存活数不少于元素数
+    assert!(live.load(AtomicOrdering::Relaxed) >= st.len());
+    //节点不泄漏也不重复释放
+    drop(st);
+    assert_eq!(live.load(AtomicOrdering::Relaxed), 0);
+}
@@ -1,7 +1,7 @@
 use std::{
     cell::RefCell,
     fmt::{Debug, Display},
-    rc::Rc,
+    rc::{Rc, Weak},
 };
 
 /// 双向链表
@@ -13,12 +13,14 @@ pub struct DList<T> {
 
 //rc起到指针的作用，为了使prev引的对象与上上一个的next的是同一个对象
 type DInnerNode<T> = Option<Rc<RefCell<DNode<T>>>>;
+//prev用Weak，否则相邻节点互相持有，整条链表永远不会被释放
+type DWeakNode<T> = Option<Weak<RefCell<DNode<T>>>>;
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone)]
 pub struct DNode<T> {
     _val: T,
     _next: DInnerNode<T>,
-    _prev: DInnerNode<T>,
+    _prev: DWeakNode<T>,
 }
 impl<T> DNode<T> {
     pub fn new(val: T) -> Self {
@@ -41,11 +43,8 @@ impl<T> DNode<T> {
     }
 
     //上一个节点
-    pub fn prev(&self) -> Option<&Rc<RefCell<Self>>> {
-        match &self._prev {
-            Some(v) => Some(v),
-            None => None,
-        }
+    pub fn prev(&self) -> Option<&Weak<RefCell<Self>>> {
+        self._prev.as_ref()
     }
 
     // 获取下一个节点的拷贝
@@ -55,11 +54,11 @@ impl<T> DNode<T> {
 
     // 获取上一个节点的拷贝
     pub fn prev_cp(&self) -> DInnerNode<T> {
-        self._prev.clone()
+        self._prev.as_ref().and_then(Weak::upgrade)
     }
 
     // 获取上一个节点的可变引用
-    pub fn prev_mut(&mut self) -> &mut DInnerNode<T> {
+    pub fn prev_mut(&mut self) -> &mut DWeakNode<T> {
         &mut self._prev
     }
 
@@ -71,6 +70,16 @@ impl<T> DNode<T> {
     pub fn get_mut(&mut self) -> &mut T {
         &mut self._val
     }
+    //从head开始逐个释放节点，避免长链表递归drop爆栈
+    //遇到仍被外部持有的节点(如get_node返回的Rc)时停止，剩余部分随外部引用一起释放
+    pub(crate) fn drop_chain(mut head: DInnerNode<T>) {
+        while let Some(x) = head {
+            head = match Rc::try_unwrap(x) {
+                Ok(node) => node.into_inner()._next,
+                Err(_) => break,
+            };
+        }
+    }
 }
 
 impl<T> DList<T> {
@@ -87,7 +96,7 @@ impl<T> DList<T> {
             let head = self._head.take().unwrap();
             new_node._next = Some(head.clone());
             let rc = Rc::new(RefCell::new(new_node));
-            head.borrow_mut()._prev = Some(rc.clone());
+            head.borrow_mut()._prev = Some(Rc::downgrade(&rc));
             self._head = Some(rc)
         } else {
             self._head = Some(Rc::new(RefCell::new(new_node)));
@@ -122,7 +131,7 @@ impl<T> DList<T> {
     }
     //清空
     pub fn clear(&mut self) {
-        self._head = None;
+        DNode::drop_chain(self._head.take());
         self._len = 0;
     }
 }
@@ -159,11 +168,11 @@ impl<T: Clone> DList<T> {
         if let Some(x) = mod_prev_node {
             //新节点赋值
             new_node._next = x.borrow()._next.clone();
-            new_node._prev = Some(x.clone());
+            new_node._prev = Some(Rc::downgrade(&x));
             let rc = Rc::new(RefCell::new(new_node));
             //右节点的prev赋值为新节点
             if let Some(y) = &x.borrow_mut()._next {
-                y.borrow_mut()._prev = Some(rc.clone());
+                y.borrow_mut()._prev = Some(Rc::downgrade(&rc));
             }
             //左节点的next赋值为新节点
             x.borrow_mut()._next = Some(rc.clone());
@@ -199,7 +208,7 @@ impl<T: Clone> DList<T> {
                 let right_node = m.borrow_mut()._next.take();
                 if let Some(r) = &right_node {
                     //设置右节点的prev为左节点
-                    r.borrow_mut()._prev = Some(l.clone());
+                    r.borrow_mut()._prev = Some(Rc::downgrade(&l));
                     //设置左节点的next为右节点
                     l.borrow_mut()._next = Some(r.clone());
                 } else {
@@ -223,6 +232,21 @@ impl<T> Default for DList<T> {
     }
 }
 
+impl<T> Drop for DList<T> {
+    fn drop(&mut self) {
+        DNode::drop_chain(self._head.take());
+    }
+}
+
+//只比较值和后续节点，prev与next成对出现无需再比
+impl<T: PartialEq> PartialEq for DNode<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self._val == other._val && self._next == other._next
+    }
+}
+
+impl<T: Eq> Eq for DNode<T> {}
+
 /// 不实现DNode的打印方法，会使得打印循环引用而爆栈
 impl<T: Debug> Display for DList<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -275,3 +299,36 @@ fn test() {
     println!("dl:{:?}", dl.get_node(1));
     println!("dl:{:?}", dl.get_node(dl.len() - 1));
 }
+
+#[test]
+fn test_leak() {
+    use crate::common::counted::Counted;
+    use std::cell::Cell;
+
+    let live = Rc::new(Cell::new(0));
+    let mut dl = DList::default();
+    for i in 0..100 {
+        dl.add_at_tail(Counted::new(i, &live));
+    }
+    assert_eq!(live.get(), 100);
+    dl.delete_head();
+    dl.delete_at_index(50);
+    dl.delete_at_index(dl.len() - 1);
+    assert_eq!(live.get(), dl.len());
+    dl.clear();
+    assert_eq!(live.get(), 0);
+
+    for i in 0..100 {
+        dl.add_at_head(Counted::new(i, &live));
+    }
+    drop(dl);
+    assert_eq!(live.get(), 0);
+
+    //长链表逐个释放，不会递归爆栈
+    let mut dl = DList::default();
+    for i in 0..200_000 {
+        dl.add_at_head(Counted::new(i, &live));
+    }
+    drop(dl);
+    assert_eq!(live.get(), 0);
+}
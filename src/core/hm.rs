@@ -1,34 +1,48 @@
-use crate::{common::errs::OUT_OF_RANGE, core::list::List};
+use crate::{
+    common::errs::OUT_OF_RANGE,
+    core::list::{List, Node},
+};
 use std::{
-    collections::hash_map::DefaultHasher,
+    collections::hash_map::RandomState,
     fmt::Debug,
-    hash::{Hash, Hasher},
+    hash::{BuildHasher, Hash},
+    mem,
     ops::{Index, IndexMut},
+    slice, vec,
 };
 
 //扩容因子
 const _FACTOR: f32 = 0.75;
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub struct MyHashMap<K, V> {
+/// 拉链法哈希表，S为哈希函数的构造器，默认与std的HashMap相同(随机种子的SipHash)
+/// 可换成FNV、固定种子的哈希(测试可复现)或针对key类型定制的哈希
+#[derive(Debug, Clone)]
+pub struct MyHashMap<K, V, S = RandomState> {
     _data: Vec<Option<List<(K, V)>>>,
     _len: usize,
     _cap: usize,
+    _hasher: S,
 }
 
-impl<K: Hash + Eq + Clone, V: Clone> MyHashMap<K, V> {
-    //根据k获取下标
-    fn _get_index(&self, k: &K) -> usize {
-        let mut hasher = DefaultHasher::new();
-        k.hash(&mut hasher);
-        hasher.finish() as usize % self._cap
-    }
+impl<K, V> MyHashMap<K, V> {
     //默认设置容量为4
     pub fn new() -> Self {
         Self::with_cap(4)
     }
     //new并设置容量
     pub fn with_cap(cap: usize) -> Self {
+        Self::with_capacity_and_hasher(cap, RandomState::new())
+    }
+}
+
+impl<K, V, S> MyHashMap<K, V, S> {
+    //指定哈希函数，容量为4
+    pub fn with_hasher(hasher: S) -> Self {
+        Self::with_capacity_and_hasher(4, hasher)
+    }
+    //指定容量和哈希函数，容量至少为1
+    pub fn with_capacity_and_hasher(cap: usize, hasher: S) -> Self {
+        let cap = cap.max(1);
         let mut v = Vec::<Option<List<(K, V)>>>::default();
         for _ in 0..cap {
             v.push(None);
@@ -37,56 +51,157 @@ impl<K: Hash + Eq + Clone, V: Clone> MyHashMap<K, V> {
             _data: v,
             _len: 0,
             _cap: cap,
+            _hasher: hasher,
         }
     }
-    pub fn put(&mut self, k: &K, v: V) {
-        let index = self._get_index(k);
-        // println!("index:{}, k:{:?}, v:{:?}", index, k, v);
-        //若k对应的v存在
-        let data = self._data[index].as_mut();
-        match data {
-            Some(d) => {
-                //若对应位置存在数据，加入其中的链表
-                //若有此k
-                let mut cur = d.next_mut();
-                while let Some(c) = cur {
-                    if c.get_value().0 == *k {
-                        c.get_mut().1 = v;
-                        return;
-                    }
-                    cur = c.next_mut();
+    //哈希函数
+    pub fn hasher(&self) -> &S {
+        &self._hasher
+    }
+    pub fn len(&self) -> usize {
+        self._len
+    }
+    pub fn is_empty(&self) -> bool {
+        self._len == 0
+    }
+    //按桶的顺序遍历，桶内按链表顺序
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            _buckets: self._data.iter(),
+            _cur: None,
+            _left: self._len,
+        }
+    }
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            _buckets: self._data.iter_mut(),
+            _cur: None,
+            _left: self._len,
+        }
+    }
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+        self.iter_mut().map(|(_, v)| v)
+    }
+    //取出所有元素，map立即变为空表，容量不变
+    pub fn drain(&mut self) -> IntoIter<K, V> {
+        let mut data = Vec::with_capacity(self._cap);
+        data.resize_with(self._cap, || None);
+        let len = mem::replace(&mut self._len, 0);
+        IntoIter {
+            _buckets: mem::replace(&mut self._data, data).into_iter(),
+            _cur: None,
+            _left: len,
+        }
+    }
+    //只保留f返回true的元素
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, mut f: F) {
+        for bucket in self._data.iter_mut() {
+            let Some(list) = bucket else {
+                continue;
+            };
+            let mut kept = List::default();
+            while let Some((k, mut v)) = list.delete_head() {
+                if f(&k, &mut v) {
+                    kept.add_at_head((k, v));
+                } else {
+                    self._len -= 1;
                 }
-                //若无此k
-                d.add((k.clone(), v));
             }
-            None => {
-                //若不存在, 插入
-                //若插入超过因子，扩容
-                if self._len as f32 / self._cap as f32 >= _FACTOR {
-                    let mut new_data = Self::with_cap(self._cap * 2);
-                    new_data._len = self._len;
-                    for d in &self._data {
-                        //扩容时下标要重新生成
-                        if let Some(dd) = d {
-                            let mut cur = dd.next();
-                            while let Some(c) = &cur {
-                                let val = c.get_value().clone();
-                                //此处put递归调用理论上来说不会经过判断是否达到扩容因子
-                                new_data.put(&val.0, val.1);
-                                cur = c.next();
-                            }
-                        }
-                    }
-                    self._data.clear();
-                    *self = new_data;
+            *bucket = (kept.len() > 0).then_some(kept);
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> MyHashMap<K, V, S> {
+    //插入，k已存在时替换值
+    pub fn put(&mut self, k: &K, v: V) {
+        self._put(k.clone(), v);
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> MyHashMap<K, V, S> {
+    //根据k获取下标
+    fn _get_index(&self, k: &K) -> usize {
+        self._hasher.hash_one(k) as usize % self._cap
+    }
+    //插入，k已存在时替换值
+    fn _put(&mut self, k: K, v: V) {
+        match self.entry(k) {
+            Entry::Occupied(mut e) => {
+                e.insert(v);
+            }
+            Entry::Vacant(e) => {
+                e.insert(v);
+            }
+        }
+    }
+    //k在下标为index的桶中的位置
+    fn _find(&self, index: usize, k: &K) -> Option<usize> {
+        let mut cur = self._data[index].as_ref()?.next();
+        let mut pos = 0;
+        while let Some(node) = cur {
+            if node.get_value().0 == *k {
+                return Some(pos);
+            }
+            cur = node.next();
+            pos += 1;
+        }
+        None
+    }
+    //插入一个确定不存在的k，返回新值的引用
+    fn _insert_new(&mut self, k: K, v: V) -> &mut V {
+        //若插入超过因子，扩容
+        if self._len as f32 / self._cap as f32 >= _FACTOR {
+            self._resize();
+        }
+        //扩容后下标要重新生成
+        let index = self._get_index(&k);
+        match self._data[index].as_mut() {
+            //若对应位置存在数据，加入其中的链表
+            Some(d) => d.add_at_head((k, v)),
+            None => self._data[index] = Some(List::new((k, v))),
+        }
+        self._len += 1;
+        let list = self._data[index].as_mut().expect(OUT_OF_RANGE);
+        &mut list.get_mut(0).expect(OUT_OF_RANGE).1
+    }
+    //容量翻倍，所有节点移到新的桶中，key和value都不需要克隆
+    fn _resize(&mut self) {
+        self._cap *= 2;
+        let mut data = Vec::with_capacity(self._cap);
+        data.resize_with(self._cap, || None);
+        let old = mem::replace(&mut self._data, data);
+        for mut list in old.into_iter().flatten() {
+            while let Some((k, v)) = list.delete_head() {
+                let index = self._get_index(&k);
+                match self._data[index].as_mut() {
+                    Some(d) => d.add_at_head((k, v)),
+                    None => self._data[index] = Some(List::new((k, v))),
                 }
-                //插入
-                let node = List::<(K, V)>::new((k.clone(), v));
-                self._data[index] = Some(node);
-                self._len += 1;
             }
         }
     }
+    //获取k对应的入口，之后可原地插入或修改，只查找一次
+    pub fn entry(&mut self, k: K) -> Entry<'_, K, V, S> {
+        let index = self._get_index(&k);
+        match self._find(index, &k) {
+            Some(pos) => Entry::Occupied(OccupiedEntry {
+                _map: self,
+                _index: index,
+                _pos: pos,
+            }),
+            None => Entry::Vacant(VacantEntry {
+                _map: self,
+                _key: k,
+            }),
+        }
+    }
 
     pub fn get_mut(&mut self, k: &K) -> Option<&mut V> {
         let index = self._get_index(k);
@@ -151,60 +266,287 @@ impl<K: Hash + Eq + Clone, V: Clone> MyHashMap<K, V> {
         }
     }
 
-    pub fn remove(&mut self, k: &K) {
+    //删除k，返回其对应的值
+    pub fn remove(&mut self, k: &K) -> Option<V> {
         let index = self._get_index(k);
-        let data = self._data[index].as_mut();
-        match data {
-            Some(v) => {
-                let mut i = 0;
-                let mut head = v.next_mut();
-                while let Some(h) = head {
-                    if h.get_value().0 == *k {
-                        if let Some(l) = v.get_node_mut(i - 1) {
-                            if let Some(m) = l.next_mut() {
-                                if let Some(r) = m.next_mut() {
-                                    *m = r.clone();
-                                }
-                            }
-                        }
-                        break;
-                    }
-                    head = h.next_mut();
-                    i += 1;
-                }
-            }
-            None => return,
-        }
+        let pos = self._find(index, k)?;
+        let (_, v) = self._data[index].as_mut()?.delete_at_index(pos)?;
+        self._len -= 1;
+        Some(v)
     }
+    //清空所有元素，容量不变
     pub fn remove_all(&mut self) {
         self._len = 0;
-        self._cap = 0;
         self._data.clear();
+        self._data.resize_with(self._cap, || None);
     }
 }
 
-impl<K, V> Default for MyHashMap<K, V> {
+impl<K, V, S: Default> Default for MyHashMap<K, V, S> {
     fn default() -> Self {
-        Self {
-            _data: Vec::default(),
-            _len: usize::default(),
-            _cap: usize::default(),
-        }
+        Self::with_hasher(S::default())
+    }
+}
+
+//内容相同即相等，与桶的布局和哈希函数无关
+impl<K: Hash + Eq, V: PartialEq, S: BuildHasher> PartialEq for MyHashMap<K, V, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self._len == other._len && self.iter().all(|(k, v)| other.get(k) == Some(v))
     }
 }
 
-impl<K: Hash + Eq + Clone, V: Clone> Index<&K> for MyHashMap<K, V> {
+impl<K: Hash + Eq, V: Eq, S: BuildHasher> Eq for MyHashMap<K, V, S> {}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Index<&K> for MyHashMap<K, V, S> {
     type Output = V;
 
     fn index(&self, index: &K) -> &Self::Output {
         self.get(index).expect(OUT_OF_RANGE)
     }
 }
-impl<K: Hash + Eq + Clone, V: Clone> IndexMut<&K> for MyHashMap<K, V> {
+impl<K: Hash + Eq, V, S: BuildHasher> IndexMut<&K> for MyHashMap<K, V, S> {
     fn index_mut(&mut self, index: &K) -> &mut Self::Output {
         self.get_mut(index).expect(OUT_OF_RANGE)
     }
 }
+impl<K: Hash + Eq, V, S: BuildHasher> Extend<(K, V)> for MyHashMap<K, V, S> {
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (k, v) in iter {
+            self._put(k, v);
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> FromIterator<(K, V)> for MyHashMap<K, V, S> {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut hm = Self::default();
+        hm.extend(iter);
+        hm
+    }
+}
+
+/// 不可变迭代器，依次走过每个桶的链表
+pub struct Iter<'a, K, V> {
+    _buckets: slice::Iter<'a, Option<List<(K, V)>>>,
+    _cur: Option<&'a Node<(K, V)>>,
+    _left: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(node) = self._cur {
+                self._cur = node.next();
+                self._left -= 1;
+                let (k, v) = node.get_value();
+                return Some((k, v));
+            }
+            //当前链表走完，换到下一个非空的桶
+            self._cur = self._buckets.next()?.as_ref().and_then(|l| l.next());
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self._left, Some(self._left))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+/// 可变迭代器，key不可变
+pub struct IterMut<'a, K, V> {
+    _buckets: slice::IterMut<'a, Option<List<(K, V)>>>,
+    _cur: Option<&'a mut Node<(K, V)>>,
+    _left: usize,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(node) = self._cur.take() {
+                let ((k, v), next) = node.split_mut();
+                self._cur = next;
+                self._left -= 1;
+                return Some((k, v));
+            }
+            self._cur = self._buckets.next()?.as_mut().and_then(|l| l.next_mut());
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self._left, Some(self._left))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+/// 按值迭代，逐个摘下链表头
+pub struct IntoIter<K, V> {
+    _buckets: vec::IntoIter<Option<List<(K, V)>>>,
+    _cur: Option<List<(K, V)>>,
+    _left: usize,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(pair) = self._cur.as_mut().and_then(|l| l.delete_head()) {
+                self._left -= 1;
+                return Some(pair);
+            }
+            self._cur = self._buckets.next()?;
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self._left, Some(self._left))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+
+impl<K, V, S> IntoIterator for MyHashMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            _buckets: self._data.into_iter(),
+            _cur: None,
+            _left: self._len,
+        }
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a MyHashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut MyHashMap<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// MyHashMap中某个key的入口，由entry获取
+pub enum Entry<'a, K, V, S = RandomState> {
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    Vacant(VacantEntry<'a, K, V, S>),
+}
+
+/// 已存在的key，记录其所在的桶和在链表中的位置
+pub struct OccupiedEntry<'a, K, V, S = RandomState> {
+    _map: &'a mut MyHashMap<K, V, S>,
+    _index: usize,
+    _pos: usize,
+}
+
+/// 不存在的key，插入时才放入表中
+pub struct VacantEntry<'a, K, V, S = RandomState> {
+    _map: &'a mut MyHashMap<K, V, S>,
+    _key: K,
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> Entry<'a, K, V, S> {
+    //不存在时插入v，返回值的引用
+    pub fn or_insert(self, v: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(v),
+        }
+    }
+    //不存在时插入f的结果，f只在需要时调用
+    pub fn or_insert_with<F: FnOnce() -> V>(self, f: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(f()),
+        }
+    }
+    //存在时就地修改
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(e) = &mut self {
+            f(e.get_mut());
+        }
+        self
+    }
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => e.key(),
+        }
+    }
+}
+
+impl<'a, K: Hash + Eq, V: Default, S: BuildHasher> Entry<'a, K, V, S> {
+    //不存在时插入默认值
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K, V, S> OccupiedEntry<'a, K, V, S> {
+    fn _pair(&self) -> &(K, V) {
+        let list = self._map._data[self._index].as_ref().expect(OUT_OF_RANGE);
+        list.get(self._pos).expect(OUT_OF_RANGE)
+    }
+    fn _pair_mut(&mut self) -> &mut (K, V) {
+        let list = self._map._data[self._index].as_mut().expect(OUT_OF_RANGE);
+        list.get_mut(self._pos).expect(OUT_OF_RANGE)
+    }
+    pub fn key(&self) -> &K {
+        &self._pair().0
+    }
+    pub fn get(&self) -> &V {
+        &self._pair().1
+    }
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self._pair_mut().1
+    }
+    //转为与map同生命周期的可变引用
+    pub fn into_mut(self) -> &'a mut V {
+        let list = self._map._data[self._index].as_mut().expect(OUT_OF_RANGE);
+        &mut list.get_mut(self._pos).expect(OUT_OF_RANGE).1
+    }
+    //替换值，返回旧值
+    pub fn insert(&mut self, v: V) -> V {
+        mem::replace(self.get_mut(), v)
+    }
+    //从表中删除，返回key和value
+    pub fn remove_entry(self) -> (K, V) {
+        let list = self._map._data[self._index].as_mut().expect(OUT_OF_RANGE);
+        let pair = list.delete_at_index(self._pos).expect(OUT_OF_RANGE);
+        self._map._len -= 1;
+        pair
+    }
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> VacantEntry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        &self._key
+    }
+    pub fn into_key(self) -> K {
+        self._key
+    }
+    //插入v，返回值的引用
+    pub fn insert(self, v: V) -> &'a mut V {
+        self._map._insert_new(self._key, v)
+    }
+}
+
 #[test]
 fn test() {
     let mut hm = MyHashMap::<i32, i32>::new();
@@ -228,4 +570,196 @@ fn test() {
         hm.get(&9),
         hm.get(&5)
     );
+    //删除链表头、中间的节点以及不存在的k
+    assert_eq!(hm.remove(&7), Some(9));
+    assert_eq!(hm.remove(&7), None);
+    assert_eq!(hm.remove(&1288), Some(39));
+    assert_eq!(hm.get(&7), None);
+    assert_eq!(hm.get(&8), Some(&19));
+    assert_eq!(hm._len, 9);
+    //清空后容量不变，仍可继续读写
+    let cap = hm._cap;
+    hm.remove_all();
+    assert_eq!(hm._cap, cap);
+    assert_eq!(hm.get(&8), None);
+    hm.put(&8, 20);
+    assert_eq!(hm.get(&8), Some(&20));
+    assert_eq!(hm._len, 1);
+}
+
+#[test]
+fn test_hasher() {
+    use std::{
+        collections::hash_map::DefaultHasher,
+        hash::{BuildHasherDefault, Hasher},
+    };
+
+    //FNV-1a
+    #[derive(Default)]
+    struct Fnv(u64);
+    impl Hasher for Fnv {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+        fn write(&mut self, bytes: &[u8]) {
+            if self.0 == 0 {
+                self.0 = 0xcbf2_9ce4_8422_2325;
+            }
+            for b in bytes {
+                self.0 = (self.0 ^ *b as u64).wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+    }
+
+    let mut fnv: MyHashMap<u32, u32, BuildHasherDefault<Fnv>> = MyHashMap::default();
+    let mut sip =
+        MyHashMap::with_capacity_and_hasher(2, BuildHasherDefault::<DefaultHasher>::default());
+    for i in 0..100 {
+        fnv.put(&i, i * 2);
+        sip.put(&i, i * 2);
+    }
+    for i in 0..100 {
+        assert_eq!(fnv.get(&i), Some(&(i * 2)));
+        assert_eq!(sip[&i], i * 2);
+    }
+    assert_eq!(fnv.get(&100), None);
+
+    //固定种子的哈希，两次构造的桶布局完全相同
+    let build = || {
+        let mut hm = MyHashMap::with_hasher(BuildHasherDefault::<DefaultHasher>::default());
+        for i in 0..50 {
+            hm.put(&i, i);
+        }
+        hm
+    };
+    assert_eq!(
+        format!("{:?}", build()._data),
+        format!("{:?}", build()._data)
+    );
+    //随机种子不同时布局可能不同，但内容相同即相等
+    let mut a = MyHashMap::new();
+    let mut b = MyHashMap::new();
+    for i in 0..50 {
+        a.put(&i, i);
+        b.put(&(49 - i), 49 - i);
+    }
+    assert_eq!(a, b);
+    b[&0] = 1;
+    assert_ne!(a, b);
+}
+
+#[test]
+fn test_entry() {
+    //key不需要Clone
+    #[derive(Debug, PartialEq, Eq, Hash)]
+    struct Word(String);
+
+    let text = "a b a c b a d a";
+    let mut hm = MyHashMap::new();
+    for w in text.split(' ') {
+        *hm.entry(Word(w.to_string())).or_insert(0) += 1;
+    }
+    assert_eq!(hm._len, 4);
+    assert_eq!(hm[&Word("a".to_string())], 4);
+    assert_eq!(hm[&Word("b".to_string())], 2);
+
+    let mut hm = MyHashMap::<u32, Vec<u32>>::new();
+    for i in 0..100 {
+        hm.entry(i % 10).or_default().push(i);
+    }
+    assert_eq!(hm._len, 10);
+    assert_eq!(hm[&3], (0..10).map(|i| i * 10 + 3).collect::<Vec<_>>());
+    //and_modify只作用于已存在的key，or_insert_with只在不存在时调用
+    hm.entry(3)
+        .and_modify(|v| v.clear())
+        .or_insert_with(|| unreachable!());
+    assert!(hm[&3].is_empty());
+    hm.entry(42)
+        .and_modify(|_| unreachable!())
+        .or_insert_with(|| vec![42]);
+    assert_eq!(hm.get(&42), Some(&vec![42]));
+    //remove_entry
+    match hm.entry(5) {
+        Entry::Occupied(e) => {
+            assert_eq!(e.key(), &5);
+            assert_eq!(e.remove_entry().0, 5);
+        }
+        Entry::Vacant(_) => unreachable!(),
+    }
+    assert_eq!(hm.get(&5), None);
+    assert_eq!(hm._len, 10);
+    match hm.entry(5) {
+        Entry::Occupied(_) => unreachable!(),
+        Entry::Vacant(e) => assert_eq!(e.into_key(), 5),
+    }
+    //扩容后所有的值都还在
+    for i in 0..10 {
+        assert_eq!(hm.get(&i).is_some(), i != 5);
+    }
+}
+
+#[test]
+fn test_iter() {
+    //排序后与期望相同，说明每个元素恰好出现一次
+    fn sorted<T: Ord>(it: impl Iterator<Item = T>) -> Vec<T> {
+        let mut v = it.collect::<Vec<_>>();
+        v.sort();
+        v
+    }
+
+    //从容量1开始，插入过程中多次扩容
+    let mut hm = MyHashMap::with_cap(1);
+    hm.extend((0..1000).map(|i| (i, i)));
+    for i in (0..1000).step_by(3) {
+        assert_eq!(hm.remove(&i), Some(i));
+    }
+    let expect = (0..1000).filter(|i| i % 3 != 0).collect::<Vec<_>>();
+    assert_eq!(hm.len(), expect.len());
+    assert_eq!(hm.iter().len(), expect.len());
+    assert_eq!(sorted(hm.keys().copied()), expect);
+    assert_eq!(sorted(hm.values().copied()), expect);
+
+    for (k, v) in hm.iter_mut() {
+        *v += k;
+    }
+    for v in hm.values_mut() {
+        *v += 1;
+    }
+    for (k, v) in &hm {
+        assert_eq!(*v, k * 2 + 1);
+    }
+    for (_, v) in &mut hm {
+        *v = 0;
+    }
+    assert!(hm.values().all(|v| *v == 0));
+
+    hm.retain(|k, v| {
+        *v = *k;
+        k % 2 == 0
+    });
+    let even = expect
+        .iter()
+        .copied()
+        .filter(|i| i % 2 == 0)
+        .collect::<Vec<_>>();
+    assert_eq!(hm.len(), even.len());
+    assert_eq!(
+        sorted(hm.iter().map(|(k, v)| (*k, *v))),
+        sorted(even.iter().map(|i| (*i, *i)))
+    );
+
+    //drain后map为空且可继续使用
+    let drained = hm.drain();
+    assert_eq!(drained.len(), even.len());
+    assert_eq!(sorted(drained.map(|(k, _)| k)), even);
+    assert!(hm.is_empty());
+    assert_eq!(hm.iter().next(), None);
+    hm.put(&1, 1);
+    assert_eq!(hm.into_iter().collect::<Vec<_>>(), [(1, 1)]);
+
+    //重复的key后者覆盖前者
+    let hm = [(1, 'a'), (2, 'b'), (1, 'c')]
+        .into_iter()
+        .collect::<MyHashMap<_, _>>();
+    assert_eq!(sorted(hm.into_iter()), [(1, 'c'), (2, 'b')]);
 }
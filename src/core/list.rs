@@ -41,6 +41,10 @@ impl<T> Node<T> {
     pub fn get_mut(&mut self) -> &mut T {
         &mut self._val
     }
+    //同时取当前可变值和下一个可变节点，供可变迭代使用
+    pub fn split_mut(&mut self) -> (&mut T, Option<&mut Self>) {
+        (&mut self._val, self._next.as_deref_mut())
+    }
 }
 
 impl<T> List<T> {
@@ -117,6 +121,25 @@ impl<T> List<T> {
             None => None,
         }
     }
+    //删头
+    pub fn delete_head(&mut self) -> Option<T> {
+        let head = self._head.take()?;
+        self._head = head._next;
+        self._len -= 1;
+        Some(head._val)
+    }
+    //删下标，节点直接移出，不需要T: Clone
+    pub fn delete_at_index(&mut self, index: usize) -> Option<T> {
+        //下标为0
+        if index == 0 {
+            return self.delete_head();
+        }
+        let left = self.get_node_mut(index - 1)?;
+        let mid = left._next.take()?;
+        left._next = mid._next;
+        self._len -= 1;
+        Some(mid._val)
+    }
     //清空
     pub fn clear(&mut self) {
         self._head = None;
@@ -155,48 +178,6 @@ impl<T: Clone> List<T> {
         }
     }
 
-    //删头
-    pub fn delete_head(&mut self) -> Option<T> {
-        if let Some(x) = self._head.as_mut() {
-            let del_val = x._val.clone();
-            self._head = x._next.clone();
-            self._len -= 1;
-            return Some(del_val);
-        } else {
-            None
-        }
-    }
-    //删下标
-    pub fn delete_at_index(&mut self, index: usize) -> Option<T> {
-        //下标为0
-        if index == 0 {
-            return self.delete_head();
-        }
-        let len = self._len;
-        let mut cur = self._head.as_mut();
-        let mut i = 0;
-        while let Some(left) = cur {
-            if i == index - 1 {
-                let mid = left._next.as_mut();
-                if let Some(m) = mid {
-                    let m_val = m._val.clone();
-                    let mut right = m._next.as_mut();
-                    if let Some(r) = right {
-                        left.as_mut()._next = Some(r.clone());
-                    } else {
-                        left._next = None;
-                    }
-                    self._len -= 1;
-                    return Some(m_val);
-                }
-                break;
-            }
-            cur = left._next.as_mut();
-            i += 1;
-        }
-        None
-    }
-
     //反转
     pub fn reverse(&mut self) {
         let mut node = &self._head;
@@ -1,5 +1,14 @@
+/// AVL树
+pub mod avl_tree;
+/// B+树
+pub mod bplus_tree;
 /// 二叉搜索树
 pub mod bst;
+/// B树
+pub mod btree;
+/// 无锁并发跳表
+#[cfg(feature = "lockfree")]
+pub mod concurrent_skip_table;
 /// 双向链表
 pub mod dlist;
 // hashmap
@@ -12,9 +21,17 @@ pub mod ordered_list;
 pub mod queue;
 /// 红黑树
 pub mod rb_tree;
+/// Robin Hood开放寻址哈希表
+pub mod robin_hood;
 /// 跳表
 pub mod skip_table;
 /// 排序
 pub mod sort;
+/// 伸展树
+pub mod splay_tree;
 /// 栈
 pub mod stack;
+/// SwissTable风格的哈希表
+pub mod swiss_table;
+/// 树堆
+pub mod treap;
@@ -56,8 +56,11 @@ impl<T: Ord + Clone> OrderedList<T> {
                 Less => {
                     // 若prev为none，则插入头；若大于prev，则插入prev后；若小于则继续
                     let prev = x_borrow.prev_cp();
-                    if prev == None {
-                        *x.borrow_mut().prev_mut() = Some(new_node);
+                    if prev.is_none() {
+                        // 新节点成为头节点
+                        *x.borrow_mut().prev_mut() = Some(Rc::downgrade(&new_node));
+                        *new_node.borrow_mut().next_mut() = Some(x.clone());
+                        self._head = Some(new_node);
                         self._len += 1;
                         break;
                     }
@@ -65,10 +68,10 @@ impl<T: Ord + Clone> OrderedList<T> {
                     let prev = prev.unwrap();
                     if t.cmp(prev.borrow().get_value()) == Greater {
                         // 设置新节点的前后节点
-                        *new_node.borrow_mut().prev_mut() = Some(prev.clone());
+                        *new_node.borrow_mut().prev_mut() = Some(Rc::downgrade(&prev));
                         *new_node.borrow_mut().next_mut() = Some(x.clone());
                         // 设置当前节点的前节点为新节点
-                        *x.borrow_mut().prev_mut() = Some(new_node.clone());
+                        *x.borrow_mut().prev_mut() = Some(Rc::downgrade(&new_node));
                         // 设置前节点的后节点为新节点
                         *prev.borrow_mut().next_mut() = Some(new_node.clone());
                         self._len += 1;
@@ -78,7 +81,8 @@ impl<T: Ord + Clone> OrderedList<T> {
                 Greater => {
                     // 若next为none，则插入尾；若小于next，则插入next前；若大于则继续
                     let next = x_borrow.next_cp();
-                    if next == None {
+                    if next.is_none() {
+                        *new_node.borrow_mut().prev_mut() = Some(Rc::downgrade(&x));
                         *x.borrow_mut().next_mut() = Some(new_node);
                         self._len += 1;
                         break;
@@ -87,12 +91,12 @@ impl<T: Ord + Clone> OrderedList<T> {
                     let next = next.unwrap();
                     if t.cmp(next.borrow().get_value()) == Less {
                         // 设置新节点的前后节点
-                        *new_node.borrow_mut().prev_mut() = Some(x.clone());
+                        *new_node.borrow_mut().prev_mut() = Some(Rc::downgrade(&x));
                         *new_node.borrow_mut().next_mut() = Some(next.clone());
                         // 设置当前节点的后节点为新节点
                         *x.borrow_mut().next_mut() = Some(new_node.clone());
                         // 设置后节点的前节点为新节点
-                        *next.borrow_mut().prev_mut() = Some(new_node.clone());
+                        *next.borrow_mut().prev_mut() = Some(Rc::downgrade(&new_node));
                         self._len += 1;
                         break;
                     }
@@ -142,7 +146,7 @@ impl<T: Ord + Clone> OrderedList<T> {
                     // 中间节点，将prev的next指为next，将next的prev指为prev
                     let (prev, next) = (prev.unwrap(), next.unwrap());
                     *prev.borrow_mut().next_mut() = Some(next.clone());
-                    *next.borrow_mut().prev_mut() = Some(prev.clone());
+                    *next.borrow_mut().prev_mut() = Some(Rc::downgrade(&prev));
                     break;
                 }
                 Less => {
@@ -173,6 +177,28 @@ impl<T: Ord + Clone> OrderedList<T> {
     }
 }
 
+impl<T> OrderedList<T> {
+    //长度
+    pub fn len(&self) -> usize {
+        self._len
+    }
+    //为空返回true
+    pub fn is_empty(&self) -> bool {
+        self._len == 0
+    }
+    //清空
+    pub fn clear(&mut self) {
+        DNode::drop_chain(self._head.take());
+        self._len = 0;
+    }
+}
+
+impl<T> Drop for OrderedList<T> {
+    fn drop(&mut self) {
+        DNode::drop_chain(self._head.take());
+    }
+}
+
 impl<T> Default for OrderedList<T> {
     fn default() -> Self {
         Self {
@@ -202,3 +228,31 @@ fn test() {
     res.delete(&1);
     println!("res={:?}", res);
 }
+
+#[test]
+fn test_leak() {
+    use crate::common::counted::Counted;
+    use std::cell::Cell;
+
+    let live = Rc::new(Cell::new(0));
+    let mut ol = OrderedList::default();
+    for i in [5, 1, 9, 3, 7, 0, 10] {
+        ol.add(Counted::new(i, &live));
+    }
+    //比头节点小的值插入到头部
+    assert!(ol.find(&Counted::new(0, &live)));
+    assert_eq!(live.get(), ol.len());
+    for i in [0, 7, 10] {
+        ol.delete(&Counted::new(i, &live));
+    }
+    assert_eq!(ol.len(), 4);
+    assert_eq!(live.get(), 4);
+    ol.clear();
+    assert_eq!(live.get(), 0);
+
+    for i in 0..100 {
+        ol.add(Counted::new(i, &live));
+    }
+    drop(ol);
+    assert_eq!(live.get(), 0);
+}
@@ -1,11 +1,16 @@
 use std::{
-    cell::RefCell,
+    cmp::Ordering,
     fmt::Debug,
-    ops::{DerefMut, Not},
+    marker::PhantomData,
+    ops::{Bound, Not, Range, RangeBounds},
     rc::Rc,
 };
 
-use crate::common::pos::Pos;
+use crate::common::{
+    errs::{CAPACITY_OVERFLOW, EMPTY_INTERVAL, NOT_SORTED},
+    node::{BinaryTree, MorrisInOrder, MorrisTree},
+    viz::TreeView,
+};
 
 //每个节点是红的或者黑的
 //根节点是黑的
@@ -18,20 +23,32 @@ use crate::common::pos::Pos;
 ///        3   5      <--     1  3
 ///                     右旋
 //////////////////////////////////////////////
-#[derive(Clone, PartialEq, Eq)]
-pub struct RBTree<T> {
-    _root: RBInnerNode<T>,
+/// 所有节点存放在_nodes中，节点之间以u32下标相连，NIL表示空
+/// 删除的节点下标放入_free，插入时优先复用
+/// A为节点的附加信息，默认不带
+#[derive(Clone)]
+pub struct RBTree<T, A = ()> {
+    _nodes: Vec<RBNode<T>>,
+    _root: u32,
+    _free: Vec<u32>,
+    _len: usize,
+    //累计旋转次数
+    _rotations: usize,
+    _aug: PhantomData<A>,
 }
 
-type RBInnerNode<T> = Option<Rc<RefCell<RBNode<T>>>>;
+//空下标
+const NIL: u32 = u32::MAX;
 
 #[derive(Clone, PartialEq, Eq)]
 pub struct RBNode<T> {
-    _left: RBInnerNode<T>,
-    _right: RBInnerNode<T>,
-    _parent: RBInnerNode<T>,
+    _left: u32,
+    _right: u32,
+    _parent: u32,
     _val: Option<T>,
     _color: Color,
+    //以该节点为根的子树的节点数
+    _size: usize,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -40,563 +57,2268 @@ pub enum Color {
     Red,
 }
 
+/// 节点的附加信息(如区间树的最大端点)
+/// 子树发生变化(插入、删除、旋转)时，由左右子节点的值重新计算
+pub trait RBAugment<T> {
+    fn update(val: &mut T, left: Option<&T>, right: Option<&T>);
+}
+
+//不带附加信息
+impl<T> RBAugment<T> for () {
+    fn update(_val: &mut T, _left: Option<&T>, _right: Option<&T>) {}
+}
+
 impl<T> RBTree<T> {
     pub fn new(val: T) -> Self {
         Self {
-            _root: Some(Rc::new(RefCell::new(RBNode::new_black(val)))),
+            _nodes: vec![RBNode::new_black(val)],
+            _root: 0,
+            _free: vec![],
+            _len: 1,
+            _rotations: 0,
+            _aug: PhantomData,
         }
     }
 }
 
-impl<T: Clone + Ord + Debug> RBTree<T> {
-    fn get_node(&self, t: &T) -> RBInnerNode<T> {
-        _get_node(&self._root, &Some(t.clone()))
+impl<T, A> RBTree<T, A> {
+    fn _empty() -> Self {
+        Self {
+            _nodes: vec![],
+            _root: NIL,
+            _free: vec![],
+            _len: 0,
+            _rotations: 0,
+            _aug: PhantomData,
+        }
+    }
+    //长度
+    pub fn len(&self) -> usize {
+        self._len
+    }
+    //为空返回true
+    pub fn is_empty(&self) -> bool {
+        self._len == 0
+    }
+    //累计旋转次数
+    pub fn rotations(&self) -> usize {
+        self._rotations
+    }
+    //清空
+    pub fn clear(&mut self) {
+        self._nodes.clear();
+        self._free.clear();
+        self._root = NIL;
+        self._len = 0;
+    }
+    //从小到大遍历，rev()则从大到小
+    pub fn iter(&self) -> Iter<'_, T, A> {
+        Iter {
+            _tree: self,
+            _front: self._first_node(self._root),
+            _back: self._last_node(self._root),
+        }
+    }
+
+    //Morris中序遍历，额外空间O(1)，返回值的拷贝
+    pub fn morris_in_order(&mut self) -> MorrisInOrder<'_, Self> {
+        MorrisInOrder::new(self)
+    }
+
+    fn _node(&self, x: u32) -> &RBNode<T> {
+        &self._nodes[x as usize]
+    }
+    fn _node_mut(&mut self, x: u32) -> &mut RBNode<T> {
+        &mut self._nodes[x as usize]
+    }
+    //放入新节点，返回其下标
+    fn _alloc(&mut self, val: T, color: Color, parent: u32) -> u32 {
+        self._put(RBNode::new(val, color, parent))
+    }
+    fn _put(&mut self, node: RBNode<T>) -> u32 {
+        match self._free.pop() {
+            Some(x) => {
+                *self._node_mut(x) = node;
+                x
+            }
+            None => {
+                assert!(self._nodes.len() < NIL as usize, "{}", CAPACITY_OVERFLOW);
+                self._nodes.push(node);
+                (self._nodes.len() - 1) as u32
+            }
+        }
+    }
+    //回收节点，未取出的值随之释放
+    fn _dealloc(&mut self, x: u32) {
+        *self._node_mut(x) = RBNode::default();
+        self._free.push(x);
+    }
+    //把parent指向old的链接改为指向new，parent为NIL时改根
+    fn _replace_child(&mut self, parent: u32, old: u32, new: u32) {
+        if parent == NIL {
+            self._root = new;
+        } else if self._left_of(parent) == old {
+            self._node_mut(parent)._left = new;
+        } else {
+            self._node_mut(parent)._right = new;
+        }
+    }
+
+    fn _set_color(&mut self, x: u32, c: Color) {
+        if x != NIL {
+            self._node_mut(x)._color = c;
+        }
+    }
+    fn _parent_of(&self, x: u32) -> u32 {
+        if x == NIL {
+            NIL
+        } else {
+            self._node(x)._parent
+        }
+    }
+    fn _left_of(&self, x: u32) -> u32 {
+        if x == NIL {
+            NIL
+        } else {
+            self._node(x)._left
+        }
+    }
+    fn _right_of(&self, x: u32) -> u32 {
+        if x == NIL {
+            NIL
+        } else {
+            self._node(x)._right
+        }
+    }
+    fn _color_of(&self, x: u32) -> Color {
+        if x == NIL {
+            Color::Black
+        } else {
+            self._node(x)._color
+        }
+    }
+    fn _size_of(&self, x: u32) -> usize {
+        if x == NIL {
+            0
+        } else {
+            self._node(x)._size
+        }
+    }
+    //节点的值，值已被取出时为None
+    fn _val_of(&self, x: u32) -> Option<&T> {
+        if x == NIL {
+            None
+        } else {
+            self._node(x)._val.as_ref()
+        }
+    }
+
+    //x为根的子树中最左侧的节点
+    fn _first_node(&self, x: u32) -> u32 {
+        let mut p = x;
+        while self._left_of(p) != NIL {
+            p = self._left_of(p);
+        }
+        p
+    }
+    //x为根的子树中最右侧的节点
+    fn _last_node(&self, x: u32) -> u32 {
+        let mut p = x;
+        while self._right_of(p) != NIL {
+            p = self._right_of(p);
+        }
+        p
+    }
+    //寻找比t小的最大值
+    fn _predecessor(&self, t: u32) -> u32 {
+        if t == NIL {
+            return NIL;
+        }
+        if self._left_of(t) != NIL {
+            return self._last_node(self._left_of(t));
+        }
+        let mut p = self._parent_of(t);
+        let mut ch = t;
+        while p != NIL && ch == self._left_of(p) {
+            ch = p;
+            p = self._parent_of(p);
+        }
+        p
+    }
+    //寻找比t大的最小值
+    fn _successor(&self, t: u32) -> u32 {
+        if t == NIL {
+            return NIL;
+        }
+        if self._right_of(t) != NIL {
+            return self._first_node(self._right_of(t));
+        }
+        let mut p = self._parent_of(t);
+        let mut ch = t;
+        while p != NIL && ch == self._right_of(p) {
+            ch = p;
+            p = self._parent_of(p);
+        }
+        p
+    }
+
+    //查找f(x)为Equal的节点，f(x)为x与目标的比较结果
+    fn _get_node_by<F: Fn(&T) -> Ordering>(&self, f: F) -> u32 {
+        let mut cur = self._root;
+        while cur != NIL {
+            match f(self._val_of(cur).unwrap()) {
+                Ordering::Equal => return cur,
+                Ordering::Less => cur = self._right_of(cur),
+                Ordering::Greater => cur = self._left_of(cur),
+            }
+        }
+        NIL
+    }
+    //f(x)为Less的最大节点
+    fn _last_less_by<F: Fn(&T) -> Ordering>(&self, f: F) -> u32 {
+        let mut res = NIL;
+        let mut cur = self._root;
+        while cur != NIL {
+            if f(self._val_of(cur).unwrap()) == Ordering::Less {
+                res = cur;
+                cur = self._right_of(cur);
+            } else {
+                cur = self._left_of(cur);
+            }
+        }
+        res
+    }
+    //f(x)不为Less的最小节点
+    fn _first_not_less_by<F: Fn(&T) -> Ordering>(&self, f: F) -> u32 {
+        let mut res = NIL;
+        let mut cur = self._root;
+        while cur != NIL {
+            if f(self._val_of(cur).unwrap()) == Ordering::Less {
+                cur = self._right_of(cur);
+            } else {
+                res = cur;
+                cur = self._left_of(cur);
+            }
+        }
+        res
+    }
+    //f(x)为Less的节点数
+    fn _count_less_by<F: Fn(&T) -> Ordering>(&self, f: F) -> usize {
+        let mut res = 0;
+        let mut cur = self._root;
+        while cur != NIL {
+            if f(self._val_of(cur).unwrap()) == Ordering::Less {
+                res += self._size_of(self._left_of(cur)) + 1;
+                cur = self._right_of(cur);
+            } else {
+                cur = self._left_of(cur);
+            }
+        }
+        res
     }
 }
 
-impl<T: Clone + Ord + PartialEq + Debug> RBTree<T> {
-    pub fn insert(&mut self, val: T) {
-        // println!("\nval={:?}", val);
-        let node = _insert(&mut self._root, val);
-        _fix_after_insert(node, &mut self._root);
+impl<T, A: RBAugment<T>> RBTree<T, A> {
+    //由子节点重新计算x的子树大小和附加信息，值已被取出的节点视为空
+    fn _pull_up(&mut self, x: u32) {
+        if x == NIL {
+            return;
+        }
+        let (l, r) = (self._left_of(x), self._right_of(x));
+        let size = self._size_of(l) + self._size_of(r);
+        //先取出值，才能同时引用左右子节点
+        let mut val = self._node_mut(x)._val.take();
+        if let Some(v) = val.as_mut() {
+            A::update(v, self._val_of(l), self._val_of(r));
+        }
+        let node = self._node_mut(x);
+        node._size = size + val.is_some() as usize;
+        node._val = val;
+    }
+    //从x开始向上重新计算到根
+    fn _pull_up_path(&mut self, x: u32) {
+        let mut cur = x;
+        while cur != NIL {
+            self._pull_up(cur);
+            cur = self._parent_of(cur);
+        }
+    }
+
+    ///////////////////////////////////////////////
+    ///      2              左旋              4
+    /// 1        4         -->       2             5
+    ///        3   5      <--     1  3
+    ///                     右旋
+    //////////////////////////////////////////////
+    //参考java->TreeMap->rotateLeft
+    fn _rotate_left(&mut self, this: u32) {
+        if this == NIL {
+            return;
+        }
+        self._rotations += 1;
+        let r = self._right_of(this);
+        let rl = self._left_of(r);
+        //this->right = right->left
+        self._node_mut(this)._right = rl;
+        //right->left->parent = this
+        if rl != NIL {
+            self._node_mut(rl)._parent = this;
+        }
+        //right->parent = this->parent
+        let p = self._parent_of(this);
+        self._node_mut(r)._parent = p;
+        //this->parent->left/right = right，this为根时root = right
+        self._replace_child(p, this, r);
+        //right->left = this
+        self._node_mut(r)._left = this;
+        //this->parent = right
+        self._node_mut(this)._parent = r;
+        //this成为right的子节点，先算this再算right
+        self._pull_up(this);
+        self._pull_up(r);
+    }
+    fn _rotate_right(&mut self, this: u32) {
+        if this == NIL {
+            return;
+        }
+        self._rotations += 1;
+        let l = self._left_of(this);
+        let lr = self._right_of(l);
+        //this->left = left->right
+        self._node_mut(this)._left = lr;
+        //left->right->parent = this
+        if lr != NIL {
+            self._node_mut(lr)._parent = this;
+        }
+        //left->parent = this->parent
+        let p = self._parent_of(this);
+        self._node_mut(l)._parent = p;
+        //this->parent->left/right = left，this为根时root = left
+        self._replace_child(p, this, l);
+        //left->right = this
+        self._node_mut(l)._right = this;
+        //this->parent = left
+        self._node_mut(this)._parent = l;
+        //this成为left的子节点，先算this再算left
+        self._pull_up(this);
+        self._pull_up(l);
+    }
+
+    /// 插入后处理旋转变色
+    /// 								red -> parent设黑、uncle设黑、gp设红、x设为gp
+    ///	    parent为左    y uncle
+    /// 								black->(x若为p的右，设为parent并左旋)、parent设黑、gp设红、右旋gp
+    /// 新节点x(不为空、不为root、且父级为红)																	root设黑
+    /// 								red -> parent设黑、uncle设黑、gp设红、x设为gp
+    /// 		parent为右    y uncle
+    /// 								black->(x若为p的左，设为parent并右旋)、parent设黑、gp设红、左旋gp
+    /// uncle为空时按黑处理
+    fn _fix_after_insert(&mut self, mut x: u32) {
+        while x != NIL && x != self._root && self._color_of(self._parent_of(x)) == Color::Red {
+            let p = self._parent_of(x);
+            let gp = self._parent_of(p);
+            let is_left = p == self._left_of(gp);
+            let uncle = if is_left {
+                self._right_of(gp)
+            } else {
+                self._left_of(gp)
+            };
+            if self._color_of(uncle) == Color::Red {
+                self._set_color(p, Color::Black);
+                self._set_color(uncle, Color::Black);
+                self._set_color(gp, Color::Red);
+                x = gp;
+                continue;
+            }
+            if is_left {
+                if x == self._right_of(p) {
+                    x = p;
+                    //左旋this
+                    self._rotate_left(x);
+                }
+                self._set_color(self._parent_of(x), Color::Black);
+                self._set_color(gp, Color::Red);
+                //右旋gp
+                self._rotate_right(gp);
+            } else {
+                if x == self._left_of(p) {
+                    x = p;
+                    //右旋this
+                    self._rotate_right(x);
+                }
+                self._set_color(self._parent_of(x), Color::Black);
+                self._set_color(gp, Color::Red);
+                //左旋gp
+                self._rotate_left(gp);
+            }
+        }
+        self._set_color(self._root, Color::Black);
+    }
+
+    fn _fix_after_del(&mut self, mut x: u32) {
+        while x != self._root && self._color_of(x) == Color::Black {
+            if x == self._left_of(self._parent_of(x)) {
+                let mut sib = self._right_of(self._parent_of(x));
+
+                if self._color_of(sib) == Color::Red {
+                    self._set_color(sib, Color::Black);
+                    self._set_color(self._parent_of(x), Color::Red);
+                    self._rotate_left(self._parent_of(x));
+                    sib = self._right_of(self._parent_of(x));
+                }
+
+                if self._color_of(self._left_of(sib)) == Color::Black
+                    && self._color_of(self._right_of(sib)) == Color::Black
+                {
+                    self._set_color(sib, Color::Red);
+                    x = self._parent_of(x);
+                } else {
+                    if self._color_of(self._right_of(sib)) == Color::Black {
+                        self._set_color(self._left_of(sib), Color::Black);
+                        self._set_color(sib, Color::Red);
+                        self._rotate_right(sib);
+                        sib = self._right_of(self._parent_of(x));
+                    }
+
+                    self._set_color(sib, self._color_of(self._parent_of(x)));
+                    self._set_color(self._parent_of(x), Color::Black);
+                    self._set_color(self._right_of(sib), Color::Black);
+                    self._rotate_left(self._parent_of(x));
+                    x = self._root;
+                }
+            } else {
+                let mut sib = self._left_of(self._parent_of(x));
+
+                if self._color_of(sib) == Color::Red {
+                    self._set_color(sib, Color::Black);
+                    self._set_color(self._parent_of(x), Color::Red);
+                    self._rotate_right(self._parent_of(x));
+                    sib = self._left_of(self._parent_of(x));
+                }
+
+                if self._color_of(self._right_of(sib)) == Color::Black
+                    && self._color_of(self._left_of(sib)) == Color::Black
+                {
+                    self._set_color(sib, Color::Red);
+                    x = self._parent_of(x);
+                } else {
+                    if self._color_of(self._left_of(sib)) == Color::Black {
+                        self._set_color(self._right_of(sib), Color::Black);
+                        self._set_color(sib, Color::Red);
+                        self._rotate_left(sib);
+                        sib = self._left_of(self._parent_of(x));
+                    }
+
+                    self._set_color(sib, self._color_of(self._parent_of(x)));
+                    self._set_color(self._parent_of(x), Color::Black);
+                    self._set_color(self._left_of(sib), Color::Black);
+                    self._rotate_right(self._parent_of(x));
+                    x = self._root;
+                }
+            }
+        }
+        self._set_color(x, Color::Black);
     }
 
-    pub fn delete(&mut self, val: &T) {
-        _delete(&mut self._root, val);
+    //插入val，已存在时返回NIL，否则返回新节点
+    fn _insert(&mut self, val: T) -> u32
+    where
+        T: Ord,
+    {
+        let mut parent = NIL;
+        let mut cur = self._root;
+        let mut ord = Ordering::Equal;
+        while cur != NIL {
+            parent = cur;
+            ord = self._val_of(cur).unwrap().cmp(&val);
+            cur = match ord {
+                Ordering::Less => self._right_of(cur),
+                Ordering::Greater => self._left_of(cur),
+                Ordering::Equal => return NIL,
+            };
+        }
+        let x = self._alloc(val, Color::Red, parent);
+        if parent == NIL {
+            self._root = x;
+        } else if ord == Ordering::Less {
+            self._node_mut(parent)._right = x;
+        } else {
+            self._node_mut(parent)._left = x;
+        }
+        self._len += 1;
+        self._pull_up_path(x);
+        self._fix_after_insert(x);
+        x
+    }
+
+    /// 参考java
+    /// 此处对我的难点是当去掉一个中间的节点之后，是用它的左节点还是右节点作为新的中间节点呢
+    /// 有两个子节点时与后继交换值，转为删除后继(后继最多只有一个右子节点)
+    /// 返回node原本的值
+    fn _delete_node(&mut self, mut node: u32) -> T {
+        if self._left_of(node) != NIL && self._right_of(node) != NIL {
+            let s = self._successor(node);
+            let sv = self._node_mut(s)._val.take();
+            let nv = std::mem::replace(&mut self._node_mut(node)._val, sv);
+            self._node_mut(s)._val = nv;
+            node = s;
+        }
+        let replacement = if self._left_of(node) != NIL {
+            self._left_of(node)
+        } else {
+            self._right_of(node)
+        };
+        let parent = self._parent_of(node);
+        let color = self._color_of(node);
+        //取出值，node之后若仍挂在树上则视为空节点
+        let val = self._node_mut(node)._val.take().unwrap();
+        self._node_mut(node)._size = 0;
+
+        if replacement != NIL {
+            self._node_mut(replacement)._parent = parent;
+            self._replace_child(parent, node, replacement);
+            //先更新祖先的子树信息，之后的旋转会据此重算
+            self._pull_up_path(parent);
+            if color == Color::Black {
+                self._fix_after_del(replacement);
+            }
+        } else if parent == NIL {
+            self._root = NIL;
+        } else {
+            self._pull_up_path(parent);
+            if color == Color::Black {
+                self._fix_after_del(node);
+            }
+            //调整后父节点可能已变化
+            let parent = self._parent_of(node);
+            self._replace_child(parent, node, NIL);
+        }
+        self._dealloc(node);
+        self._len -= 1;
+        val
+    }
+    //删除f(x)为Equal的节点，返回删除的值
+    fn _remove_by<F: Fn(&T) -> Ordering>(&mut self, f: F) -> Option<T> {
+        let node = self._get_node_by(f);
+        if node == NIL {
+            return None;
+        }
+        Some(self._delete_node(node))
     }
 }
 
-impl<T: Clone + Debug> RBTree<T> {
-    fn _check(&self) -> bool {
-        if let Some(x) = &self._root {
-            let x_borrow = x.borrow();
-            if x_borrow._color == Color::Red {
-                return false;
+impl<T: Ord, A: RBAugment<T>> RBTree<T, A> {
+    fn get_node(&self, t: &T) -> u32 {
+        self._get_node_by(|x| x.cmp(t))
+    }
+    //是否包含t
+    pub fn contains(&self, t: &T) -> bool {
+        self.get_node(t) != NIL
+    }
+    //t所在节点的深度(根为0)，不存在时返回None
+    pub fn depth(&self, t: &T) -> Option<usize> {
+        let mut depth = 0;
+        let mut x = self._root;
+        while x != NIL {
+            x = match t.cmp(self._val_of(x)?) {
+                std::cmp::Ordering::Equal => return Some(depth),
+                std::cmp::Ordering::Less => self._left_of(x),
+                std::cmp::Ordering::Greater => self._right_of(x),
+            };
+            depth += 1;
+        }
+        None
+    }
+    //插入，已存在时返回false
+    pub fn insert(&mut self, val: T) -> bool {
+        self._insert(val) != NIL
+    }
+    //删除，返回删除的值
+    pub fn delete(&mut self, val: &T) -> Option<T> {
+        self._remove_by(|x| x.cmp(val))
+    }
+}
+
+impl<T: Ord> RBTree<T> {
+    //由从小到大排好序的值构造，O(n)，相邻的相等值只保留一个
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::_from_sorted(iter)
+    }
+}
+
+/// 基于join的集合运算(参考Blelloch, Just Join for Parallel Ordered Sets)
+/// split/join只沿树高移动节点，并、交、差对m、n(m <= n)个值为O(m log(n/m + 1))
+/// 两棵树各自占用一个数组，运算前先把较小的树移入较大的树的数组，这一步为O(m)
+impl<T: Ord, A: RBAugment<T>> RBTree<T, A> {
+    fn _from_sorted<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vals: Vec<Option<T>> = vec![];
+        for v in iter {
+            if let Some(Some(last)) = vals.last() {
+                match last.cmp(&v) {
+                    Ordering::Less => {}
+                    Ordering::Equal => continue,
+                    Ordering::Greater => panic!("{}", NOT_SORTED),
+                }
+            }
+            vals.push(Some(v));
+        }
+        let mut tree = Self::_empty();
+        tree._nodes.reserve(vals.len());
+        //取中间值为根时，除最深一层外都是满的，最深一层设为红即可使黑高相同
+        let full = (usize::BITS - 1 - (vals.len() + 1).leading_zeros()) as usize;
+        let root = tree._build(&mut vals, 0, full);
+        tree._set_root(root);
+        tree
+    }
+    //用vals构造子树，depth为当前深度，深度为red_depth的节点为红
+    fn _build(&mut self, vals: &mut [Option<T>], depth: usize, red_depth: usize) -> u32 {
+        if vals.is_empty() {
+            return NIL;
+        }
+        let (left, rest) = vals.split_at_mut(vals.len() / 2);
+        let (mid, right) = rest.split_first_mut().unwrap();
+        let color = if depth == red_depth {
+            Color::Red
+        } else {
+            Color::Black
+        };
+        let l = self._build(left, depth + 1, red_depth);
+        let x = self._alloc(mid.take().unwrap(), color, NIL);
+        let r = self._build(right, depth + 1, red_depth);
+        self._link(l, x, r, color)
+    }
+
+    //并集，值相同时保留self中的
+    pub fn union(self, other: Self) -> Self {
+        self._combine(other, Self::_union)
+    }
+    //交集，保留self中的值
+    pub fn intersection(self, other: Self) -> Self {
+        self._combine(other, Self::_intersection)
+    }
+    //差集，self中不在other中的值
+    pub fn difference(self, other: Self) -> Self {
+        self._combine(other, Self::_difference)
+    }
+    //按key拆分为(小于key的部分, key本身, 大于key的部分)
+    //较小的一部分会被移到新的数组，O(log n + min(左, 右))
+    pub fn split_at(mut self, key: &T) -> (Self, Option<T>, Self) {
+        let (l, m, r) = self._split(self._root, &|x: &T| x.cmp(key));
+        let val = if m == NIL {
+            None
+        } else {
+            let v = self._node_mut(m)._val.take();
+            self._dealloc(m);
+            v
+        };
+        let mut other = Self::_empty();
+        if self._size_of(l) <= self._size_of(r) {
+            let nl = self._move_subtree(l, &mut other);
+            other._set_root(nl);
+            self._set_root(r);
+            (other, val, self)
+        } else {
+            let nr = self._move_subtree(r, &mut other);
+            other._set_root(nr);
+            self._set_root(l);
+            (self, val, other)
+        }
+    }
+    //以pivot连接两棵树，要求left中的值都小于pivot，right中的值都大于pivot
+    pub fn join(mut left: Self, pivot: T, mut right: Self) -> Self {
+        let ordered = left
+            ._val_of(left._last_node(left._root))
+            .is_none_or(|x| *x < pivot)
+            && right
+                ._val_of(right._first_node(right._root))
+                .is_none_or(|x| *x > pivot);
+        assert!(ordered, "{}", NOT_SORTED);
+        let (mut tree, l, r) = if left._len >= right._len {
+            let r = right._move_subtree(right._root, &mut left);
+            let l = left._root;
+            (left, l, r)
+        } else {
+            let l = left._move_subtree(left._root, &mut right);
+            let r = right._root;
+            (right, l, r)
+        };
+        let k = tree._alloc(pivot, Color::Red, NIL);
+        let root = tree._join(l, k, r);
+        tree._set_root(root);
+        tree
+    }
+
+    //把较小的树移入较大的树的数组，再以op(self的根, other的根)的结果为根
+    fn _combine(mut self, mut other: Self, op: fn(&mut Self, u32, u32) -> u32) -> Self {
+        if self._len >= other._len {
+            let (a, b) = (self._root, other._move_subtree(other._root, &mut self));
+            let root = op(&mut self, a, b);
+            self._set_root(root);
+            self
+        } else {
+            let (a, b) = (self._move_subtree(self._root, &mut other), other._root);
+            let root = op(&mut other, a, b);
+            other._set_root(root);
+            other
+        }
+    }
+    //a、b为同一数组中的两棵树
+    fn _union(&mut self, a: u32, b: u32) -> u32 {
+        if a == NIL {
+            return b;
+        }
+        if b == NIL {
+            return a;
+        }
+        let (bl, br, al, m, ar) = self._split_by_node(a, b);
+        let l = self._union(al, bl);
+        let r = self._union(ar, br);
+        let k = if m == NIL {
+            b
+        } else {
+            self._dealloc(b);
+            m
+        };
+        self._join(l, k, r)
+    }
+    fn _intersection(&mut self, a: u32, b: u32) -> u32 {
+        if a == NIL || b == NIL {
+            self._free_subtree(a);
+            self._free_subtree(b);
+            return NIL;
+        }
+        let (bl, br, al, m, ar) = self._split_by_node(a, b);
+        let l = self._intersection(al, bl);
+        let r = self._intersection(ar, br);
+        self._dealloc(b);
+        if m == NIL {
+            self._join2(l, r)
+        } else {
+            self._join(l, m, r)
+        }
+    }
+    fn _difference(&mut self, a: u32, b: u32) -> u32 {
+        if a == NIL || b == NIL {
+            self._free_subtree(b);
+            return a;
+        }
+        let (bl, br, al, m, ar) = self._split_by_node(a, b);
+        let l = self._difference(al, bl);
+        let r = self._difference(ar, br);
+        self._dealloc(b);
+        if m != NIL {
+            self._dealloc(m);
+        }
+        self._join2(l, r)
+    }
+    //取下b的左右子树，再用b的值拆分a，返回(b左, b右, a中较小部分, a中与b相等的节点, a中较大部分)
+    fn _split_by_node(&mut self, a: u32, b: u32) -> (u32, u32, u32, u32, u32) {
+        let (bl, br) = self._expose(b);
+        //b已从树上取下，暂时取出其值用于比较
+        let key = self._node_mut(b)._val.take().unwrap();
+        let (al, m, ar) = self._split(a, &|x: &T| x.cmp(&key));
+        self._node_mut(b)._val = Some(key);
+        (bl, br, al, m, ar)
+    }
+}
+
+impl<T, A: RBAugment<T>> RBTree<T, A> {
+    //以x为根设置整棵树，根设为黑不影响各路径黑高相同
+    fn _set_root(&mut self, x: u32) {
+        self._root = x;
+        if x != NIL {
+            self._node_mut(x)._parent = NIL;
+        }
+        self._set_color(x, Color::Black);
+        self._len = self._size_of(x);
+    }
+    //从x到nil路径上(含x)黑节点的个数
+    fn _black_height(&self, x: u32) -> usize {
+        let mut h = 0;
+        let mut cur = x;
+        while cur != NIL {
+            h += (self._color_of(cur) == Color::Black) as usize;
+            cur = self._left_of(cur);
+        }
+        h
+    }
+    //以k为根、l和r为左右子树组成一棵树
+    fn _link(&mut self, l: u32, k: u32, r: u32, color: Color) -> u32 {
+        let node = self._node_mut(k);
+        node._left = l;
+        node._right = r;
+        node._parent = NIL;
+        node._color = color;
+        for child in [l, r] {
+            if child != NIL {
+                self._node_mut(child)._parent = k;
+            }
+        }
+        self._pull_up(k);
+        k
+    }
+    //取下x的左右子树，各自成为独立的树
+    fn _expose(&mut self, x: u32) -> (u32, u32) {
+        let (l, r) = (self._left_of(x), self._right_of(x));
+        for child in [l, r] {
+            if child != NIL {
+                self._node_mut(child)._parent = NIL;
+            }
+        }
+        let node = self._node_mut(x);
+        node._left = NIL;
+        node._right = NIL;
+        node._parent = NIL;
+        (l, r)
+    }
+    //连接l、k、r，l中的值都小于k，r中的值都大于k
+    //沿较高一侧的边缘向下，找到黑高与另一侧相同的黑节点后接上红节点k，再向上修复红红相连
+    fn _join(&mut self, l: u32, k: u32, r: u32) -> u32 {
+        let (hl, hr) = (self._black_height(l), self._black_height(r));
+        match hl.cmp(&hr) {
+            Ordering::Greater => {
+                let t = self._join_right(l, hl, k, r, hr);
+                if self._color_of(t) == Color::Red
+                    && self._color_of(self._right_of(t)) == Color::Red
+                {
+                    self._set_color(t, Color::Black);
+                }
+                t
+            }
+            Ordering::Less => {
+                let t = self._join_left(l, hl, k, r, hr);
+                if self._color_of(t) == Color::Red && self._color_of(self._left_of(t)) == Color::Red
+                {
+                    self._set_color(t, Color::Black);
+                }
+                t
+            }
+            Ordering::Equal => {
+                let color =
+                    if self._color_of(l) == Color::Black && self._color_of(r) == Color::Black {
+                        Color::Red
+                    } else {
+                        Color::Black
+                    };
+                self._link(l, k, r, color)
+            }
+        }
+    }
+    //hl >= hr，沿l的右边缘向下
+    fn _join_right(&mut self, l: u32, hl: usize, k: u32, r: u32, hr: usize) -> u32 {
+        let color = self._color_of(l);
+        if color == Color::Black && hl == hr {
+            return self._link(l, k, r, Color::Red);
+        }
+        let (ll, lr) = self._expose(l);
+        let hc = hl - (color == Color::Black) as usize;
+        let c = self._join_right(lr, hc, k, r, hr);
+        if color == Color::Black
+            && self._color_of(c) == Color::Red
+            && self._color_of(self._right_of(c)) == Color::Red
+        {
+            //红红相连，以l左旋
+            let (cl, cr) = self._expose(c);
+            self._set_color(cr, Color::Black);
+            let left = self._link(ll, l, cl, Color::Black);
+            return self._link(left, c, cr, Color::Red);
+        }
+        self._link(ll, l, c, color)
+    }
+    //hl < hr，沿r的左边缘向下
+    fn _join_left(&mut self, l: u32, hl: usize, k: u32, r: u32, hr: usize) -> u32 {
+        let color = self._color_of(r);
+        if color == Color::Black && hl == hr {
+            return self._link(l, k, r, Color::Red);
+        }
+        let (rl, rr) = self._expose(r);
+        let hc = hr - (color == Color::Black) as usize;
+        let c = self._join_left(l, hl, k, rl, hc);
+        if color == Color::Black
+            && self._color_of(c) == Color::Red
+            && self._color_of(self._left_of(c)) == Color::Red
+        {
+            //红红相连，以r右旋
+            let (cl, cr) = self._expose(c);
+            self._set_color(cl, Color::Black);
+            let right = self._link(cr, r, rr, Color::Black);
+            return self._link(cl, c, right, Color::Red);
+        }
+        self._link(c, r, rr, color)
+    }
+    //按f拆分x为根的树，f(x)为x与目标的比较结果，返回(小于部分, 相等的节点, 大于部分)
+    fn _split<F: Fn(&T) -> Ordering>(&mut self, x: u32, f: &F) -> (u32, u32, u32) {
+        if x == NIL {
+            return (NIL, NIL, NIL);
+        }
+        let (l, r) = self._expose(x);
+        match f(self._val_of(x).unwrap()) {
+            Ordering::Equal => (l, x, r),
+            Ordering::Greater => {
+                let (ll, m, lr) = self._split(l, f);
+                let right = self._join(lr, x, r);
+                (ll, m, right)
+            }
+            Ordering::Less => {
+                let (rl, m, rr) = self._split(r, f);
+                let left = self._join(l, x, rl);
+                (left, m, rr)
+            }
+        }
+    }
+    //拆出最大的节点，返回(其余部分, 最大节点)
+    fn _split_last(&mut self, x: u32) -> (u32, u32) {
+        let (l, r) = self._expose(x);
+        if r == NIL {
+            return (l, x);
+        }
+        let (rest, last) = self._split_last(r);
+        (self._join(l, x, rest), last)
+    }
+    //连接l和r，l中的值都小于r
+    fn _join2(&mut self, l: u32, r: u32) -> u32 {
+        if l == NIL {
+            return r;
+        }
+        let (rest, last) = self._split_last(l);
+        self._join(rest, last, r)
+    }
+    //把x为根的子树移到dst中，返回在dst中的根
+    //子树结构不变，大小和附加信息无需重新计算
+    fn _move_subtree(&mut self, x: u32, dst: &mut Self) -> u32 {
+        if x == NIL {
+            return NIL;
+        }
+        let l = self._move_subtree(self._left_of(x), dst);
+        let r = self._move_subtree(self._right_of(x), dst);
+        let node = std::mem::take(self._node_mut(x));
+        self._free.push(x);
+        let y = dst._put(RBNode {
+            _left: l,
+            _right: r,
+            _parent: NIL,
+            ..node
+        });
+        for child in [l, r] {
+            if child != NIL {
+                dst._node_mut(child)._parent = y;
             }
-            let mut count = 0;
-            x_borrow._count(&mut count);
-            _check(&self._root.clone(), count, 0)
+        }
+        y
+    }
+    //回收x为根的整棵子树
+    fn _free_subtree(&mut self, x: u32) {
+        if x == NIL {
+            return;
+        }
+        self._free_subtree(self._left_of(x));
+        self._free_subtree(self._right_of(x));
+        self._dealloc(x);
+    }
+}
+
+impl<T, A> RBTree<T, A> {
+    //最小值
+    pub fn first(&self) -> Option<&T> {
+        self._val_of(self._first_node(self._root))
+    }
+    //最大值
+    pub fn last(&self) -> Option<&T> {
+        self._val_of(self._last_node(self._root))
+    }
+    //第k小的值，k从0开始
+    pub fn select(&self, mut k: usize) -> Option<&T> {
+        let mut cur = self._root;
+        while cur != NIL {
+            let left = self._size_of(self._left_of(cur));
+            match k.cmp(&left) {
+                Ordering::Less => cur = self._left_of(cur),
+                Ordering::Equal => return self._val_of(cur),
+                Ordering::Greater => {
+                    k -= left + 1;
+                    cur = self._right_of(cur);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<T: Ord, A> RBTree<T, A> {
+    //小于t的值的个数，即t从0开始的排名
+    pub fn rank(&self, t: &T) -> usize {
+        self._count_less_by(|x| x.cmp(t))
+    }
+    //范围内值的个数
+    pub fn count_range<R: RangeBounds<T>>(&self, range: R) -> usize {
+        let start = match range.start_bound() {
+            Bound::Included(s) => self.rank(s),
+            Bound::Excluded(s) => self._count_less_by(_le(s)),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(e) => self._count_less_by(_le(e)),
+            Bound::Excluded(e) => self.rank(e),
+            Bound::Unbounded => self._len,
+        };
+        end.saturating_sub(start)
+    }
+    //按范围从小到大遍历
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Iter<'_, T, A> {
+        let front = match range.start_bound() {
+            Bound::Included(s) => self._first_not_less_by(|x| x.cmp(s)),
+            Bound::Excluded(s) => self._first_not_less_by(_le(s)),
+            Bound::Unbounded => self._first_node(self._root),
+        };
+        let back = match range.end_bound() {
+            Bound::Included(e) => self._last_less_by(_le(e)),
+            Bound::Excluded(e) => self._last_less_by(|x| x.cmp(e)),
+            Bound::Unbounded => self._last_node(self._root),
+        };
+        //起点在终点之后说明范围为空
+        let empty = match (self._val_of(front), self._val_of(back)) {
+            (Some(f), Some(b)) => f > b,
+            _ => true,
+        };
+        if empty {
+            return Iter {
+                _tree: self,
+                _front: NIL,
+                _back: NIL,
+            };
+        }
+        Iter {
+            _tree: self,
+            _front: front,
+            _back: back,
+        }
+    }
+}
+
+impl<T: Ord, A> RBTree<T, A> {
+    //小于等于t的最大值
+    pub fn floor(&self, t: &T) -> Option<&T> {
+        self._val_of(self._last_less_by(_le(t)))
+    }
+    //大于等于t的最小值
+    pub fn ceiling(&self, t: &T) -> Option<&T> {
+        self._val_of(self._first_not_less_by(|x| x.cmp(t)))
+    }
+    //小于t的最大值
+    pub fn predecessor(&self, t: &T) -> Option<&T> {
+        self._val_of(self._last_less_by(|x| x.cmp(t)))
+    }
+    //大于t的最小值
+    pub fn successor(&self, t: &T) -> Option<&T> {
+        self._val_of(self._first_not_less_by(_le(t)))
+    }
+}
+
+//小于等于t时为Less，用于把floor/successor转为严格比较
+fn _le<T: Ord>(t: &T) -> impl Fn(&T) -> Ordering + '_ {
+    move |x| match x.cmp(t) {
+        Ordering::Greater => Ordering::Greater,
+        _ => Ordering::Less,
+    }
+}
+
+/// 红黑树迭代器，沿_parent/_left/_right下标移动，返回值的引用
+pub struct Iter<'a, T, A = ()> {
+    _tree: &'a RBTree<T, A>,
+    _front: u32,
+    _back: u32,
+}
+
+impl<'a, T, A> Iterator for Iter<'a, T, A> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let val = self._tree._val_of(self._front)?;
+        //两端相遇则结束
+        if self._front == self._back {
+            self._front = NIL;
+            self._back = NIL;
         } else {
-            true
+            self._front = self._tree._successor(self._front);
+        }
+        Some(val)
+    }
+}
+
+impl<T, A> DoubleEndedIterator for Iter<'_, T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let val = self._tree._val_of(self._back)?;
+        if self._front == self._back {
+            self._front = NIL;
+            self._back = NIL;
+        } else {
+            self._back = self._tree._predecessor(self._back);
+        }
+        Some(val)
+    }
+}
+
+/// 红黑树的消耗型迭代器，按从小到大的顺序移出所有值
+pub struct IntoIter<T> {
+    _vals: std::vec::IntoIter<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self._vals.next()
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self._vals.next_back()
+    }
+}
+
+impl<T, A> IntoIterator for RBTree<T, A> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    //中序遍历取出所有值
+    fn into_iter(mut self) -> Self::IntoIter {
+        let mut vals = Vec::with_capacity(self._len);
+        let mut cur = self._first_node(self._root);
+        while cur != NIL {
+            if let Some(v) = self._node_mut(cur)._val.take() {
+                vals.push(v);
+            }
+            cur = self._successor(cur);
+        }
+        IntoIter {
+            _vals: vals.into_iter(),
         }
     }
 }
 
+impl<'a, T, A> IntoIterator for &'a RBTree<T, A> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Ord + Clone, A> RBTree<T, A> {
+    //检查红黑树的所有性质，返回第一个不满足的性质及其所在节点的值
+    pub fn validate(&self) -> Result<(), RBViolation<T>> {
+        if self._root != NIL {
+            let val = || self._val_of(self._root).unwrap().clone();
+            if self._color_of(self._root) == Color::Red {
+                return Err(RBViolation::RedRoot(val()));
+            }
+            if self._parent_of(self._root) != NIL {
+                return Err(RBViolation::BrokenParent(val()));
+            }
+        }
+        let (_, size) = self._validate(self._root, None, None)?;
+        if size != self._len {
+            return Err(RBViolation::Len {
+                len: self._len,
+                actual: size,
+            });
+        }
+        Ok(())
+    }
+
+    //递归检查x为根的子树，返回(黑高, 节点数)
+    //lo、hi为祖先给出的取值范围(不含)
+    fn _validate(
+        &self,
+        x: u32,
+        lo: Option<&T>,
+        hi: Option<&T>,
+    ) -> Result<(usize, usize), RBViolation<T>> {
+        if x == NIL {
+            return Ok((1, 0));
+        }
+        let node = self._node(x);
+        let val = node._val.as_ref().unwrap();
+        let bound = lo.filter(|l| val <= *l).or(hi.filter(|h| val >= *h));
+        if let Some(b) = bound {
+            return Err(RBViolation::Order {
+                node: val.clone(),
+                bound: b.clone(),
+            });
+        }
+        for child in [node._left, node._right] {
+            if child == NIL {
+                continue;
+            }
+            let child_val = || self._val_of(child).unwrap().clone();
+            if self._parent_of(child) != x {
+                return Err(RBViolation::BrokenParent(child_val()));
+            }
+            if node._color == Color::Red && self._color_of(child) == Color::Red {
+                return Err(RBViolation::RedRed {
+                    parent: val.clone(),
+                    child: child_val(),
+                });
+            }
+        }
+        let (left, lsize) = self._validate(node._left, lo, Some(val))?;
+        let (right, rsize) = self._validate(node._right, Some(val), hi)?;
+        if left != right {
+            return Err(RBViolation::BlackHeight {
+                node: val.clone(),
+                left,
+                right,
+            });
+        }
+        if node._size != lsize + rsize + 1 {
+            return Err(RBViolation::Size {
+                node: val.clone(),
+                size: node._size,
+                actual: lsize + rsize + 1,
+            });
+        }
+        let black = (node._color == Color::Black) as usize;
+        Ok((left + black, lsize + rsize + 1))
+    }
+}
+
+/// validate检查出的红黑树性质错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RBViolation<T> {
+    //根节点为红
+    RedRoot(T),
+    //红节点的子节点为红
+    RedRed { parent: T, child: T },
+    //左右子树的黑高不等
+    BlackHeight { node: T, left: usize, right: usize },
+    //子节点的_parent没有指向该节点
+    BrokenParent(T),
+    //不满足左小右大，bound为违反的祖先节点值
+    Order { node: T, bound: T },
+    //记录的子树大小与实际不符
+    Size { node: T, size: usize, actual: usize },
+    //记录的长度与实际节点数不符
+    Len { len: usize, actual: usize },
+}
+
 impl<T> RBNode<T> {
-    pub fn new(val: T, color: Color, parent: RBInnerNode<T>) -> Self {
+    pub fn new(val: T, color: Color, parent: u32) -> Self {
         Self {
-            _left: None,
-            _right: None,
+            _left: NIL,
+            _right: NIL,
             _parent: parent,
             _val: Some(val),
             _color: color,
+            _size: 1,
         }
     }
     pub fn new_red(val: T) -> Self {
-        Self::new(val, Color::Red, None)
+        Self::new(val, Color::Red, NIL)
     }
     pub fn new_black(val: T) -> Self {
-        Self::new(val, Color::Black, None)
+        Self::new(val, Color::Black, NIL)
     }
-    //统计最左侧链的黑色总数
-    fn _count(&self, count: &mut usize) {
-        if let Some(x) = &self._left {
-            if x.borrow()._color == Color::Black {
-                *count += 1;
+}
+
+/// 红黑树映射
+/// 键值对直接存放在树的节点中，get/get_mut/entry返回节点内值的引用
+pub struct RBMap<K, V> {
+    _tree: RBTree<RBSlot<K, V>>,
+}
+
+//树中存放的键值对，只按_key比较
+#[derive(Debug, Clone)]
+struct RBSlot<K, V> {
+    _key: K,
+    _val: V,
+}
+
+impl<K, V> RBMap<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    //长度
+    pub fn len(&self) -> usize {
+        self._tree.len()
+    }
+    //为空返回true
+    pub fn is_empty(&self) -> bool {
+        self._tree.is_empty()
+    }
+    //清空
+    pub fn clear(&mut self) {
+        self._tree.clear();
+    }
+
+    fn _slot(&self, x: u32) -> &RBSlot<K, V> {
+        self._tree._node(x)._val.as_ref().unwrap()
+    }
+    fn _slot_mut(&mut self, x: u32) -> &mut RBSlot<K, V> {
+        self._tree._node_mut(x)._val.as_mut().unwrap()
+    }
+}
+
+impl<K: Ord, V> RBMap<K, V> {
+    //k所在节点的下标
+    fn _find(&self, k: &K) -> Option<u32> {
+        Some(self._tree._get_node_by(|s| s._key.cmp(k))).filter(|x| *x != NIL)
+    }
+    //是否包含k
+    pub fn contains_key(&self, k: &K) -> bool {
+        self._find(k).is_some()
+    }
+    pub fn get(&self, k: &K) -> Option<&V> {
+        let x = self._find(k)?;
+        Some(&self._slot(x)._val)
+    }
+    pub fn get_mut(&mut self, k: &K) -> Option<&mut V> {
+        let x = self._find(k)?;
+        Some(&mut self._slot_mut(x)._val)
+    }
+    //插入，已存在时替换并返回旧值
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        if let Some(x) = self._find(&k) {
+            return Some(std::mem::replace(&mut self._slot_mut(x)._val, v));
+        }
+        self._tree._insert(RBSlot { _key: k, _val: v });
+        None
+    }
+    //删除，返回删除的值
+    pub fn remove(&mut self, k: &K) -> Option<V> {
+        self.remove_entry(k).map(|(_, v)| v)
+    }
+    //删除，返回删除的键值对
+    pub fn remove_entry(&mut self, k: &K) -> Option<(K, V)> {
+        let slot = self._tree._remove_by(|s| s._key.cmp(k))?;
+        Some((slot._key, slot._val))
+    }
+    //获取k对应的entry，用于原地修改或插入
+    pub fn entry(&mut self, k: K) -> Entry<'_, K, V> {
+        match self._find(&k) {
+            Some(x) => Entry::Occupied(OccupiedEntry {
+                _map: self,
+                _node: x,
+            }),
+            None => Entry::Vacant(VacantEntry {
+                _map: self,
+                _key: k,
+            }),
+        }
+    }
+}
+
+/// RBMap中的一个位置，可能已有值(Occupied)或为空(Vacant)
+pub enum Entry<'a, K, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+pub struct OccupiedEntry<'a, K, V> {
+    _map: &'a mut RBMap<K, V>,
+    //键值对所在节点的下标
+    _node: u32,
+}
+
+pub struct VacantEntry<'a, K, V> {
+    _map: &'a mut RBMap<K, V>,
+    _key: K,
+}
+
+impl<'a, K: Ord, V> Entry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => e.key(),
+        }
+    }
+    //为空时插入v
+    pub fn or_insert(self, v: V) -> &'a mut V {
+        self.or_insert_with(|| v)
+    }
+    //为空时插入f()
+    pub fn or_insert_with<F: FnOnce() -> V>(self, f: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(f()),
+        }
+    }
+    //已有值时修改
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(e) = &mut self {
+            f(e.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K: Ord, V: Default> Entry<'a, K, V> {
+    //为空时插入默认值
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K: Ord, V> OccupiedEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self._map._slot(self._node)._key
+    }
+    pub fn get(&self) -> &V {
+        &self._map._slot(self._node)._val
+    }
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self._map._slot_mut(self._node)._val
+    }
+    //转为与map生命周期相同的可变引用
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self._map._slot_mut(self._node)._val
+    }
+    //替换值，返回旧值
+    pub fn insert(&mut self, v: V) -> V {
+        std::mem::replace(self.get_mut(), v)
+    }
+    //删除，返回删除的值
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+    //删除，返回删除的键值对
+    pub fn remove_entry(self) -> (K, V) {
+        let slot = self._map._tree._delete_node(self._node);
+        (slot._key, slot._val)
+    }
+}
+
+impl<'a, K: Ord, V> VacantEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self._key
+    }
+    pub fn into_key(self) -> K {
+        self._key
+    }
+    //插入值，返回其可变引用
+    pub fn insert(self, v: V) -> &'a mut V {
+        //插入后的旋转只改变链接，新节点的下标不变
+        let x = self._map._tree._insert(RBSlot {
+            _key: self._key,
+            _val: v,
+        });
+        &mut self._map._slot_mut(x)._val
+    }
+}
+
+impl<K: PartialEq, V> PartialEq for RBSlot<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self._key == other._key
+    }
+}
+
+impl<K: Eq, V> Eq for RBSlot<K, V> {}
+
+impl<K: Ord, V> PartialOrd for RBSlot<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, V> Ord for RBSlot<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self._key.cmp(&other._key)
+    }
+}
+
+impl<K, V> Default for RBMap<K, V> {
+    fn default() -> Self {
+        Self {
+            _tree: RBTree::default(),
+        }
+    }
+}
+
+/// 区间树，区间为左闭右开[start, end)
+/// 按(start, end)排序，每个节点额外记录子树中最大的end，用于跳过不可能重叠的子树
+pub struct IntervalTree<K, V> {
+    _tree: RBTree<IntervalEntry<K, V>, MaxEnd>,
+}
+
+//区间树节点中存放的值，只按(start, end)比较
+#[derive(Debug, Clone)]
+struct IntervalEntry<K, V> {
+    _start: K,
+    _end: K,
+    _val: V,
+    //子树中最大的end
+    _max: K,
+}
+
+//维护子树最大end的附加信息
+#[derive(Clone)]
+struct MaxEnd;
+
+impl<K: Ord + Clone, V> RBAugment<IntervalEntry<K, V>> for MaxEnd {
+    fn update(
+        val: &mut IntervalEntry<K, V>,
+        left: Option<&IntervalEntry<K, V>>,
+        right: Option<&IntervalEntry<K, V>>,
+    ) {
+        let mut max = &val._end;
+        for child in [left, right].into_iter().flatten() {
+            if child._max > *max {
+                max = &child._max;
             }
-            x.borrow()._count(count)
         }
+        val._max = max.clone();
+    }
+}
+
+impl<K, V> IntervalTree<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    //长度
+    pub fn len(&self) -> usize {
+        self._tree.len()
+    }
+    //为空返回true
+    pub fn is_empty(&self) -> bool {
+        self._tree.is_empty()
+    }
+    //清空
+    pub fn clear(&mut self) {
+        self._tree.clear();
     }
-    //判断节点是否符合要求
-    fn _judge_node(&self, child: &Self, count: &mut usize) -> bool {
-        //性质4
-        if self._color == Color::Red && child._color == Color::Red {
+}
+
+impl<K: Ord + Clone, V> IntervalTree<K, V> {
+    //按区间查找节点
+    fn _get_node(&self, range: &Range<K>) -> u32 {
+        self._tree
+            ._get_node_by(|e| (&e._start, &e._end).cmp(&(&range.start, &range.end)))
+    }
+    //是否包含区间
+    pub fn contains(&self, range: &Range<K>) -> bool {
+        self._get_node(range) != NIL
+    }
+    //插入区间，已存在时替换并返回旧值
+    pub fn insert(&mut self, range: Range<K>, v: V) -> Option<V> {
+        assert!(range.start < range.end, "{}", EMPTY_INTERVAL);
+        let x = self._get_node(&range);
+        if x != NIL {
+            let e = self._tree._node_mut(x)._val.as_mut().unwrap();
+            return Some(std::mem::replace(&mut e._val, v));
+        }
+        self._tree._insert(IntervalEntry {
+            _max: range.end.clone(),
+            _start: range.start,
+            _end: range.end,
+            _val: v,
+        });
+        None
+    }
+    //删除区间，返回删除的值
+    pub fn remove(&mut self, range: &Range<K>) -> Option<V> {
+        let entry = self
+            ._tree
+            ._remove_by(|e| (&e._start, &e._end).cmp(&(&range.start, &range.end)))?;
+        Some(entry._val)
+    }
+    //是否有区间与query重叠，O(log n)
+    pub fn any_overlap(&self, query: &Range<K>) -> bool {
+        if query.start >= query.end {
             return false;
         }
-        //性质5
-        if child._color == Color::Black {
-            *count += 1;
+        let t = &self._tree;
+        let mut cur = t._root;
+        while cur != NIL {
+            let e = t._val_of(cur).unwrap();
+            if e._start < query.end && query.start < e._end {
+                return true;
+            }
+            //左子树中有end大于query.start的区间时，若左子树没有重叠，右子树的start更大也不会重叠
+            let left = t._left_of(cur);
+            cur = match t._val_of(left) {
+                Some(l) if l._max > query.start => left,
+                _ => t._right_of(cur),
+            };
         }
-        true
+        false
     }
+}
 
-    //变色
-    fn _change_color(&mut self, pos: Pos) {
-        self._color = Color::Black;
-        if let Some(x) = &self._parent {
-            x.borrow_mut()._color = Color::Red;
-            match pos {
-                Pos::Left => {
-                    if let Some(y) = &x.borrow_mut()._right {
-                        y.borrow_mut()._color = Color::Black;
-                    }
-                }
-                Pos::Right => {
-                    if let Some(y) = &x.borrow_mut()._left {
-                        y.borrow_mut()._color = Color::Black;
-                    }
+impl<K: Ord, V> IntervalTree<K, V> {
+    //与query重叠的所有区间，按(start, end)从小到大
+    pub fn overlapping<'a>(&'a self, query: &'a Range<K>) -> Overlapping<'a, K, V> {
+        let mut it = Overlapping {
+            _tree: &self._tree,
+            _stack: vec![],
+            _lo: &query.start,
+            _hi: Bound::Excluded(&query.end),
+        };
+        if query.start < query.end {
+            it._push_left(self._tree._root);
+        }
+        it
+    }
+    //包含point的所有区间，按(start, end)从小到大
+    pub fn stabbing<'a>(&'a self, point: &'a K) -> Overlapping<'a, K, V> {
+        let mut it = Overlapping {
+            _tree: &self._tree,
+            _stack: vec![],
+            _lo: point,
+            _hi: Bound::Included(point),
+        };
+        it._push_left(self._tree._root);
+        it
+    }
+}
+
+impl<K, V> IntervalTree<K, V> {
+    //从小到大的所有区间
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (Range<&K>, &V)> + '_ {
+        self._tree.iter().map(|e| (&e._start..&e._end, &e._val))
+    }
+}
+
+/// 区间树的查询迭代器，中序返回end大于lo且start在hi之内的区间
+/// 栈中记录尚未访问的祖先，子树最大end不大于lo时整棵子树跳过
+pub struct Overlapping<'a, K, V> {
+    _tree: &'a RBTree<IntervalEntry<K, V>, MaxEnd>,
+    _stack: Vec<u32>,
+    _lo: &'a K,
+    _hi: Bound<&'a K>,
+}
+
+impl<K: Ord, V> Overlapping<'_, K, V> {
+    //压入x及其左链中可能有重叠的节点
+    fn _push_left(&mut self, mut x: u32) {
+        while let Some(e) = self._tree._val_of(x) {
+            if e._max <= *self._lo {
+                return;
+            }
+            self._stack.push(x);
+            x = self._tree._left_of(x);
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for Overlapping<'a, K, V> {
+    type Item = (Range<&'a K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(x) = self._stack.pop() {
+            let e = self._tree._val_of(x).unwrap();
+            let start_in = match self._hi {
+                Bound::Included(h) => e._start <= *h,
+                Bound::Excluded(h) => e._start < *h,
+                Bound::Unbounded => true,
+            };
+            //之后的区间start只会更大，都不在hi之内
+            if !start_in {
+                self._stack.clear();
+                return None;
+            }
+            self._push_left(self._tree._right_of(x));
+            if e._end > *self._lo {
+                return Some((&e._start..&e._end, &e._val));
+            }
+        }
+        None
+    }
+}
+
+impl<K: PartialEq, V> PartialEq for IntervalEntry<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self._start == other._start && self._end == other._end
+    }
+}
+
+impl<K: Eq, V> Eq for IntervalEntry<K, V> {}
+
+impl<K: Ord, V> PartialOrd for IntervalEntry<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, V> Ord for IntervalEntry<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self._start, &self._end).cmp(&(&other._start, &other._end))
+    }
+}
+
+impl<K, V> Default for IntervalTree<K, V> {
+    fn default() -> Self {
+        Self {
+            _tree: RBTree::_empty(),
+        }
+    }
+}
+
+/// 持久化(不可变)红黑树，用于撤销/重做、快照等需要保留历史版本的场景
+/// insert/remove不修改自身，而是返回新版本：只复制根到修改处路径上的节点，其余子树通过Rc与旧版本共享
+/// 因此每个版本都保持有效，保留一个版本只需O(1)，每次修改新增O(log n)个节点
+/// 插入删除采用函数式写法(参考Kahrs, Red-black trees with types)，节点没有父指针
+#[derive(Clone)]
+pub struct PersistentRBTree<T> {
+    _root: PInnerNode<T>,
+}
+
+type PInnerNode<T> = Option<Rc<PNode<T>>>;
+
+//创建后不再修改的节点
+struct PNode<T> {
+    _left: PInnerNode<T>,
+    _right: PInnerNode<T>,
+    _val: T,
+    _color: Color,
+    //以该节点为根的子树的节点数
+    _size: usize,
+}
+
+impl<T> PersistentRBTree<T> {
+    //最小值
+    pub fn first(&self) -> Option<&T> {
+        self.iter().next()
+    }
+    //最大值
+    pub fn last(&self) -> Option<&T> {
+        self.iter().next_back()
+    }
+    //第k小的值，k从0开始
+    pub fn select(&self, mut k: usize) -> Option<&T> {
+        let mut cur = self._root.as_deref();
+        while let Some(x) = cur {
+            let left = _psize(&x._left);
+            match k.cmp(&left) {
+                Ordering::Less => cur = x._left.as_deref(),
+                Ordering::Equal => return Some(&x._val),
+                Ordering::Greater => {
+                    k -= left + 1;
+                    cur = x._right.as_deref();
                 }
             }
         }
-        //子不需要变，因为本来就是红
+        None
+    }
+    pub fn new(val: T) -> Self {
+        Self {
+            _root: _pnode(Color::Black, None, val, None),
+        }
+    }
+    //长度
+    pub fn len(&self) -> usize {
+        _psize(&self._root)
+    }
+    //为空返回true
+    pub fn is_empty(&self) -> bool {
+        self._root.is_none()
+    }
+    //从小到大遍历，rev()则从大到小
+    pub fn iter(&self) -> PIter<'_, T> {
+        let mut it = PIter {
+            _front: vec![],
+            _back: vec![],
+            _len: self.len(),
+        };
+        it._push_left(self._root.as_deref());
+        it._push_right(self._root.as_deref());
+        it
+    }
+    //是否与other为同一版本(根节点相同)
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self._root, &other._root) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T: Ord> PersistentRBTree<T> {
+    //小于等于t的最大值
+    pub fn floor(&self, t: &T) -> Option<&T> {
+        _plast_less_by(&self._root, _le(t))
+    }
+    //大于等于t的最小值
+    pub fn ceiling(&self, t: &T) -> Option<&T> {
+        _pfirst_not_less_by(&self._root, |x| x.cmp(t))
+    }
+    //小于t的最大值
+    pub fn predecessor(&self, t: &T) -> Option<&T> {
+        _plast_less_by(&self._root, |x| x.cmp(t))
+    }
+    //大于t的最小值
+    pub fn successor(&self, t: &T) -> Option<&T> {
+        _pfirst_not_less_by(&self._root, _le(t))
+    }
+    //是否包含t
+    pub fn contains(&self, t: &T) -> bool {
+        let mut cur = self._root.as_deref();
+        while let Some(x) = cur {
+            cur = match x._val.cmp(t) {
+                Ordering::Equal => return true,
+                Ordering::Less => x._right.as_deref(),
+                Ordering::Greater => x._left.as_deref(),
+            };
+        }
+        false
+    }
+    //小于t的值的个数，即t从0开始的排名
+    pub fn rank(&self, t: &T) -> usize {
+        _pcount_less_by(&self._root, |x| x.cmp(t))
+    }
+    //范围内值的个数
+    pub fn count_range<R: RangeBounds<T>>(&self, range: R) -> usize {
+        let start = match range.start_bound() {
+            Bound::Included(s) => self.rank(s),
+            Bound::Excluded(s) => _pcount_less_by(&self._root, _le(s)),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(e) => _pcount_less_by(&self._root, _le(e)),
+            Bound::Excluded(e) => self.rank(e),
+            Bound::Unbounded => self.len(),
+        };
+        end.saturating_sub(start)
+    }
+    //按范围从小到大遍历
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> PIter<'_, T> {
+        let mut it = PIter {
+            _front: vec![],
+            _back: vec![],
+            _len: self.count_range((range.start_bound(), range.end_bound())),
+        };
+        if it._len == 0 {
+            return it;
+        }
+        //沿查找路径压入不小于起点的节点
+        let mut cur = self._root.as_deref();
+        while let Some(x) = cur {
+            let less = match range.start_bound() {
+                Bound::Included(s) => x._val < *s,
+                Bound::Excluded(s) => x._val <= *s,
+                Bound::Unbounded => false,
+            };
+            if less {
+                cur = x._right.as_deref();
+            } else {
+                it._front.push(x);
+                cur = x._left.as_deref();
+            }
+        }
+        //沿查找路径压入不大于终点的节点
+        let mut cur = self._root.as_deref();
+        while let Some(x) = cur {
+            let greater = match range.end_bound() {
+                Bound::Included(e) => x._val > *e,
+                Bound::Excluded(e) => x._val >= *e,
+                Bound::Unbounded => false,
+            };
+            if greater {
+                cur = x._left.as_deref();
+            } else {
+                it._back.push(x);
+                cur = x._right.as_deref();
+            }
+        }
+        it
+    }
+}
+
+impl<T: Ord + Clone> PersistentRBTree<T> {
+    //插入，返回新版本，已存在时返回与自身共享全部节点的版本
+    pub fn insert(&self, val: T) -> Self {
+        match _pins(&self._root, val) {
+            Some(root) => Self {
+                _root: _recolor(&root, Color::Black),
+            },
+            None => self.clone(),
+        }
     }
+    //删除，返回新版本，不存在时返回与自身共享全部节点的版本
+    pub fn remove(&self, t: &T) -> Self {
+        if !self.contains(t) {
+            return self.clone();
+        }
+        let root = _pdel(&self._root, t);
+        Self {
+            _root: _recolor(&root, Color::Black),
+        }
+    }
+    //检查红黑树的性质，节点没有父指针，因此不会出现BrokenParent
+    pub fn validate(&self) -> Result<(), RBViolation<T>> {
+        if let Some(x) = &self._root {
+            if x._color == Color::Red {
+                return Err(RBViolation::RedRoot(x._val.clone()));
+            }
+        }
+        _pvalidate(&self._root, None, None).map(|_| ())
+    }
+}
+
+/// 持久化红黑树的迭代器，两端各用一个栈记录尚未访问的祖先，返回值的引用
+pub struct PIter<'a, T> {
+    _front: Vec<&'a PNode<T>>,
+    _back: Vec<&'a PNode<T>>,
+    //剩余的值的个数，为0时两端相遇
+    _len: usize,
 }
 
-fn _check<T: Clone>(pnode: &RBInnerNode<T>, count: usize, mut tmp: usize) -> bool {
-    if pnode.is_none() {
-        return true;
+impl<'a, T> PIter<'a, T> {
+    //压入x及其左链
+    fn _push_left(&mut self, mut x: Option<&'a PNode<T>>) {
+        while let Some(y) = x {
+            self._front.push(y);
+            x = y._left.as_deref();
+        }
     }
-    let node = pnode.clone().unwrap().borrow().clone();
-    if node._color == Color::Black {
-        tmp += 1;
+    //压入x及其右链
+    fn _push_right(&mut self, mut x: Option<&'a PNode<T>>) {
+        while let Some(y) = x {
+            self._back.push(y);
+            x = y._right.as_deref();
+        }
     }
+}
 
-    let parent = node._parent.clone();
-    if let Some(x) = parent {
-        if x.borrow()._color == Color::Red && node._color == Color::Red {
-            return false;
+impl<'a, T> Iterator for PIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self._len == 0 {
+            return None;
         }
+        let x = self._front.pop()?;
+        self._push_left(x._right.as_deref());
+        self._len -= 1;
+        Some(&x._val)
     }
-    if pnode.is_none() && tmp != count {
-        return false;
-    }
-    _check(&node._left, count, tmp) && _check(&node._right, count, tmp)
 }
 
-fn _get_node<T: Ord + Debug>(root: &RBInnerNode<T>, val: &Option<T>) -> RBInnerNode<T> {
-    if root.is_none() {
-        return None;
-    }
-    let mut cur = root.clone();
-    while cur.is_some() {
-        let tmp = cur.clone().unwrap();
-        let tmp = tmp.borrow();
-        match tmp._val.cmp(val) {
-            std::cmp::Ordering::Equal => {
-                return cur.clone();
-            }
-            std::cmp::Ordering::Less => {
-                cur = tmp._right.clone();
-            }
-            std::cmp::Ordering::Greater => {
-                cur = tmp._left.clone();
-            }
+impl<T> DoubleEndedIterator for PIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self._len == 0 {
+            return None;
         }
+        let x = self._back.pop()?;
+        self._push_right(x._left.as_deref());
+        self._len -= 1;
+        Some(&x._val)
     }
-    return None;
 }
 
-/// 参考java
-/// 此处对我的难点是当去掉一个中间的节点之后，是用它的左节点还是右节点作为新的中间节点呢
-fn _delete<T: Ord + Clone + PartialEq + Debug>(root: &mut RBInnerNode<T>, val: &T) {
-    if root.is_none() {
-        return;
-    }
+impl<'a, T> IntoIterator for &'a PersistentRBTree<T> {
+    type Item = &'a T;
+    type IntoIter = PIter<'a, T>;
 
-    let mut node = _get_node(&root, &Some(val.clone()));
-    if node.is_none() {
-        return;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
     }
-    let noder = node.clone().unwrap();
-    if noder.borrow()._left.is_none() && noder.borrow()._right.is_none() {
-        let s = _successor(&node);
-        let sr = s.clone().unwrap();
-        let mut sbm = sr.borrow_mut();
-        noder.borrow_mut()._val = sbm._val.clone();
-        node = s.clone();
-    }
-    let noder = node.clone().unwrap();
-    let mut nodebm = noder.borrow_mut();
-    let replacement = if nodebm._left.is_some() {
-        nodebm._left.clone()
-    } else {
-        nodebm._right.clone()
-    };
+}
 
-    if replacement.is_some() {
-        let replace = replacement.clone().unwrap();
-        replace.borrow_mut()._parent = nodebm._parent.clone();
-        if nodebm._parent.is_none() {
-            *root = replacement.clone();
-        } else {
-            let parent = nodebm._parent.clone().unwrap();
-            let mut pbm = parent.borrow_mut();
-            if node == pbm._left {
-                pbm._left = replacement.clone();
-            } else {
-                pbm._right = replacement.clone();
-            }
-        }
+fn _pnode<T>(color: Color, left: PInnerNode<T>, val: T, right: PInnerNode<T>) -> PInnerNode<T> {
+    Some(Rc::new(PNode {
+        _size: _psize(&left) + _psize(&right) + 1,
+        _left: left,
+        _right: right,
+        _val: val,
+        _color: color,
+    }))
+}
 
-        nodebm._left = None;
-        nodebm._right = None;
-        nodebm._parent = None;
+fn _psize<T>(x: &PInnerNode<T>) -> usize {
+    x.as_ref().map_or(0, |y| y._size)
+}
 
-        if nodebm._color == Color::Black {
-            _fix_after_del(&replacement, root);
-        }
-    } else if nodebm._parent.is_none() {
-        *root = None;
-    } else {
-        if nodebm._color == Color::Black {
-            _fix_after_del(&node, root);
-        }
+fn _is_red<T>(x: &PInnerNode<T>) -> bool {
+    x.as_ref().is_some_and(|y| y._color == Color::Red)
+}
 
-        if nodebm._parent.is_some() {
-            let parent = nodebm._parent.clone().unwrap();
-            let mut parentbm = parent.borrow_mut();
-            if node == parentbm._left {
-                parentbm._left = None;
-            } else if node == parentbm._right {
-                parentbm._right = None;
-            }
-            nodebm._parent = None;
+//非空的黑节点，空节点不算
+fn _is_black<T>(x: &PInnerNode<T>) -> bool {
+    x.as_ref().is_some_and(|y| y._color == Color::Black)
+}
+
+//颜色不同时复制一个改色的节点，相同则共享
+fn _recolor<T: Clone>(x: &PInnerNode<T>, color: Color) -> PInnerNode<T> {
+    match x {
+        Some(y) if y._color != color => {
+            _pnode(color, y._left.clone(), y._val.clone(), y._right.clone())
         }
+        _ => x.clone(),
     }
 }
 
-//寻找比t大的最小值
-fn _successor<T: PartialEq>(t: &RBInnerNode<T>) -> RBInnerNode<T> {
-    if t.is_none() {
-        return None;
+//把红红相连的情况转为红节点带两个黑子节点，没有红红相连时返回黑节点
+fn _pbalance<T: Clone>(l: PInnerNode<T>, v: T, r: PInnerNode<T>) -> PInnerNode<T> {
+    use Color::{Black, Red};
+    if _is_red(&l) && _is_red(&r) {
+        return _pnode(Red, _recolor(&l, Black), v, _recolor(&r, Black));
     }
-    let tb = t.clone().unwrap();
-    let tb = tb.borrow();
-    if tb._right.is_some() {
-        let mut p = tb._right.clone();
-        let pb = p.clone().unwrap();
-        let pb = pb.borrow();
-        while pb._right.is_some() {
-            p = pb._left.clone();
+    if let Some(ln) = l.as_ref().filter(|x| x._color == Red) {
+        if let Some(ll) = ln._left.as_ref().filter(|x| x._color == Red) {
+            let a = _pnode(Black, ll._left.clone(), ll._val.clone(), ll._right.clone());
+            let b = _pnode(Black, ln._right.clone(), v, r);
+            return _pnode(Red, a, ln._val.clone(), b);
         }
-        p.clone()
-    } else {
-        let mut p = tb._parent.clone();
-        let mut ch = t.clone();
-        while p.is_some() {
-            let pb = p.clone().unwrap();
-            let pb = pb.borrow();
-            if ch != pb._right {
-                break;
-            }
-            ch = p.clone();
-            p = pb._parent.clone();
+        if let Some(lr) = ln._right.as_ref().filter(|x| x._color == Red) {
+            let a = _pnode(Black, ln._left.clone(), ln._val.clone(), lr._left.clone());
+            let b = _pnode(Black, lr._right.clone(), v, r);
+            return _pnode(Red, a, lr._val.clone(), b);
         }
-        p.clone()
     }
+    if let Some(rn) = r.as_ref().filter(|x| x._color == Red) {
+        if let Some(rr) = rn._right.as_ref().filter(|x| x._color == Red) {
+            let a = _pnode(Black, l, v, rn._left.clone());
+            let b = _pnode(Black, rr._left.clone(), rr._val.clone(), rr._right.clone());
+            return _pnode(Red, a, rn._val.clone(), b);
+        }
+        if let Some(rl) = rn._left.as_ref().filter(|x| x._color == Red) {
+            let a = _pnode(Black, l, v, rl._left.clone());
+            let b = _pnode(Black, rl._right.clone(), rn._val.clone(), rn._right.clone());
+            return _pnode(Red, a, rl._val.clone(), b);
+        }
+    }
+    _pnode(Black, l, v, r)
 }
 
-pub fn _insert<T: Ord + Clone>(this: &mut RBInnerNode<T>, val: T) -> RBInnerNode<T> {
-    if this.is_none() {
-        let root = Rc::new(RefCell::new(RBNode::new_black(val.clone())));
-        *this = Some(root.clone());
-        return Some(root);
+//插入到x为根的子树，返回新的子树，已存在时返回None
+fn _pins<T: Ord + Clone>(x: &PInnerNode<T>, val: T) -> Option<PInnerNode<T>> {
+    let n = match x {
+        Some(n) => n,
+        None => return Some(_pnode(Color::Red, None, val, None)),
+    };
+    let (color, v) = (n._color, n._val.clone());
+    match val.cmp(&n._val) {
+        Ordering::Less => {
+            let l = _pins(&n._left, val)?;
+            Some(match color {
+                Color::Black => _pbalance(l, v, n._right.clone()),
+                Color::Red => _pnode(Color::Red, l, v, n._right.clone()),
+            })
+        }
+        Ordering::Greater => {
+            let r = _pins(&n._right, val)?;
+            Some(match color {
+                Color::Black => _pbalance(n._left.clone(), v, r),
+                Color::Red => _pnode(Color::Red, n._left.clone(), v, r),
+            })
+        }
+        Ordering::Equal => None,
     }
-    let mut this = this.clone();
-    while this.is_some() {
-        let tmp = this.unwrap();
-        let mut tb = tmp.borrow_mut();
-        match tb._val.cmp(&Some(val.clone())) {
-            std::cmp::Ordering::Less => {
-                if tb._right.is_some() {
-                    this = tb._right.clone();
-                } else {
-                    //self->right 设为new_node
-                    let rc = Rc::new(RefCell::new(RBNode::new_red(val)));
-                    tb._right = Some(rc.clone());
-                    //new_node->parent设为self
-                    rc.borrow_mut()._parent = Some(tmp.clone());
-                    return Some(rc.clone());
-                }
-            }
-            std::cmp::Ordering::Greater => {
-                if tb._left.is_some() {
-                    this = tb._left.clone();
-                } else {
-                    //self->left 设为new_node
-                    let rc = Rc::new(RefCell::new(RBNode::new_red(val)));
-                    tb._left = Some(rc.clone());
-                    //new_node->parent设为self
-                    rc.borrow_mut()._parent = Some(tmp.clone());
-                    return Some(rc.clone());
-                }
-            }
-            std::cmp::Ordering::Equal => break,
-        }
-    }
-    None
-}
-
-/// 插入后处理旋转变色
-/// 								red -> parent设黑、uncle设黑、gp设红、x设为gp
-///	    parent为左    y uncle
-/// 								black->(x若为p的右，设为parent并左旋)、parent设黑、gp设红、右旋gp
-/// 新节点x(不为空、不为root、且父级为红)																	root设黑
-/// 								red -> parent设黑、uncle设黑、gp设红、x设为gp
-/// 		parent为右    y uncle
-/// 								black->(x若为p的左，设为parent并右旋)、parent设黑、gp设红、左旋gp
-fn _fix_after_insert<T: PartialEq + Debug>(mut this: RBInnerNode<T>, root: &mut RBInnerNode<T>) {
-    while this.is_some() && this != *root {
-        let x = this.clone().unwrap();
-        let p = x.borrow()._parent.clone().unwrap();
-        if p.borrow()._color == Color::Black {
-            break;
-        }
-        let grandparent = p.borrow()._parent.clone();
-        let uncle;
-        let pos = if let Some(gp) = &grandparent {
-            if gp.borrow()._left == Some(p.clone()) {
-                uncle = gp.borrow()._right.clone();
-                Pos::Left
+}
+
+//从x为根的子树中删除t，t必须存在
+//从黑节点下删除会使该侧黑高减一，由_balleft/_balright补回
+fn _pdel<T: Ord + Clone>(x: &PInnerNode<T>, t: &T) -> PInnerNode<T> {
+    let n = x.as_ref()?;
+    match t.cmp(&n._val) {
+        Ordering::Less => {
+            let l = _pdel(&n._left, t);
+            if _is_black(&n._left) {
+                _balleft(l, n._val.clone(), n._right.clone())
             } else {
-                uncle = gp.borrow()._left.clone();
-                Pos::Right
+                _pnode(Color::Red, l, n._val.clone(), n._right.clone())
             }
-        } else {
-            uncle = None;
-            Pos::Right
-        };
-        // println!("uncle={:?}", uncle);
-        if let Some(u) = uncle {
-            let mut ub = u.borrow_mut();
-            match ub._color {
-                Color::Red => {
-                    p.borrow_mut()._color = Color::Black;
-                    ub._color = Color::Black;
-                    if let Some(gp) = &grandparent {
-                        gp.borrow_mut()._color = Color::Red;
-                    }
-                    this = grandparent.clone();
-                }
-                Color::Black => {
-                    match pos {
-                        Pos::Left => {
-                            if this == p.borrow()._right {
-                                this = Some(p.clone());
-                                //左旋this
-                                _rotate_left(&this, root);
-                            }
-                            p.borrow_mut()._color = Color::Black;
-                            if let Some(gp) = &grandparent {
-                                gp.borrow_mut()._color = Color::Red;
-                                //右旋gp
-                                _rotate_right(&Some(gp.clone()), root);
-                            }
-                        }
-                        Pos::Right => {
-                            if this == p.borrow()._left {
-                                this = Some(p.clone());
-                                //右旋this
-                                _rotate_right(&this, root);
-                            }
-                            p.borrow_mut()._color = Color::Black;
-                            if let Some(gp) = &grandparent {
-                                gp.borrow_mut()._color = Color::Red;
-                                //左旋gp
-                                _rotate_left(&Some(gp.clone()), root);
-                            }
-                        }
-                    }
-                }
+        }
+        Ordering::Greater => {
+            let r = _pdel(&n._right, t);
+            if _is_black(&n._right) {
+                _balright(n._left.clone(), n._val.clone(), r)
+            } else {
+                _pnode(Color::Red, n._left.clone(), n._val.clone(), r)
             }
-        } else {
-            break;
         }
-    }
-    if let Some(r) = root.as_ref() {
-        r.borrow_mut()._color = Color::Black;
+        Ordering::Equal => _papp(&n._left, &n._right),
     }
 }
 
-fn _fix_after_del<T: PartialEq + Clone + Debug>(x: &RBInnerNode<T>, root: &mut RBInnerNode<T>) {
-    let mut x = x.clone();
-    while x != *root && _color_of(&x) == Color::Black {
-        if x == _left_of(&_parent_of(&x)) {
-            let mut sib = _right_of(&_parent_of(&x));
-
-            if _color_of(&sib) == Color::Red {
-                _set_color(&sib, Color::Black);
-                _set_color(&_parent_of(&x), Color::Red);
-                _rotate_left(&_parent_of(&x), root);
-                sib = _right_of(&_parent_of(&x))
-            }
-
-            if _color_of(&_left_of(&sib)) == Color::Black
-                && _color_of(&_right_of(&sib)) == Color::Black
-            {
-                _set_color(&sib, Color::Red);
-                x = _parent_of(&x);
-            } else {
-                if _color_of(&_right_of(&sib)) == Color::Black {
-                    _set_color(&_left_of(&sib), Color::Black);
-                    _set_color(&sib, Color::Red);
-                    _rotate_right(&sib, root);
-                    sib = _right_of(&_parent_of(&x));
-                }
+//左子树黑高比右子树少一时重新平衡
+fn _balleft<T: Clone>(l: PInnerNode<T>, v: T, r: PInnerNode<T>) -> PInnerNode<T> {
+    use Color::{Black, Red};
+    if _is_red(&l) {
+        return _pnode(Red, _recolor(&l, Black), v, r);
+    }
+    let rn = r.as_ref().unwrap();
+    if rn._color == Black {
+        return _pbalance(l, v, _recolor(&r, Red));
+    }
+    //右子节点为红，其左子节点必为黑
+    let rl = rn._left.as_ref().unwrap();
+    let a = _pnode(Black, l, v, rl._left.clone());
+    let b = _pbalance(
+        rl._right.clone(),
+        rn._val.clone(),
+        _recolor(&rn._right, Red),
+    );
+    _pnode(Red, a, rl._val.clone(), b)
+}
 
-                _set_color(&sib, _color_of(&_parent_of(&x)));
-                _set_color(&_parent_of(&x), Color::Black);
-                _set_color(&_right_of(&sib), Color::Black);
-                _rotate_left(&_parent_of(&x), root);
-                x = root.clone();
-            }
-        } else {
-            let mut sib = _left_of(&_parent_of(&x));
+//右子树黑高比左子树少一时重新平衡
+fn _balright<T: Clone>(l: PInnerNode<T>, v: T, r: PInnerNode<T>) -> PInnerNode<T> {
+    use Color::{Black, Red};
+    if _is_red(&r) {
+        return _pnode(Red, l, v, _recolor(&r, Black));
+    }
+    let ln = l.as_ref().unwrap();
+    if ln._color == Black {
+        return _pbalance(_recolor(&l, Red), v, r);
+    }
+    //左子节点为红，其右子节点必为黑
+    let lr = ln._right.as_ref().unwrap();
+    let a = _pbalance(_recolor(&ln._left, Red), ln._val.clone(), lr._left.clone());
+    let b = _pnode(Black, lr._right.clone(), v, r);
+    _pnode(Red, a, lr._val.clone(), b)
+}
 
-            if _color_of(&sib) == Color::Red {
-                _set_color(&sib, Color::Black);
-                _set_color(&_parent_of(&x), Color::Red);
-                _rotate_right(&_parent_of(&x), root);
-                sib = _left_of(&_parent_of(&x))
+//拼接被删除节点的左右子树(l中的值都小于r)
+fn _papp<T: Clone>(l: &PInnerNode<T>, r: &PInnerNode<T>) -> PInnerNode<T> {
+    use Color::{Black, Red};
+    let (ln, rn) = match (l, r) {
+        (None, _) => return r.clone(),
+        (_, None) => return l.clone(),
+        (Some(a), Some(b)) => (a, b),
+    };
+    match (ln._color, rn._color) {
+        (Red, Red) => {
+            let m = _papp(&ln._right, &rn._left);
+            match m.as_ref().filter(|x| x._color == Red) {
+                Some(mn) => _pnode(
+                    Red,
+                    _pnode(Red, ln._left.clone(), ln._val.clone(), mn._left.clone()),
+                    mn._val.clone(),
+                    _pnode(Red, mn._right.clone(), rn._val.clone(), rn._right.clone()),
+                ),
+                None => _pnode(
+                    Red,
+                    ln._left.clone(),
+                    ln._val.clone(),
+                    _pnode(Red, m, rn._val.clone(), rn._right.clone()),
+                ),
             }
-
-            if _color_of(&_right_of(&sib)) == Color::Black
-                && _color_of(&_left_of(&sib)) == Color::Black
-            {
-                _set_color(&sib, Color::Red);
-                x = _parent_of(&x);
-            } else {
-                if _color_of(&_left_of(&sib)) == Color::Black {
-                    _set_color(&_right_of(&sib), Color::Black);
-                    _set_color(&sib, Color::Red);
-                    _rotate_left(&sib, root);
-                    sib = _left_of(&_parent_of(&x));
-                }
-
-                _set_color(&sib, _color_of(&_parent_of(&x)));
-                _set_color(&_parent_of(&x), Color::Black);
-                _set_color(&_left_of(&sib), Color::Black);
-                _rotate_right(&_parent_of(&x), root);
-                x = root.clone();
+        }
+        (Black, Black) => {
+            let m = _papp(&ln._right, &rn._left);
+            match m.as_ref().filter(|x| x._color == Red) {
+                Some(mn) => _pnode(
+                    Red,
+                    _pnode(Black, ln._left.clone(), ln._val.clone(), mn._left.clone()),
+                    mn._val.clone(),
+                    _pnode(Black, mn._right.clone(), rn._val.clone(), rn._right.clone()),
+                ),
+                None => _balleft(
+                    ln._left.clone(),
+                    ln._val.clone(),
+                    _pnode(Black, m, rn._val.clone(), rn._right.clone()),
+                ),
             }
         }
+        (Black, Red) => _pnode(Red, _papp(l, &rn._left), rn._val.clone(), rn._right.clone()),
+        (Red, Black) => _pnode(Red, ln._left.clone(), ln._val.clone(), _papp(&ln._right, r)),
     }
-    _set_color(&x, Color::Black);
 }
 
-fn _set_color<T>(x: &RBInnerNode<T>, c: Color) {
-    if let Some(y) = &x {
-        y.borrow_mut()._color = c;
+//f(x)为Less的最大值
+fn _plast_less_by<T, F: Fn(&T) -> Ordering>(root: &PInnerNode<T>, f: F) -> Option<&T> {
+    let mut res = None;
+    let mut cur = root.as_deref();
+    while let Some(x) = cur {
+        if f(&x._val) == Ordering::Less {
+            res = Some(x);
+            cur = x._right.as_deref();
+        } else {
+            cur = x._left.as_deref();
+        }
     }
+    res.map(|x| &x._val)
 }
 
-fn _parent_of<T: Clone>(x: &RBInnerNode<T>) -> RBInnerNode<T> {
-    if let Some(y) = &x {
-        y.borrow()._parent.clone()
-    } else {
-        None
+//f(x)不为Less的最小值
+fn _pfirst_not_less_by<T, F: Fn(&T) -> Ordering>(root: &PInnerNode<T>, f: F) -> Option<&T> {
+    let mut res = None;
+    let mut cur = root.as_deref();
+    while let Some(x) = cur {
+        if f(&x._val) == Ordering::Less {
+            cur = x._right.as_deref();
+        } else {
+            res = Some(x);
+            cur = x._left.as_deref();
+        }
     }
+    res.map(|x| &x._val)
 }
 
-fn _left_of<T: Clone>(x: &RBInnerNode<T>) -> RBInnerNode<T> {
-    if let Some(y) = &x {
-        y.borrow()._left.clone()
-    } else {
-        None
+//f(x)为Less的节点数
+fn _pcount_less_by<T, F: Fn(&T) -> Ordering>(root: &PInnerNode<T>, f: F) -> usize {
+    let mut res = 0;
+    let mut cur = root.as_deref();
+    while let Some(x) = cur {
+        if f(&x._val) == Ordering::Less {
+            res += _psize(&x._left) + 1;
+            cur = x._right.as_deref();
+        } else {
+            cur = x._left.as_deref();
+        }
     }
+    res
 }
 
-fn _right_of<T: Clone>(x: &RBInnerNode<T>) -> RBInnerNode<T> {
-    if let Some(y) = &x {
-        y.borrow()._right.clone()
-    } else {
-        None
+//递归检查x为根的子树，返回(黑高, 节点数)
+fn _pvalidate<T: Ord + Clone>(
+    x: &PInnerNode<T>,
+    lo: Option<&T>,
+    hi: Option<&T>,
+) -> Result<(usize, usize), RBViolation<T>> {
+    let n = match x {
+        Some(n) => n,
+        None => return Ok((1, 0)),
+    };
+    let val = &n._val;
+    let bound = lo.filter(|l| val <= *l).or(hi.filter(|h| val >= *h));
+    if let Some(b) = bound {
+        return Err(RBViolation::Order {
+            node: val.clone(),
+            bound: b.clone(),
+        });
+    }
+    for child in [&n._left, &n._right].into_iter().flatten() {
+        if n._color == Color::Red && child._color == Color::Red {
+            return Err(RBViolation::RedRed {
+                parent: val.clone(),
+                child: child._val.clone(),
+            });
+        }
+    }
+    let (left, lsize) = _pvalidate(&n._left, lo, Some(val))?;
+    let (right, rsize) = _pvalidate(&n._right, Some(val), hi)?;
+    if left != right {
+        return Err(RBViolation::BlackHeight {
+            node: val.clone(),
+            left,
+            right,
+        });
     }
+    if n._size != lsize + rsize + 1 {
+        return Err(RBViolation::Size {
+            node: val.clone(),
+            size: n._size,
+            actual: lsize + rsize + 1,
+        });
+    }
+    let black = (n._color == Color::Black) as usize;
+    Ok((left + black, lsize + rsize + 1))
 }
 
-fn _color_of<T: Clone>(x: &RBInnerNode<T>) -> Color {
-    if x.is_none() {
-        return Color::Black;
+impl<T> Default for PersistentRBTree<T> {
+    fn default() -> Self {
+        Self { _root: None }
     }
-    let y = x.clone().unwrap();
-    let yb = y.borrow().clone();
-    yb._color
 }
 
-///////////////////////////////////////////////
-///      2              左旋              4
-/// 1        4         -->       2             5
-///        3   5      <--     1  3
-///                     右旋
-//////////////////////////////////////////////
-//参考java->TreeMap->rotateLeft
-//这里自己实现应该也没什么问题，只是之前陷入了传参为RBNode而非Rc RefCell RBNode，会使RBNode并非一个对象的问题
-fn _rotate_left<T: PartialEq>(this: &RBInnerNode<T>, root: &mut RBInnerNode<T>) {
-    if this.is_none() {
-        return;
-    }
-    let thisu = this.clone().unwrap();
-    let mut thisbm = thisu.borrow_mut();
-    //right
-    let r = thisbm._right.clone().unwrap();
-    let mut rbm = r.borrow_mut();
-    //this->right = right->left
-    thisbm._right = rbm._left.clone();
-
-    //right->left->parent = this
-    if rbm._left.is_some() {
-        let rbml = rbm._left.clone().unwrap();
-        rbml.borrow_mut()._parent = this.clone();
-    }
-    //right->parent = this->parent
-    rbm._parent = thisbm._parent.clone();
-    //root = right
-    if thisbm._parent.is_none() {
-        *root = Some(r.clone());
-    } else {
-        let parent = thisbm._parent.clone().unwrap();
-        let mut pbm = parent.borrow_mut();
-        if pbm._left == *this {
-            //this->parent->left = right
-            pbm._left = Some(r.clone());
-        } else {
-            //this->parent->right = right
-            pbm._right = Some(r.clone());
-        }
-    }
-    //right->left = this
-    rbm._left = this.clone();
-    //this->parent = right;
-    thisbm._parent = Some(r.clone());
-}
-
-fn _rotate_right<T: PartialEq>(this: &RBInnerNode<T>, root: &mut RBInnerNode<T>) {
-    if this.is_none() {
-        return;
-    }
-    let thisu = this.clone().unwrap();
-    let mut thisbm = thisu.borrow_mut();
-    //left
-    let l = thisbm._left.clone().unwrap();
-    let mut lbm = l.borrow_mut();
-    //this->left = right->right
-    thisbm._left = lbm._right.clone();
-
-    //left->right->parent = this
-    if lbm._right.is_some() {
-        let lbml = lbm._right.clone().unwrap();
-        lbml.borrow_mut()._parent = this.clone();
-    }
-    //left->parent = this->parent
-    lbm._parent = thisbm._parent.clone();
-    //root = left
-    if thisbm._parent.is_none() {
-        *root = Some(l.clone());
-    } else {
-        let parent = thisbm._parent.clone().unwrap();
-        let mut pbm = parent.borrow_mut();
-        if pbm._right == *this {
-            //this->parent->right = left
-            pbm._right = Some(l.clone());
-        } else {
-            //this->parent->left = left
-            pbm._left = Some(l.clone());
-        }
+impl<T: Debug> Debug for PersistentRBTree<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
     }
-    //left->right = this
-    lbm._right = this.clone();
-    //this->parent = left;
-    thisbm._parent = Some(l.clone());
 }
 
 //此处代码全部未用到
@@ -746,18 +2468,19 @@ fn _rotate_right<T: PartialEq>(this: &RBInnerNode<T>, root: &mut RBInnerNode<T>)
 
 impl<T> Default for RBTree<T> {
     fn default() -> Self {
-        Self { _root: None }
+        Self::_empty()
     }
 }
 
 impl<T> Default for RBNode<T> {
     fn default() -> Self {
         Self {
-            _left: None,
-            _right: None,
-            _parent: None,
+            _left: NIL,
+            _right: NIL,
+            _parent: NIL,
             _val: None,
             _color: Color::default(),
+            _size: 0,
         }
     }
 }
@@ -779,24 +2502,116 @@ impl Not for Color {
     }
 }
 
+//按中序比较值，与节点在_nodes中的位置无关
+impl<T: PartialEq, A> PartialEq for RBTree<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        let (mut a, mut b) = (self.iter(), other.iter());
+        if self._len != other._len {
+            return false;
+        }
+        while let (Some(x), Some(y)) = (a.next(), b.next()) {
+            if x != y {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl<T: Eq, A> Eq for RBTree<T, A> {}
+
 impl<T: Debug> Debug for RBNode<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "val: {:?}, color: {:?} => ", self._val, self._color)?;
-        if let Some(x) = &self._left {
-            Debug::fmt(&x.borrow(), f);
+        write!(f, "val: {:?}, color: {:?} => ", self._val, self._color)
+    }
+}
+
+impl<T: Debug, A> RBTree<T, A> {
+    //先序输出x为根的子树
+    fn _fmt_node(&self, x: u32, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if x == NIL {
+            return Ok(());
         }
-        if let Some(x) = &self._right {
-            Debug::fmt(&x.borrow(), f);
+        Debug::fmt(self._node(x), f)?;
+        self._fmt_node(self._left_of(x), f)?;
+        self._fmt_node(self._right_of(x), f)
+    }
+}
+
+//NIL转为None
+fn _link(x: u32) -> Option<u32> {
+    (x != NIL).then_some(x)
+}
+
+impl<T, A> BinaryTree for RBTree<T, A> {
+    type Node<'a>
+        = u32
+    where
+        Self: 'a;
+    type Value = T;
+
+    fn root(&self) -> Option<u32> {
+        _link(self._root)
+    }
+    fn left(&self, x: u32) -> Option<u32> {
+        _link(self._left_of(x))
+    }
+    fn right(&self, x: u32) -> Option<u32> {
+        _link(self._right_of(x))
+    }
+    fn value(&self, x: u32) -> Option<&T> {
+        self._val_of(x)
+    }
+}
+
+//遍历期间只改写右链接，父链接、颜色、大小都不变，遍历结束即恢复
+impl<T, A> MorrisTree for RBTree<T, A> {
+    type Value = T;
+
+    fn morris_detach(&mut self) -> (Option<u32>, usize) {
+        let root = _link(std::mem::replace(&mut self._root, NIL));
+        (root, std::mem::take(&mut self._len))
+    }
+    fn morris_attach(&mut self, root: Option<u32>, len: usize) {
+        self._root = root.unwrap_or(NIL);
+        self._len = len;
+    }
+    fn morris_left(&self, x: u32) -> Option<u32> {
+        _link(self._left_of(x))
+    }
+    fn morris_right(&self, x: u32) -> Option<u32> {
+        _link(self._right_of(x))
+    }
+    fn morris_set_right(&mut self, x: u32, right: Option<u32>) {
+        self._node_mut(x)._right = right.unwrap_or(NIL);
+    }
+    fn morris_value(&self, x: u32) -> Option<&T> {
+        self._val_of(x)
+    }
+}
+
+impl<T: Debug, A> TreeView for RBTree<T, A> {
+    fn view_label(&self, x: u32) -> String {
+        format!("{:?}", self._val_of(x).unwrap())
+    }
+    fn view_attrs(&self, x: u32) -> String {
+        let color = match self._color_of(x) {
+            Color::Red => "red",
+            Color::Black => "black",
+        };
+        format!(", style=filled, fillcolor={color}, fontcolor=white")
+    }
+    fn view_tag(&self, x: u32) -> Option<&'static str> {
+        match self._color_of(x) {
+            Color::Red => Some("R"),
+            Color::Black => Some("B"),
         }
-        write!(f, "")
     }
 }
 
-impl<T: Debug> Debug for RBTree<T> {
+impl<T: Debug, A> Debug for RBTree<T, A> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let Some(x) = &self._root {
-            Debug::fmt(&x.borrow(), f);
-        }
+        self._fmt_node(self._root, f)?;
         write!(f, "None")
     }
 }
@@ -811,7 +2626,7 @@ fn test() {
     // rbt.insert(7);
     // println!();
     // // rbt.insert(10);
-    // println!("rbt={:?}, check:{:?}", rbt, rbt._check());
+    // println!("rbt={:?}, check:{:?}", rbt, rbt.validate());
     let mut rbt = RBTree::new(50);
     rbt.insert(29);
     // rbt.insert(12);
@@ -828,5 +2643,606 @@ fn test() {
     println!();
     // rbt.insert(10);
     println!("rbt={:?}", rbt);
-    println!("check:{:?}", rbt._check());
+    println!("check:{:?}", rbt.validate());
+    assert_eq!(rbt.validate(), Ok(()));
+}
+
+#[test]
+fn test_map() {
+    use crate::common::rand::XorShift;
+    use std::collections::BTreeMap;
+
+    let mut map = RBMap::new();
+    assert_eq!(map.insert(3, "c"), None);
+    assert_eq!(map.insert(1, "a"), None);
+    assert_eq!(map.insert(3, "C"), Some("c"));
+    assert_eq!(map.get(&3), Some(&"C"));
+    *map.get_mut(&1).unwrap() = "A";
+    assert_eq!(map.get(&1), Some(&"A"));
+    assert!(map.contains_key(&1));
+    assert_eq!(map.remove(&1), Some("A"));
+    assert_eq!(map.remove(&1), None);
+    assert_eq!(map.len(), 1);
+
+    let mut counts = RBMap::new();
+    for w in "a b a c b a".split(' ') {
+        *counts.entry(w).or_insert(0) += 1;
+    }
+    assert_eq!(counts.get(&"a"), Some(&3));
+    counts.entry("b").and_modify(|v| *v *= 10).or_default();
+    counts.entry("d").and_modify(|v| *v *= 10).or_default();
+    assert_eq!(counts.get(&"b"), Some(&20));
+    assert_eq!(counts.get(&"d"), Some(&0));
+    if let Entry::Occupied(e) = counts.entry("c") {
+        assert_eq!(e.remove_entry(), ("c", 1));
+    }
+    assert_eq!(counts.len(), 3);
+
+    //与BTreeMap对比
+    let mut map = RBMap::new();
+    let mut bm = BTreeMap::new();
+    let mut rng = XorShift::new(5);
+    for i in 0..5000 {
+        let k = rng.gen_range(400);
+        if rng.gen_range(3) == 0 {
+            assert_eq!(map.remove(&k), bm.remove(&k));
+        } else {
+            assert_eq!(map.insert(k, i), bm.insert(k, i));
+        }
+        assert_eq!(map.len(), bm.len());
+        assert!(map._tree.validate().is_ok());
+    }
+    for (k, v) in &bm {
+        assert_eq!(map.get(k), Some(v));
+    }
+}
+
+#[test]
+fn test_iter() {
+    use crate::common::rand::XorShift;
+    use std::collections::BTreeSet;
+
+    let mut rbt = RBTree::default();
+    let mut set = BTreeSet::new();
+    let mut rng = XorShift::new(17);
+    for _ in 0..2000 {
+        let v = rng.gen_range(1000) as i32;
+        if rng.gen_range(4) == 0 {
+            rbt.delete(&v);
+            set.remove(&v);
+        } else {
+            rbt.insert(v);
+            set.insert(v);
+        }
+    }
+    assert!(rbt.iter().eq(set.iter()));
+    assert!(rbt.iter().rev().eq(set.iter().rev()));
+    assert!((&rbt).into_iter().eq(set.iter()));
+    assert_eq!(rbt.first(), set.first());
+    assert_eq!(rbt.last(), set.last());
+    for t in [-1, 0, 250, 499, 500, 777, 1000] {
+        assert!(rbt.range(t..t + 100).eq(set.range(t..t + 100)));
+        assert!(rbt.range(..=t).rev().eq(set.range(..=t).rev()));
+        assert!(rbt
+            .range((Bound::Excluded(t), Bound::Unbounded))
+            .eq(set.range((Bound::Excluded(t), Bound::Unbounded))));
+        assert_eq!(rbt.floor(&t), set.range(..=t).next_back());
+        assert_eq!(rbt.ceiling(&t), set.range(t..).next());
+        assert_eq!(rbt.predecessor(&t), set.range(..t).next_back());
+        assert_eq!(
+            rbt.successor(&t),
+            set.range((Bound::Excluded(t), Bound::Unbounded)).next()
+        );
+    }
+    assert_eq!(
+        rbt.range((Bound::Included(600), Bound::Excluded(300)))
+            .next(),
+        None
+    );
+    //两端交替取值
+    let mut it = rbt.iter();
+    let mut both = vec![];
+    while let (Some(a), Some(b)) = (it.next(), it.next_back()) {
+        both.push(a);
+        both.push(b);
+    }
+    assert_eq!(both.len() / 2, set.len() / 2);
+    let len = rbt.len();
+    assert!(rbt.into_iter().eq(set.into_iter()));
+    assert!(len > 0);
+}
+
+#[test]
+fn test_order_statistic() {
+    use crate::common::rand::XorShift;
+    use std::collections::BTreeSet;
+
+    let mut rbt = RBTree::default();
+    let mut set = BTreeSet::new();
+    let mut rng = XorShift::new(23);
+    for _ in 0..3000 {
+        let v = rng.gen_range(800);
+        if rng.gen_range(3) == 0 {
+            assert_eq!(rbt.delete(&v), set.take(&v));
+        } else {
+            assert_eq!(rbt.insert(v), set.insert(v));
+        }
+        assert_eq!(rbt._size_of(rbt._root), set.len());
+    }
+    let sorted = set.iter().cloned().collect::<Vec<_>>();
+    for (k, v) in sorted.iter().enumerate() {
+        assert_eq!(rbt.select(k), Some(v));
+        assert_eq!(rbt.rank(v), k);
+    }
+    assert_eq!(rbt.select(sorted.len()), None);
+    for (a, b) in [(0, 800), (100, 200), (350, 351), (500, 400), (799, 1000)] {
+        let (ex, inc) = if a <= b {
+            (set.range(a..b).count(), set.range(a..=b).count())
+        } else {
+            (0, 0)
+        };
+        assert_eq!(rbt.count_range(a..b), ex);
+        assert_eq!(rbt.count_range(a..=b), inc);
+        assert_eq!(rbt.count_range(..b), set.range(..b).count());
+    }
+}
+
+#[test]
+fn test_interval() {
+    use crate::common::rand::XorShift;
+
+    //检查每个节点的最大end
+    fn check<K: Ord + Clone, V>(t: &RBTree<IntervalEntry<K, V>, MaxEnd>, x: u32) -> Option<K> {
+        let e = t._val_of(x)?;
+        let max = [
+            check(t, t._left_of(x)),
+            check(t, t._right_of(x)),
+            Some(e._end.clone()),
+        ]
+        .into_iter()
+        .flatten()
+        .max();
+        assert!(max.as_ref() == Some(&e._max));
+        max
+    }
+
+    let mut it = IntervalTree::new();
+    it.insert(15..20, "a");
+    it.insert(10..30, "b");
+    it.insert(17..19, "c");
+    it.insert(5..20, "d");
+    it.insert(12..15, "e");
+    it.insert(30..40, "f");
+    assert_eq!(it.insert(30..40, "F"), Some("f"));
+    assert_eq!(it.len(), 6);
+    let names = |v: Overlapping<'_, i32, &'static str>| v.map(|x| *x.1).collect::<Vec<_>>();
+    assert_eq!(names(it.overlapping(&(14..16))), vec!["d", "b", "e", "a"]);
+    assert_eq!(names(it.stabbing(&30)), vec!["F"]);
+    assert_eq!(names(it.stabbing(&19)), vec!["d", "b", "a"]);
+    assert!(it.any_overlap(&(0..6)));
+    assert!(!it.any_overlap(&(40..50)));
+    assert_eq!(it.remove(&(10..30)), Some("b"));
+    assert_eq!(it.remove(&(10..30)), None);
+    assert_eq!(names(it.stabbing(&25)), Vec::<&str>::new());
+    check(&it._tree, it._tree._root);
+
+    //与暴力查找对比
+    let mut it = IntervalTree::new();
+    let mut all: Vec<(Range<usize>, usize)> = vec![];
+    let mut rng = XorShift::new(31);
+    for i in 0..1500 {
+        let s = rng.gen_range(1000);
+        let r = s..s + 1 + rng.gen_range(50);
+        if rng.gen_range(3) == 0 && !all.is_empty() {
+            let (r, v) = all.swap_remove(rng.gen_range(all.len()));
+            assert_eq!(it.remove(&r), Some(v));
+        } else if !it.contains(&r) {
+            it.insert(r.clone(), i);
+            all.push((r, i));
+        }
+        check(&it._tree, it._tree._root);
+    }
+    all.sort_by_key(|a| (a.0.start, a.0.end));
+    fn borrowed((r, v): &(Range<usize>, usize)) -> (Range<&usize>, &usize) {
+        (&r.start..&r.end, v)
+    }
+    assert!(it.iter().eq(all.iter().map(borrowed)));
+    for q in [0..10, 100..101, 500..600, 990..2000, 7..7] {
+        let expected = all
+            .iter()
+            //空区间不与任何区间重叠
+            .filter(|(r, _)| q.start < q.end && r.start < q.end && q.start < r.end)
+            .map(borrowed)
+            .collect::<Vec<_>>();
+        assert_eq!(it.any_overlap(&q), !expected.is_empty());
+        assert_eq!(it.overlapping(&q).collect::<Vec<_>>(), expected);
+        let stab = all
+            .iter()
+            .filter(|(r, _)| r.contains(&q.start))
+            .map(borrowed)
+            .collect::<Vec<_>>();
+        assert_eq!(it.stabbing(&q.start).collect::<Vec<_>>(), stab);
+    }
+}
+
+#[test]
+fn test_validate() {
+    let mut rbt = RBTree::default();
+    for i in 1..=10 {
+        rbt.insert(i);
+        assert_eq!(rbt.validate(), Ok(()));
+    }
+    let root = rbt._root;
+    let root_val = *rbt._val_of(root).unwrap();
+
+    rbt._set_color(root, Color::Red);
+    assert_eq!(rbt.validate(), Err(RBViolation::RedRoot(root_val)));
+    rbt._set_color(root, Color::Black);
+
+    //有子节点的红节点
+    let red = rbt
+        .iter()
+        .map(|v| rbt.get_node(v))
+        .find(|x| rbt._color_of(*x) == Color::Red && rbt._left_of(*x) != NIL)
+        .unwrap();
+    let red_val = *rbt._val_of(red).unwrap();
+    let child = rbt._left_of(red);
+    let child_val = *rbt._val_of(child).unwrap();
+    rbt._set_color(child, Color::Red);
+    assert_eq!(
+        rbt.validate(),
+        Err(RBViolation::RedRed {
+            parent: red_val,
+            child: child_val
+        })
+    );
+    rbt._set_color(child, Color::Black);
+    rbt._set_color(red, Color::Black);
+    assert!(matches!(
+        rbt.validate(),
+        Err(RBViolation::BlackHeight { .. })
+    ));
+    rbt._set_color(red, Color::Red);
+    assert_eq!(rbt.validate(), Ok(()));
+
+    rbt._node_mut(child)._parent = NIL;
+    assert_eq!(rbt.validate(), Err(RBViolation::BrokenParent(child_val)));
+    rbt._node_mut(child)._parent = red;
+
+    let first = rbt._first_node(rbt._root);
+    rbt._node_mut(first)._val = Some(100);
+    assert!(matches!(
+        rbt.validate(),
+        Err(RBViolation::Order { node: 100, .. })
+    ));
+    rbt._node_mut(first)._val = Some(1);
+    assert_eq!(rbt.validate(), Ok(()));
+
+    rbt._len += 1;
+    assert_eq!(
+        rbt.validate(),
+        Err(RBViolation::Len {
+            len: 11,
+            actual: 10
+        })
+    );
+}
+
+#[test]
+fn test_viz() {
+    let mut rbt = RBTree::default();
+    for v in 1..=5 {
+        rbt.insert(v);
+    }
+    let expected = [
+        "    ┌── 5[R]",
+        "┌── 4[B]",
+        "│   └── 3[R]",
+        "2[B]",
+        "└── 1[B]",
+    ];
+    assert_eq!(rbt.to_ascii(), expected.join("\n") + "\n");
+    let dot = rbt.to_dot();
+    assert_eq!(dot.matches("fillcolor=red").count(), 2);
+    assert_eq!(dot.matches("fillcolor=black").count(), 3);
+    assert_eq!(dot.matches(" -> ").count(), 4);
+}
+
+#[test]
+fn test_traversal() {
+    let mut rbt = RBTree::default();
+    for v in 1..=5 {
+        rbt.insert(v);
+    }
+    //      2
+    //    1   4
+    //       3 5
+    assert_eq!(
+        rbt.pre_order_iter().copied().collect::<Vec<_>>(),
+        [2, 1, 4, 3, 5]
+    );
+    assert_eq!(
+        rbt.in_order_iter().copied().collect::<Vec<_>>(),
+        [1, 2, 3, 4, 5]
+    );
+    assert_eq!(
+        rbt.post_order_iter().copied().collect::<Vec<_>>(),
+        [1, 3, 5, 4, 2]
+    );
+    assert_eq!(
+        rbt.level_order_iter().copied().collect::<Vec<_>>(),
+        [2, 1, 4, 3, 5]
+    );
+
+    let mut rbt = RBTree::from_sorted_iter(0..1000);
+    let expected: Vec<i32> = rbt.iter().copied().collect();
+    assert_eq!(rbt.morris_in_order().collect::<Vec<_>>(), expected);
+    //中途停止后线索被恢复，树仍然合法
+    assert_eq!(rbt.morris_in_order().nth(500), Some(500));
+    assert_eq!(rbt.validate(), Ok(()));
+    assert!(rbt.in_order_iter().copied().eq(expected));
+    //迭代器被forget时树变为空树，不会留下线索
+    let mut it = rbt.morris_in_order();
+    assert_eq!(it.nth(500), Some(500));
+    std::mem::forget(it);
+    assert!(rbt.is_empty() && rbt.iter().next().is_none());
+    assert_eq!(rbt.validate(), Ok(()));
+    rbt.insert(1);
+    assert_eq!(rbt.morris_in_order().collect::<Vec<_>>(), [1]);
+}
+
+#[test]
+fn test_arena() {
+    let mut rbt = RBTree::default();
+    for i in 0..100 {
+        rbt.insert(i);
+    }
+    //删除的节点下标被复用，_nodes不再增长
+    for i in 0..50 {
+        rbt.delete(&i);
+    }
+    assert_eq!(rbt._free.len(), 50);
+    for i in 100..150 {
+        rbt.insert(i);
+    }
+    assert_eq!(rbt._nodes.len(), 100);
+    assert!(rbt._free.is_empty());
+    assert_eq!(rbt.validate(), Ok(()));
+
+    //clone后互不影响
+    let mut other = rbt.clone();
+    other.delete(&120);
+    assert!(rbt.contains(&120));
+    assert_ne!(rbt, other);
+
+    //值相同而节点位置不同的两棵树相等
+    let mut rev = RBTree::default();
+    for i in (50..150).rev() {
+        rev.insert(i);
+    }
+    assert_eq!(rbt, rev);
+    rbt.clear();
+    assert!(rbt.is_empty() && rbt._nodes.is_empty());
+}
+
+#[test]
+fn test_persistent() {
+    use crate::common::rand::XorShift;
+    use std::collections::{BTreeSet, HashSet};
+
+    //保留每一个版本及对应的BTreeSet快照
+    let mut versions = vec![(PersistentRBTree::default(), BTreeSet::new())];
+    let mut rng = XorShift::new(41);
+    for _ in 0..3000 {
+        let (tree, set) = versions.last().unwrap();
+        let (mut tree, mut set) = (tree.clone(), set.clone());
+        let v = rng.gen_range(600) as i32;
+        if rng.gen_range(3) == 0 {
+            tree = tree.remove(&v);
+            set.remove(&v);
+        } else {
+            tree = tree.insert(v);
+            set.insert(v);
+        }
+        versions.push((tree, set));
+    }
+    for (tree, set) in &versions {
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(tree.len(), set.len());
+        assert!(tree.iter().eq(set.iter()));
+        assert!(tree.iter().rev().eq(set.iter().rev()));
+    }
+    for (tree, set) in versions.iter().step_by(97) {
+        let sorted = set.iter().cloned().collect::<Vec<_>>();
+        for (k, v) in sorted.iter().enumerate() {
+            assert_eq!(tree.select(k), Some(v));
+            assert_eq!(tree.rank(v), k);
+        }
+        assert_eq!(tree.first(), set.first());
+        assert_eq!(tree.last(), set.last());
+        for t in [-1, 0, 100, 299, 300, 599, 600] {
+            assert!(tree.contains(&t) == set.contains(&t));
+            assert_eq!(tree.floor(&t), set.range(..=t).next_back());
+            assert_eq!(tree.ceiling(&t), set.range(t..).next());
+            assert_eq!(tree.predecessor(&t), set.range(..t).next_back());
+            assert_eq!(
+                tree.successor(&t),
+                set.range((Bound::Excluded(t), Bound::Unbounded)).next()
+            );
+            assert!(tree.range(t..t + 50).eq(set.range(t..t + 50)));
+            assert!(tree.range(..=t).rev().eq(set.range(..=t).rev()));
+            assert_eq!(tree.count_range(t..t + 50), set.range(t..t + 50).count());
+        }
+    }
+
+    //新版本只新增根到修改处路径上的节点
+    fn collect<T>(x: &PInnerNode<T>, res: &mut HashSet<*const PNode<T>>) {
+        if let Some(n) = x {
+            res.insert(Rc::as_ptr(n));
+            collect(&n._left, res);
+            collect(&n._right, res);
+        }
+    }
+    let mut tree = PersistentRBTree::default();
+    for i in 0..10000 {
+        tree = tree.insert(i);
+    }
+    let (mut old, mut new) = (HashSet::new(), HashSet::new());
+    let next = tree.insert(5000).remove(&7777).insert(20000);
+    collect(&tree._root, &mut old);
+    collect(&next._root, &mut new);
+    assert!(new.difference(&old).count() < 3 * 2 * 14);
+    assert!(tree.contains(&7777) && !next.contains(&7777));
+    //未修改时共享全部节点
+    assert!(tree.insert(10).ptr_eq(&tree));
+    assert!(tree.remove(&-1).ptr_eq(&tree));
+}
+
+#[test]
+fn test_set_ops() {
+    use crate::common::rand::XorShift;
+    use std::collections::BTreeSet;
+
+    for n in [0, 1, 2, 3, 6, 7, 8, 100, 1023, 1024] {
+        let rbt = RBTree::from_sorted_iter((0..n).flat_map(|x| [x, x]));
+        assert_eq!(rbt.validate(), Ok(()));
+        assert!(rbt.iter().copied().eq(0..n));
+    }
+
+    let mut rng = XorShift::new(53);
+    //随机集合，先插入再删除一部分，使数组中有空闲的下标
+    let mut random_set = |n: usize, max: usize| {
+        let mut rbt = RBTree::default();
+        let mut set = BTreeSet::new();
+        for _ in 0..n {
+            let v = rng.gen_range(max);
+            rbt.insert(v);
+            set.insert(v);
+        }
+        for _ in 0..n / 4 {
+            let v = rng.gen_range(max);
+            rbt.delete(&v);
+            set.remove(&v);
+        }
+        (rbt, set)
+    };
+    for (n, m) in [(0, 50), (1, 300), (300, 300), (2000, 40), (500, 1500)] {
+        let (a, sa) = random_set(n, 2000);
+        let (b, sb) = random_set(m, 2000);
+        let check = |t: RBTree<usize>, s: BTreeSet<usize>| {
+            assert_eq!(t.validate(), Ok(()));
+            assert!(t.into_iter().eq(s));
+        };
+        check(a.clone().union(b.clone()), &sa | &sb);
+        check(a.clone().intersection(b.clone()), &sa & &sb);
+        check(a.clone().difference(b.clone()), &sa - &sb);
+        check(b.clone().difference(a.clone()), &sb - &sa);
+
+        for key in [0, 700, 1000, 1999, 5000] {
+            let (l, m, r) = a.clone().split_at(&key);
+            assert_eq!(l.validate(), Ok(()));
+            assert_eq!(r.validate(), Ok(()));
+            assert_eq!(m, sa.get(&key).cloned());
+            assert!(l.iter().eq(sa.range(..key)));
+            assert!(r.iter().eq(sa.range(key + 1..)));
+            //拆开后再连接
+            let joined = RBTree::join(l, key, r);
+            assert_eq!(joined.validate(), Ok(()));
+            assert_eq!(joined.len(), sa.len() + m.is_none() as usize);
+        }
+    }
+
+    //两侧高度相差很大
+    let big = RBTree::from_sorted_iter(0..5000);
+    let small = RBTree::from_sorted_iter([6000, 6001]);
+    let joined = RBTree::join(big, 5500, small);
+    assert_eq!(joined.validate(), Ok(()));
+    assert!(joined
+        .iter()
+        .copied()
+        .eq((0..5000).chain([5500, 6000, 6001])));
+    let joined = RBTree::join(RBTree::new(-1), 0, RBTree::from_sorted_iter(1..3000));
+    assert_eq!(joined.validate(), Ok(()));
+    assert_eq!(joined.select(1), Some(&0));
+}
+
+//cargo test --release bench_rb -- --ignored --nocapture
+//20万个随机u64，单线程，release下的一次结果:
+//  RBTree:   insert 146ms, lookup 123ms, delete 197ms
+//  BTreeMap: insert  35ms, lookup  44ms, delete  38ms
+#[test]
+#[ignore]
+fn bench_rb() {
+    use crate::common::rand::XorShift;
+    use std::{collections::BTreeMap, time::Instant};
+
+    const N: usize = 200_000;
+    let mut rng = XorShift::new(1);
+    let keys = (0..N).map(|_| rng.next_u64()).collect::<Vec<_>>();
+
+    let start = Instant::now();
+    let mut rbt = RBTree::new(keys[0]);
+    for k in &keys[1..] {
+        rbt.insert(*k);
+    }
+    let insert = start.elapsed();
+    let start = Instant::now();
+    assert!(keys.iter().all(|k| rbt.contains(k)));
+    let lookup = start.elapsed();
+    let start = Instant::now();
+    for k in &keys {
+        rbt.delete(k);
+    }
+    let delete = start.elapsed();
+    println!("RBTree:   insert {insert:?}, lookup {lookup:?}, delete {delete:?}");
+
+    let start = Instant::now();
+    let mut map = BTreeMap::new();
+    for k in &keys {
+        map.insert(*k, ());
+    }
+    let insert = start.elapsed();
+    let start = Instant::now();
+    assert!(keys.iter().all(|k| map.contains_key(k)));
+    let lookup = start.elapsed();
+    let start = Instant::now();
+    for k in &keys {
+        map.remove(k);
+    }
+    let delete = start.elapsed();
+    println!("BTreeMap: insert {insert:?}, lookup {lookup:?}, delete {delete:?}");
+}
+
+#[test]
+fn test_leak() {
+    use crate::common::counted::Counted;
+    use std::{cell::Cell, rc::Rc};
+
+    //节点存放在数组中，没有指针环，删除、清空、drop都会释放值
+    let live = Rc::new(Cell::new(0));
+    let mut rbt = RBTree::default();
+    for i in 0..100 {
+        rbt.insert(Counted::new(i, &live));
+    }
+    for i in 0..30 {
+        rbt.delete(&Counted::new(i, &live));
+    }
+    assert_eq!(live.get(), 70);
+    rbt.clear();
+    assert_eq!(live.get(), 0);
+    for i in 0..100 {
+        rbt.insert(Counted::new(i, &live));
+    }
+    drop(rbt);
+    assert_eq!(live.get(), 0);
+
+    let mut map = RBMap::new();
+    for i in 0..50 {
+        map.insert(i, Counted::new(i, &live));
+    }
+    map.remove(&3);
+    map.entry(4).or_insert_with(|| Counted::new(-1, &live));
+    assert_eq!(live.get(), 49);
+    drop(map);
+    assert_eq!(live.get(), 0);
 }
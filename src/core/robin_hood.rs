@@ -0,0 +1,389 @@
+use crate::common::errs::{CAPACITY_OVERFLOW, OUT_OF_RANGE};
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hash},
+    mem,
+    ops::{Index, IndexMut},
+};
+
+//最大负载 7/8，开放寻址必须留有空槽，查找才能在空槽处停下
+const _LOAD_NUM: usize = 7;
+const _LOAD_DEN: usize = 8;
+
+/// 开放寻址哈希表(Robin Hood + 线性探测)
+/// 插入时与"离家更近"的元素交换位置，使探测距离的方差很小
+/// 删除时把后面的元素整体前移(backward shift)，不留墓碑
+/// API与拉链法的MyHashMap相同，所有元素放在一个连续的数组中，没有逐个节点的分配
+#[derive(Debug, Clone)]
+pub struct RobinHoodMap<K, V, S = RandomState> {
+    _slots: Vec<Option<Slot<K, V>>>,
+    _len: usize,
+    _hasher: S,
+}
+
+#[derive(Debug, Clone)]
+struct Slot<K, V> {
+    //保存完整的哈希，扩容时不必重新计算，比较key前也可先比较哈希
+    _hash: u64,
+    _key: K,
+    _val: V,
+}
+
+impl<K, V> RobinHoodMap<K, V> {
+    //默认设置容量为4
+    pub fn new() -> Self {
+        Self::with_cap(4)
+    }
+    //new并设置容量
+    pub fn with_cap(cap: usize) -> Self {
+        Self::with_capacity_and_hasher(cap, RandomState::new())
+    }
+}
+
+impl<K, V, S> RobinHoodMap<K, V, S> {
+    //指定哈希函数，容量为4
+    pub fn with_hasher(hasher: S) -> Self {
+        Self::with_capacity_and_hasher(4, hasher)
+    }
+    //指定容量和哈希函数，槽数取装载因子下能放下cap个元素的2的幂，下标用掩码计算
+    pub fn with_capacity_and_hasher(cap: usize, hasher: S) -> Self {
+        let n = cap
+            .checked_mul(_LOAD_DEN)
+            .and_then(|x| (x / _LOAD_NUM + 1).checked_next_power_of_two())
+            .expect(CAPACITY_OVERFLOW);
+        RobinHoodMap {
+            _slots: Self::_empty_slots(n),
+            _len: 0,
+            _hasher: hasher,
+        }
+    }
+    //哈希函数
+    pub fn hasher(&self) -> &S {
+        &self._hasher
+    }
+    pub fn len(&self) -> usize {
+        self._len
+    }
+    pub fn is_empty(&self) -> bool {
+        self._len == 0
+    }
+    //清空，保留容量
+    pub fn remove_all(&mut self) {
+        self._slots.iter_mut().for_each(|s| *s = None);
+        self._len = 0;
+    }
+
+    fn _empty_slots(n: usize) -> Vec<Option<Slot<K, V>>> {
+        let mut v = Vec::with_capacity(n);
+        v.resize_with(n, || None);
+        v
+    }
+    fn _mask(&self) -> usize {
+        self._slots.len() - 1
+    }
+    //下标i处哈希为hash的元素离理想位置的距离
+    fn _dist(&self, hash: u64, i: usize) -> usize {
+        i.wrapping_sub(hash as usize) & self._mask()
+    }
+    //插入一个确定不存在的元素，调用前需保证有空槽
+    fn _insert_slot(&mut self, mut cur: Slot<K, V>) {
+        let mask = self._mask();
+        let mut i = cur._hash as usize & mask;
+        let mut dist = 0;
+        loop {
+            let d = match &self._slots[i] {
+                Some(s) => self._dist(s._hash, i),
+                None => {
+                    self._slots[i] = Some(cur);
+                    return;
+                }
+            };
+            //劫富济贫：已有元素离家更近，把位置让给当前元素，继续为它找位置
+            if d < dist {
+                if let Some(s) = self._slots[i].as_mut() {
+                    mem::swap(s, &mut cur);
+                }
+                dist = d;
+            }
+            i = (i + 1) & mask;
+            dist += 1;
+        }
+    }
+    //容量翻倍，按保存的哈希重新放置
+    fn _resize(&mut self) {
+        let new_slots = Self::_empty_slots(self._slots.len() * 2);
+        let old = mem::replace(&mut self._slots, new_slots);
+        for s in old.into_iter().flatten() {
+            self._insert_slot(s);
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> RobinHoodMap<K, V, S> {
+    //k所在的下标
+    fn _find(&self, k: &K) -> Option<usize> {
+        let hash = self._hasher.hash_one(k);
+        let mask = self._mask();
+        let mut i = hash as usize & mask;
+        let mut dist = 0;
+        loop {
+            let s = self._slots[i].as_ref()?;
+            //按Robin Hood的顺序，k若存在不会排在离家更近的元素之后
+            if self._dist(s._hash, i) < dist {
+                return None;
+            }
+            if s._hash == hash && s._key == *k {
+                return Some(i);
+            }
+            i = (i + 1) & mask;
+            dist += 1;
+        }
+    }
+    pub fn get(&self, k: &K) -> Option<&V> {
+        let i = self._find(k)?;
+        self._slots[i].as_ref().map(|s| &s._val)
+    }
+    pub fn get_mut(&mut self, k: &K) -> Option<&mut V> {
+        let i = self._find(k)?;
+        self._slots[i].as_mut().map(|s| &mut s._val)
+    }
+    //删除k，返回其对应的值
+    pub fn remove(&mut self, k: &K) -> Option<V> {
+        let mut i = self._find(k)?;
+        let removed = self._slots[i].take()?;
+        //后面不在理想位置上的元素依次前移一格，直到空槽或在理想位置的元素
+        let mask = self._mask();
+        loop {
+            let next = (i + 1) & mask;
+            match &self._slots[next] {
+                Some(s) if self._dist(s._hash, next) > 0 => {
+                    self._slots[i] = self._slots[next].take();
+                    i = next;
+                }
+                _ => break,
+            }
+        }
+        self._len -= 1;
+        Some(removed._val)
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> RobinHoodMap<K, V, S> {
+    //插入，k已存在时替换值
+    pub fn put(&mut self, k: &K, v: V) {
+        if let Some(old) = self.get_mut(k) {
+            *old = v;
+            return;
+        }
+        //若插入后超过负载，扩容
+        if (self._len + 1) * _LOAD_DEN > self._slots.len() * _LOAD_NUM {
+            self._resize();
+        }
+        self._insert_slot(Slot {
+            _hash: self._hasher.hash_one(k),
+            _key: k.clone(),
+            _val: v,
+        });
+        self._len += 1;
+    }
+}
+
+impl<K, V, S: Default> Default for RobinHoodMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+//内容相同即相等，与槽的布局和哈希函数无关
+impl<K: Hash + Eq, V: PartialEq, S: BuildHasher> PartialEq for RobinHoodMap<K, V, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self._len == other._len
+            && self
+                ._slots
+                .iter()
+                .flatten()
+                .all(|s| other.get(&s._key) == Some(&s._val))
+    }
+}
+
+impl<K: Hash + Eq, V: Eq, S: BuildHasher> Eq for RobinHoodMap<K, V, S> {}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Index<&K> for RobinHoodMap<K, V, S> {
+    type Output = V;
+
+    fn index(&self, index: &K) -> &Self::Output {
+        self.get(index).expect(OUT_OF_RANGE)
+    }
+}
+impl<K: Hash + Eq, V, S: BuildHasher> IndexMut<&K> for RobinHoodMap<K, V, S> {
+    fn index_mut(&mut self, index: &K) -> &mut Self::Output {
+        self.get_mut(index).expect(OUT_OF_RANGE)
+    }
+}
+
+//检查Robin Hood的不变式：沿探测方向，每一步距离最多加1，空槽后的元素必在理想位置
+#[cfg(test)]
+fn _check<K, V, S>(map: &RobinHoodMap<K, V, S>) {
+    let n = map._slots.len();
+    assert_eq!(map._slots.iter().flatten().count(), map._len);
+    for i in 0..n {
+        let next = (i + 1) % n;
+        if let Some(s) = &map._slots[next] {
+            let d = map._dist(s._hash, next);
+            match &map._slots[i] {
+                Some(p) => assert!(d <= map._dist(p._hash, i) + 1),
+                None => assert_eq!(d, 0),
+            }
+        }
+    }
+}
+
+#[test]
+fn test() {
+    use crate::common::rand::XorShift;
+    use std::collections::HashMap;
+
+    let mut map = RobinHoodMap::new();
+    let mut std = HashMap::new();
+    let mut rng = XorShift::new(7);
+    for _ in 0..20000 {
+        let k = rng.gen_range(1000) as u32;
+        match rng.gen_range(3) {
+            0 => assert_eq!(map.remove(&k), std.remove(&k)),
+            _ => {
+                map.put(&k, k * 2);
+                std.insert(k, k * 2);
+            }
+        }
+        assert_eq!(map.len(), std.len());
+    }
+    _check(&map);
+    for k in 0..1000 {
+        assert_eq!(map.get(&k), std.get(&k));
+    }
+    //替换与下标访问
+    let k = *std.keys().next().unwrap();
+    map.put(&k, 1);
+    assert_eq!(map[&k], 1);
+    map[&k] += 1;
+    assert_eq!(map.get(&k), Some(&2));
+    map.remove_all();
+    assert!(map.is_empty());
+    assert_eq!(map.get(&k), None);
+    //按容量预留后插入cap个元素不扩容
+    let mut map = RobinHoodMap::with_cap(14);
+    let slots = map._slots.len();
+    for k in 0..14 {
+        map.put(&k, k);
+    }
+    assert_eq!(map._slots.len(), slots);
+}
+
+#[test]
+fn test_backward_shift() {
+    use crate::common::ident::BuildIdent;
+
+    //key本身作为哈希，可以精确构造冲突
+    let mut map: RobinHoodMap<u64, u64, BuildIdent> =
+        RobinHoodMap::with_capacity_and_hasher(13, Default::default());
+    assert_eq!(map._slots.len(), 16);
+    //1、17、33都以1为理想位置，2被挤到4，15绕回到0
+    for k in [1, 17, 33, 2, 15, 31] {
+        map.put(&k, k);
+    }
+    _check(&map);
+    let keys = |m: &RobinHoodMap<u64, u64, _>| {
+        m._slots
+            .iter()
+            .map(|s| s.as_ref().map_or(0, |s| s._key))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(keys(&map)[..6], [31, 1, 17, 33, 2, 0]);
+    assert_eq!(keys(&map)[15], 15);
+    //删除后后面的元素前移，不留墓碑
+    assert_eq!(map.remove(&17), Some(17));
+    assert_eq!(keys(&map)[..6], [31, 1, 33, 2, 0, 0]);
+    _check(&map);
+    //跨越数组末尾的前移
+    assert_eq!(map.remove(&15), Some(15));
+    assert_eq!(keys(&map)[15], 31);
+    assert_eq!(keys(&map)[0], 0);
+    _check(&map);
+    assert_eq!(map.remove(&17), None);
+    for k in [1, 33, 2, 31] {
+        assert_eq!(map.get(&k), Some(&k));
+    }
+}
+
+#[test]
+#[should_panic(expected = "capacity overflow")]
+fn test_capacity_overflow() {
+    //换算槽数时溢出，不能回绕成很小的表
+    let _: RobinHoodMap<u64, u64> = RobinHoodMap::with_cap(usize::MAX / 2);
+}
+
+//cargo test --release bench_hash_map -- --ignored --nocapture
+//cargo test --release --features simd bench_hash_map -- --ignored --nocapture
+//100万个随机u64，从默认容量开始插入，release下同一台机器上的一次结果:
+//                 insert  hit    miss   remove
+//  MyHashMap:     384ms   79ms   107ms  119ms
+//  RobinHoodMap:  209ms   80ms   80ms   74ms
+//  SwissMap:      118ms   93ms   33ms   118ms   (SWAR)
+//  SwissMap:      95ms    76ms   25ms   78ms    (simd, SSE2)
+//  HashMap:       75ms    62ms   25ms   63ms
+#[test]
+#[ignore]
+fn bench_hash_map() {
+    use super::{hm::MyHashMap, swiss_table::SwissMap};
+    use crate::common::rand::XorShift;
+    use std::{collections::HashMap, time::Instant};
+
+    const N: usize = 1_000_000;
+    let mut rng = XorShift::new(1);
+    let keys = (0..N).map(|_| rng.next_u64()).collect::<Vec<_>>();
+    let misses = (0..N).map(|_| rng.next_u64()).collect::<Vec<_>>();
+
+    //四项依次为插入、命中查找、未命中查找、删除
+    macro_rules! bench {
+        ($name:literal, $map:expr, $put:expr) => {{
+            let mut map = $map;
+            let start = Instant::now();
+            for k in &keys {
+                $put(&mut map, k);
+            }
+            let insert = start.elapsed();
+            let start = Instant::now();
+            assert!(keys.iter().all(|k| map.get(k) == Some(k)));
+            let hit = start.elapsed();
+            let start = Instant::now();
+            assert!(misses.iter().all(|k| map.get(k).is_none()));
+            let miss = start.elapsed();
+            let start = Instant::now();
+            for k in &keys {
+                assert_eq!(map.remove(k), Some(*k));
+            }
+            let remove = start.elapsed();
+            println!(
+                "{:<14} insert {insert:?}, hit {hit:?}, miss {miss:?}, remove {remove:?}",
+                $name
+            );
+        }};
+    }
+    bench!(
+        "MyHashMap:",
+        MyHashMap::new(),
+        |m: &mut MyHashMap<_, _>, k| m.put(k, *k)
+    );
+    bench!(
+        "RobinHoodMap:",
+        RobinHoodMap::new(),
+        |m: &mut RobinHoodMap<_, _>, k| m.put(k, *k)
+    );
+    bench!("SwissMap:", SwissMap::new(), |m: &mut SwissMap<_, _>, k| m
+        .put(k, *k));
+    bench!(
+        "HashMap:",
+        HashMap::new(),
+        |m: &mut HashMap<_, _>, k: &u64| m.insert(*k, *k)
+    );
+}
@@ -1,20 +1,669 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cmp::Ordering,
+    fmt::Debug,
+    mem,
+    ops::{Bound, RangeBounds},
+};
 
-use super::ordered_list::OrderedList;
+use crate::common::{errs::CAPACITY_OVERFLOW, rand::XorShift};
 
-pub struct SkipTable<T> {
-    _head: SkipInnerNode<T>,
+//默认最大层数
+const _MAX_LEVEL: usize = 16;
+//默认晋升概率
+const _P: f64 = 0.5;
+
+/// 跳表(有序集合)
+/// 每层是一条带头哨兵的单向链表，_down指向下一层的同值节点，最底层包含全部元素
+/// 节点存放在数组中，_next/_down为下标，删除的节点放入空闲列表复用
+/// V为元素附带的值，只存在最底层节点中，上层节点只存key；作为集合使用时V为()
+/// 4 ---------------------> 9
+/// 4 --------> 6 ---------> 9
+/// 4 --> 5 --> 6 --> 7 ---> 9
+pub struct SkipTable<T, V = ()> {
+    _nodes: Vec<SkipNode<T, V>>,
+    //被删除节点的下标
+    _free: Vec<u32>,
+    //最高层的头哨兵
+    _head: u32,
     _len: usize,
+    //当前层数
+    _level: usize,
+    _max_level: usize,
+    //晋升到上一层的概率
+    _p: f64,
+    _rng: XorShift,
+}
+
+type SkipInnerNode = Option<u32>;
+
+struct SkipNode<K, V> {
+    //头哨兵和空闲节点为None
+    _key: Option<K>,
+    //只有最底层节点有值
+    _val: Option<V>,
+    _next: SkipInnerNode,
+    _down: SkipInnerNode,
+}
+
+impl<K, V> SkipNode<K, V> {
+    fn new(key: Option<K>, val: Option<V>, next: SkipInnerNode, down: SkipInnerNode) -> Self {
+        Self {
+            _key: key,
+            _val: val,
+            _next: next,
+            _down: down,
+        }
+    }
+}
+
+impl<K, V> SkipTable<K, V> {
+    //设置最大层数与晋升概率
+    pub fn with_config(max_level: usize, p: f64) -> Self {
+        assert!(max_level > 0, "max_level must be positive");
+        assert!(p > 0.0 && p < 1.0, "p must be in (0, 1)");
+        Self {
+            _nodes: vec![SkipNode::new(None, None, None, None)],
+            _free: vec![],
+            _head: 0,
+            _len: 0,
+            _level: 1,
+            _max_level: max_level,
+            _p: p,
+            _rng: XorShift::default(),
+        }
+    }
+    //设置随机种子，种子相同则层数分布相同
+    pub fn seed(&mut self, seed: u64) {
+        self._rng = XorShift::new(seed);
+    }
+    //长度
+    pub fn len(&self) -> usize {
+        self._len
+    }
+    //为空返回true
+    pub fn is_empty(&self) -> bool {
+        self._len == 0
+    }
+    //当前层数
+    pub fn level(&self) -> usize {
+        self._level
+    }
+    //清空
+    pub fn clear(&mut self) {
+        self._nodes.clear();
+        self._nodes.push(SkipNode::new(None, None, None, None));
+        self._free.clear();
+        self._head = 0;
+        self._len = 0;
+        self._level = 1;
+    }
+
+    //随机生成新节点的层数
+    fn _random_level(&mut self) -> usize {
+        let mut level = 1;
+        while level < self._max_level && self._rng.next_f64() < self._p {
+            level += 1;
+        }
+        level
+    }
+
+    fn _node(&self, x: u32) -> &SkipNode<K, V> {
+        &self._nodes[x as usize]
+    }
+    fn _node_mut(&mut self, x: u32) -> &mut SkipNode<K, V> {
+        &mut self._nodes[x as usize]
+    }
+    //非头哨兵节点的key
+    fn _key(&self, x: u32) -> &K {
+        self._node(x)._key.as_ref().expect("head node has no key")
+    }
+    //最底层节点的键值对
+    fn _entry(&self, x: u32) -> (&K, &V) {
+        let node = self._node(x);
+        let key = node._key.as_ref().expect("head node has no key");
+        (
+            key,
+            node._val.as_ref().expect("only bottom nodes have values"),
+        )
+    }
+
+    //放入新节点，优先复用空闲的下标
+    fn _alloc(&mut self, node: SkipNode<K, V>) -> u32 {
+        match self._free.pop() {
+            Some(x) => {
+                *self._node_mut(x) = node;
+                x
+            }
+            None => {
+                let x = u32::try_from(self._nodes.len()).expect(CAPACITY_OVERFLOW);
+                self._nodes.push(node);
+                x
+            }
+        }
+    }
+    //取出节点的内容，下标放入空闲列表
+    fn _dealloc(&mut self, x: u32) -> SkipNode<K, V> {
+        self._free.push(x);
+        mem::replace(self._node_mut(x), SkipNode::new(None, None, None, None))
+    }
+
+    //最底层的头哨兵
+    fn _bottom_head(&self) -> u32 {
+        let mut cur = self._head;
+        while let Some(down) = self._node(cur)._down {
+            cur = down;
+        }
+        cur
+    }
+
+    //自顶向下，返回每层最后一个比较结果为Less的节点，插入、删除时用
+    //f(x)为x与目标的比较结果
+    fn _preds_by<F: Fn(&K) -> Ordering>(&self, f: &F) -> Vec<u32> {
+        let mut res = Vec::with_capacity(self._level);
+        let mut cur = Some(self._head);
+        while let Some(mut x) = cur {
+            while let Some(n) = self._node(x)._next {
+                if f(self._key(n)) != Ordering::Less {
+                    break;
+                }
+                x = n;
+            }
+            cur = self._node(x)._down;
+            res.push(x);
+        }
+        res
+    }
+    //与_preds_by相同的下行路径，只返回最底层的那个节点，不分配
+    fn _pred_by<F: Fn(&K) -> Ordering>(&self, f: &F) -> u32 {
+        let mut x = self._head;
+        loop {
+            while let Some(n) = self._node(x)._next {
+                if f(self._key(n)) != Ordering::Less {
+                    break;
+                }
+                x = n;
+            }
+            match self._node(x)._down {
+                Some(down) => x = down,
+                None => return x,
+            }
+        }
+    }
+
+    //最底层的第一个节点
+    fn _first(&self) -> SkipInnerNode {
+        self._node(self._bottom_head())._next
+    }
+
+    //最底层的最后一个节点，每层都走到最右再向下
+    fn _last(&self) -> SkipInnerNode {
+        let mut cur = Some(self._head);
+        let mut res = self._head;
+        while let Some(mut x) = cur {
+            while let Some(n) = self._node(x)._next {
+                x = n;
+            }
+            cur = self._node(x)._down;
+            res = x;
+        }
+        self._node(res)._key.as_ref().map(|_| res)
+    }
+
+    //最底层中第一个比较结果不为Less的节点
+    fn _lower_bound_by<F: Fn(&K) -> Ordering>(&self, f: &F) -> SkipInnerNode {
+        self._node(self._pred_by(f))._next
+    }
+
+    //每层中比较结果为Equal的节点及其前驱，自顶向下
+    fn _targets_by<F: Fn(&K) -> Ordering>(&self, f: &F) -> Vec<(u32, u32)> {
+        self._preds_by(f)
+            .into_iter()
+            .filter_map(|pred| {
+                let next = self._node(pred)._next?;
+                (f(self._key(next)) == Ordering::Equal).then_some((pred, next))
+            })
+            .collect()
+    }
+
+    //从所有层摘下_targets_by找到的节点，返回最底层的键值对
+    fn _unlink(&mut self, targets: Vec<(u32, u32)>) -> Option<(K, V)> {
+        let mut res = None;
+        for (pred, x) in targets {
+            let node = self._dealloc(x);
+            self._node_mut(pred)._next = node._next;
+            res = node._key.zip(node._val);
+        }
+        if res.is_some() {
+            self._len -= 1;
+            self._shrink();
+        }
+        res
+    }
+
+    //去掉顶部的空层
+    fn _shrink(&mut self) {
+        while self._level > 1 && self._node(self._head)._next.is_none() {
+            let head = self._head;
+            self._head = self._node(head)._down.expect("upper head has a down link");
+            self._dealloc(head);
+            self._level -= 1;
+        }
+    }
 }
 
-type SkipInnerNode<T> = Option<Rc<RefCell<SkipNode<T>>>>;
+impl<K: Ord, V> SkipTable<K, V> {
+    //key等于k的最底层节点
+    fn _find(&self, k: &K) -> SkipInnerNode {
+        self._lower_bound_by(&|x: &K| x.cmp(k))
+            .filter(|x| self._key(*x) == k)
+    }
+    //删除k，返回最底层的键值对
+    fn _remove(&mut self, k: &K) -> Option<(K, V)> {
+        let targets = self._targets_by(&|x: &K| x.cmp(k));
+        self._unlink(targets)
+    }
+    //删除最底层的节点x
+    fn _remove_node(&mut self, x: u32) -> Option<(K, V)> {
+        let targets = self._targets_by(&|k: &K| k.cmp(self._key(x)));
+        self._unlink(targets)
+    }
 
-struct SkipNode<T> {
-    _val: T,
-    _next: SkipInnerNode<T>,
-    _down: SkipInnerNode<T>,
+    //范围起点对应的最底层节点
+    fn _start_of(&self, start: Bound<&K>) -> SkipInnerNode {
+        match start {
+            Bound::Included(s) => self._lower_bound_by(&|x: &K| x.cmp(s)),
+            //等于s时也继续向右
+            Bound::Excluded(s) => self._lower_bound_by(&|x: &K| match x.cmp(s) {
+                Ordering::Greater => Ordering::Greater,
+                _ => Ordering::Less,
+            }),
+            Bound::Unbounded => self._first(),
+        }
+    }
+    //范围终点之后的第一个最底层节点，None表示直到末尾
+    fn _end_of(&self, end: Bound<&K>) -> SkipInnerNode {
+        match end {
+            Bound::Included(e) => self._start_of(Bound::Excluded(e)),
+            Bound::Excluded(e) => self._start_of(Bound::Included(e)),
+            Bound::Unbounded => None,
+        }
+    }
+    //按范围遍历最底层，不需要拷贝范围的端点
+    fn _range<R: RangeBounds<K>>(&self, range: R) -> MapIter<'_, K, V> {
+        let mut cur = self._start_of(range.start_bound());
+        let end = self._end_of(range.end_bound());
+        //起点不在终点之前时为空
+        if let (Some(s), Some(e)) = (cur, end) {
+            if self._key(s) >= self._key(e) {
+                cur = None;
+            }
+        }
+        MapIter {
+            _table: self,
+            _cur: cur,
+            _end: end,
+        }
+    }
 }
 
-impl<T> SkipNode<T> {}
+impl<K: Ord + Clone, V> SkipTable<K, V> {
+    //插入k，已存在时只替换最底层的值并返回旧值
+    fn _insert(&mut self, k: K, v: V) -> Option<V> {
+        let mut preds = self._preds_by(&|x: &K| x.cmp(&k));
+        let bottom = *preds.last().expect("at least one level");
+        if let Some(x) = self._node(bottom)._next.filter(|x| *self._key(*x) == k) {
+            return self._node_mut(x)._val.replace(v);
+        }
+
+        let level = self._random_level();
+        //层数不够则在顶部加层
+        while self._level < level {
+            let head = self._alloc(SkipNode::new(None, None, None, Some(self._head)));
+            preds.insert(0, head);
+            self._head = head;
+            self._level += 1;
+        }
+        //自底向上逐层插入，值只放在最底层，最上层直接移入k
+        let mut down = None;
+        let mut val = Some(v);
+        let mut key = Some(k);
+        for (i, pred) in preds.into_iter().rev().take(level).enumerate() {
+            let k = if i + 1 == level {
+                key.take()
+            } else {
+                key.clone()
+            };
+            let next = self._node(pred)._next;
+            let node = self._alloc(SkipNode::new(k, val.take(), next, down));
+            self._node_mut(pred)._next = Some(node);
+            down = Some(node);
+        }
+        self._len += 1;
+        None
+    }
+}
+
+impl<T: Ord> SkipTable<T> {
+    //是否包含t
+    pub fn contains(&self, t: &T) -> bool {
+        self._find(t).is_some()
+    }
+    //删除t，返回删除的值
+    pub fn remove(&mut self, t: &T) -> Option<T> {
+        self._remove(t).map(|e| e.0)
+    }
+    //获取与t相等的值
+    pub fn get(&self, t: &T) -> Option<&T> {
+        self._find(t).map(|x| self._key(x))
+    }
+    //从小到大遍历
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.range(..)
+    }
+    //按范围从小到大遍历
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Iter<'_, T> {
+        Iter {
+            _inner: self._range(range),
+        }
+    }
+}
+
+impl<T: Ord + Clone> SkipTable<T> {
+    //初始化并增加一个值
+    pub fn new(val: T) -> Self {
+        let mut tmp = Self::default();
+        tmp.insert(val);
+        tmp
+    }
+    //插入，已存在时返回false
+    pub fn insert(&mut self, val: T) -> bool {
+        self._insert(val, ()).is_none()
+    }
+}
+
+/// 按键遍历最底层节点，返回键值对的引用
+pub struct MapIter<'a, K, V> {
+    _table: &'a SkipTable<K, V>,
+    _cur: SkipInnerNode,
+    //范围之后的第一个节点
+    _end: SkipInnerNode,
+}
+
+impl<'a, K, V> Iterator for MapIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cur = self._cur.filter(|x| Some(*x) != self._end)?;
+        self._cur = self._table._node(cur)._next;
+        Some(self._table._entry(cur))
+    }
+}
 
-impl<T> SkipTable<T> {}
+/// 跳表迭代器，返回值的引用
+pub struct Iter<'a, T> {
+    _inner: MapIter<'a, T, ()>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self._inner.next().map(|e| e.0)
+    }
+}
+
+impl<K, V> Default for SkipTable<K, V> {
+    fn default() -> Self {
+        Self::with_config(_MAX_LEVEL, _P)
+    }
+}
+
+impl<T: Debug> Debug for SkipTable<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut set = f.debug_set();
+        let mut cur = self._first();
+        while let Some(x) = cur {
+            set.entry(self._key(x));
+            cur = self._node(x)._next;
+        }
+        set.finish()
+    }
+}
+
+/// 基于跳表的有序映射，接口参照BTreeMap
+/// 上层节点只存K，V只在最底层存一份，查询返回引用
+pub struct SkipMap<K, V> {
+    _table: SkipTable<K, V>,
+}
+
+impl<K, V> SkipMap<K, V> {
+    //设置最大层数与晋升概率
+    pub fn with_config(max_level: usize, p: f64) -> Self {
+        Self {
+            _table: SkipTable::with_config(max_level, p),
+        }
+    }
+    //设置随机种子
+    pub fn seed(&mut self, seed: u64) {
+        self._table.seed(seed);
+    }
+    //长度
+    pub fn len(&self) -> usize {
+        self._table.len()
+    }
+    //为空返回true
+    pub fn is_empty(&self) -> bool {
+        self._table.is_empty()
+    }
+    //清空
+    pub fn clear(&mut self) {
+        self._table.clear();
+    }
+    //最小的键值对
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self._table._first().map(|x| self._table._entry(x))
+    }
+    //最大的键值对
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self._table._last().map(|x| self._table._entry(x))
+    }
+}
+
+impl<K: Ord, V> SkipMap<K, V> {
+    //是否包含k
+    pub fn contains_key(&self, k: &K) -> bool {
+        self._table._find(k).is_some()
+    }
+    //删除k，返回删除的值
+    pub fn remove(&mut self, k: &K) -> Option<V> {
+        self._table._remove(k).map(|e| e.1)
+    }
+    //获取k对应的值
+    pub fn get(&self, k: &K) -> Option<&V> {
+        self._table._find(k).map(|x| self._table._entry(x).1)
+    }
+    //获取k对应的可变值
+    pub fn get_mut(&mut self, k: &K) -> Option<&mut V> {
+        let x = self._table._find(k)?;
+        self._table._node_mut(x)._val.as_mut()
+    }
+    //小于等于k的最大键值对
+    pub fn floor(&self, k: &K) -> Option<(&K, &V)> {
+        //等于k时也继续向右，最底层的前驱即为所求
+        let f = |x: &K| match x.cmp(k) {
+            Ordering::Greater => Ordering::Greater,
+            _ => Ordering::Less,
+        };
+        let pred = self._table._pred_by(&f);
+        self._table._node(pred)._key.as_ref()?;
+        Some(self._table._entry(pred))
+    }
+    //大于等于k的最小键值对
+    pub fn ceiling(&self, k: &K) -> Option<(&K, &V)> {
+        self._table
+            ._lower_bound_by(&|x: &K| x.cmp(k))
+            .map(|x| self._table._entry(x))
+    }
+    //删除并返回最小的键值对
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        let x = self._table._first()?;
+        self._table._remove_node(x)
+    }
+    //删除并返回最大的键值对
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        let x = self._table._last()?;
+        self._table._remove_node(x)
+    }
+    //按键从小到大遍历
+    pub fn iter(&self) -> MapIter<'_, K, V> {
+        self.range(..)
+    }
+    //所有键
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|e| e.0)
+    }
+    //所有值
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|e| e.1)
+    }
+    //按键的范围从小到大遍历
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> MapIter<'_, K, V> {
+        self._table._range(range)
+    }
+}
+
+impl<K: Ord + Clone, V> SkipMap<K, V> {
+    //初始化并增加一个键值对
+    pub fn new(k: K, v: V) -> Self {
+        let mut tmp = Self::default();
+        tmp.insert(k, v);
+        tmp
+    }
+    //插入，已存在时替换并返回旧值
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        self._table._insert(k, v)
+    }
+}
+
+impl<K, V> Default for SkipMap<K, V> {
+    fn default() -> Self {
+        Self {
+            _table: SkipTable::default(),
+        }
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for SkipMap<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut map = f.debug_map();
+        let mut cur = self._table._first();
+        while let Some(x) = cur {
+            let (k, v) = self._table._entry(x);
+            map.entry(k, v);
+            cur = self._table._node(x)._next;
+        }
+        map.finish()
+    }
+}
+
+#[test]
+fn test() {
+    let mut st = SkipTable::new(5);
+    st.seed(7);
+    for i in [3, 9, 1, 7, 5, 8] {
+        st.insert(i);
+    }
+    println!("st={:?}, level={}", st, st.level());
+    assert_eq!(st.len(), 6);
+    assert!(st.contains(&7));
+    assert_eq!(st.get(&9), Some(&9));
+    assert_eq!(st.remove(&7), Some(7));
+    assert_eq!(st.remove(&7), None);
+    assert_eq!(st.iter().copied().collect::<Vec<_>>(), vec![1, 3, 5, 8, 9]);
+    assert_eq!(st.range(3..8).copied().collect::<Vec<_>>(), vec![3, 5]);
+    assert_eq!(
+        st.range((Bound::Excluded(3), Bound::Included(8)))
+            .copied()
+            .collect::<Vec<_>>(),
+        vec![5, 8]
+    );
+    assert_eq!(
+        st.range((Bound::Included(8), Bound::Excluded(3))).next(),
+        None
+    );
+}
+
+#[test]
+fn test_random() {
+    use std::collections::BTreeSet;
+
+    let mut st = SkipTable::with_config(8, 0.25);
+    st.seed(42);
+    let mut set = BTreeSet::new();
+    let mut rng = XorShift::new(1);
+    for _ in 0..5000 {
+        let v = rng.gen_range(500);
+        if rng.gen_range(3) == 0 {
+            assert_eq!(st.remove(&v), set.take(&v));
+        } else {
+            assert_eq!(st.insert(v), set.insert(v));
+        }
+        assert_eq!(st.len(), set.len());
+    }
+    assert!(st.level() <= 8);
+    assert!(st.iter().eq(set.iter()));
+    assert!(st.range(100..=300).eq(set.range(100..=300)));
+    //删除的节点被复用，数组不会一直增长
+    assert!(st._nodes.len() < 5000);
+    st.clear();
+    assert!(st.is_empty());
+    assert_eq!(st.iter().next(), None);
+}
+
+#[test]
+fn test_map() {
+    use std::collections::BTreeMap;
+
+    let mut sm = SkipMap::new(5, "five");
+    sm.seed(3);
+    assert_eq!(sm.insert(1, "one"), None);
+    assert_eq!(sm.insert(9, "nine"), None);
+    assert_eq!(sm.insert(5, "FIVE"), Some("five"));
+    println!("sm={:?}", sm);
+    assert_eq!(sm.get(&5), Some(&"FIVE"));
+    *sm.get_mut(&9).unwrap() = "NINE";
+    assert_eq!(sm.first(), Some((&1, &"one")));
+    assert_eq!(sm.last(), Some((&9, &"NINE")));
+    assert_eq!(sm.floor(&4), Some((&1, &"one")));
+    assert_eq!(sm.floor(&5), Some((&5, &"FIVE")));
+    assert_eq!(sm.floor(&0), None);
+    assert_eq!(sm.ceiling(&6), Some((&9, &"NINE")));
+    assert_eq!(sm.ceiling(&10), None);
+    assert_eq!(
+        sm.range(2..).collect::<Vec<_>>(),
+        vec![(&5, &"FIVE"), (&9, &"NINE")]
+    );
+    assert_eq!(sm.pop_first(), Some((1, "one")));
+    assert_eq!(sm.pop_last(), Some((9, "NINE")));
+    assert_eq!(sm.keys().collect::<Vec<_>>(), vec![&5]);
+
+    //与BTreeMap对比，值不需要Clone
+    let mut sm = SkipMap::default();
+    let mut bm = BTreeMap::new();
+    let mut rng = XorShift::new(9);
+    for i in 0..3000 {
+        let k = rng.gen_range(300);
+        match rng.gen_range(4) {
+            0 => assert_eq!(sm.remove(&k), bm.remove(&k)),
+            1 => assert_eq!(sm.pop_first(), bm.pop_first()),
+            _ => assert_eq!(sm.insert(k, Box::new(i)), bm.insert(k, Box::new(i))),
+        }
+        assert_eq!(sm.len(), bm.len());
+        assert_eq!(sm.last(), bm.last_key_value());
+        assert_eq!(sm.floor(&k), bm.range(..=k).next_back());
+    }
+    assert!(sm.range(50..200).eq(bm.range(50..200)));
+    //每个值只在最底层存一份
+    let vals = sm._table._nodes.iter().filter(|n| n._val.is_some()).count();
+    assert_eq!(vals, sm.len());
+}
@@ -0,0 +1,450 @@
+use std::{cmp::Ordering, fmt::Debug};
+
+use crate::common::{errs::NOT_SORTED, pos::Pos};
+
+/// 伸展树，每次访问(查找、插入、删除)后把访问到的节点旋转到根
+/// 频繁访问的key会停留在根附近，单次操作均摊O(log n)
+/// 采用自顶向下伸展：沿查找路径把节点分别挂到左、右两棵临时树上，最后拼回根的两侧
+pub struct SplayTree<K, V> {
+    _root: InnerSplayNode<K, V>,
+    //累计旋转次数
+    _rotations: usize,
+}
+
+type InnerSplayNode<K, V> = Option<Box<SplayNode<K, V>>>;
+
+#[derive(Debug)]
+pub struct SplayNode<K, V> {
+    //以Pos为下标: [左, 右]
+    _children: [InnerSplayNode<K, V>; 2],
+    _key: K,
+    _val: V,
+    //以该节点为根的子树的节点数
+    _size: usize,
+}
+
+impl<K, V> SplayNode<K, V> {
+    pub fn new(key: K, val: V) -> Self {
+        Self {
+            _children: [None, None],
+            _key: key,
+            _val: val,
+            _size: 1,
+        }
+    }
+    pub fn get_key(&self) -> &K {
+        &self._key
+    }
+    pub fn get_val(&self) -> &V {
+        &self._val
+    }
+    pub fn child(&self, pos: Pos) -> Option<&Self> {
+        self._children[pos as usize].as_deref()
+    }
+    fn _take_child(&mut self, pos: Pos) -> InnerSplayNode<K, V> {
+        self._children[pos as usize].take()
+    }
+    fn _set_child(&mut self, pos: Pos, child: InnerSplayNode<K, V>) {
+        self._children[pos as usize] = child;
+    }
+    fn _pull_up(&mut self) {
+        self._size = 1 + _size(self.child(Pos::Left)) + _size(self.child(Pos::Right));
+    }
+}
+
+fn _size<K, V>(node: Option<&SplayNode<K, V>>) -> usize {
+    node.map_or(0, |x| x._size)
+}
+
+//查找方向: Less往左，Greater往右，Equal表示找到
+fn _dir(ord: Ordering) -> Option<Pos> {
+    match ord {
+        Ordering::Less => Some(Pos::Left),
+        Ordering::Greater => Some(Pos::Right),
+        Ordering::Equal => None,
+    }
+}
+
+impl<K, V> Default for SplayTree<K, V> {
+    fn default() -> Self {
+        Self {
+            _root: None,
+            _rotations: 0,
+        }
+    }
+}
+
+impl<K, V> SplayTree<K, V> {
+    pub fn new(key: K, val: V) -> Self {
+        Self {
+            _root: Some(Box::new(SplayNode::new(key, val))),
+            _rotations: 0,
+        }
+    }
+    fn _from_root(root: InnerSplayNode<K, V>) -> Self {
+        Self {
+            _root: root,
+            _rotations: 0,
+        }
+    }
+    //长度
+    pub fn len(&self) -> usize {
+        _size(self._root.as_deref())
+    }
+    //为空返回true
+    pub fn is_empty(&self) -> bool {
+        self._root.is_none()
+    }
+    //累计旋转次数
+    pub fn rotations(&self) -> usize {
+        self._rotations
+    }
+    //根节点，即最近一次访问的节点
+    pub fn root(&self) -> Option<&SplayNode<K, V>> {
+        self._root.as_deref()
+    }
+    //清空
+    pub fn clear(&mut self) {
+        _drop_nodes(self._root.take());
+    }
+    //按key从小到大遍历，不改变树的结构
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut it = Iter { _stack: vec![] };
+        it._push_left(self._root.as_deref());
+        it
+    }
+
+    //把x在pos一侧的子节点转上来，返回新的子树根
+    //pos为Left时即右旋，为Right时即左旋
+    fn _rotate(&mut self, mut x: Box<SplayNode<K, V>>, pos: Pos) -> Box<SplayNode<K, V>> {
+        self._rotations += 1;
+        let mut c = x._take_child(pos).unwrap();
+        x._set_child(pos, c._take_child(pos.opposite()));
+        x._pull_up();
+        c._set_child(pos.opposite(), Some(x));
+        c._pull_up();
+        c
+    }
+    //把f指向的节点伸展到根，f返回目标相对于节点key的方向
+    //目标不存在时，最后访问的节点(目标的前驱或后继)成为根
+    fn _splay_by<F: Fn(&K) -> Ordering>(&mut self, f: F) {
+        let mut t = match self._root.take() {
+            Some(t) => t,
+            None => return,
+        };
+        //hangs[Left]为比目标小的节点，hangs[Right]为比目标大的节点，按自上而下的顺序
+        let mut hangs: [Vec<Box<SplayNode<K, V>>>; 2] = [vec![], vec![]];
+        while let Some(pos) = _dir(f(&t._key)) {
+            let c = match t.child(pos) {
+                Some(c) => c,
+                None => break,
+            };
+            //zig-zig: 先旋转一次
+            if _dir(f(&c._key)) == Some(pos) {
+                t = self._rotate(t, pos);
+                if t.child(pos).is_none() {
+                    break;
+                }
+            }
+            //t及其另一侧子树整体挂到相反一侧
+            let c = t._take_child(pos).unwrap();
+            hangs[pos.opposite() as usize].push(t);
+            t = c;
+        }
+        //自下而上拼接，下层挂在上层空出来的一侧
+        for pos in [Pos::Left, Pos::Right] {
+            let mut acc = t._take_child(pos);
+            while let Some(mut piece) = hangs[pos as usize].pop() {
+                piece._set_child(pos.opposite(), acc);
+                piece._pull_up();
+                acc = Some(piece);
+            }
+            t._set_child(pos, acc);
+        }
+        t._pull_up();
+        self._root = Some(t);
+    }
+}
+
+impl<K: Ord, V> SplayTree<K, V> {
+    //把key伸展到根，存在时返回true
+    fn _splay(&mut self, key: &K) -> bool {
+        self._splay_by(|x| key.cmp(x));
+        self._root.as_ref().is_some_and(|x| x._key == *key)
+    }
+    //是否包含key，同样会伸展
+    pub fn contains_key(&mut self, key: &K) -> bool {
+        self._splay(key)
+    }
+    //查找
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        if !self._splay(key) {
+            return None;
+        }
+        self._root.as_deref().map(|x| &x._val)
+    }
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        if !self._splay(key) {
+            return None;
+        }
+        self._root.as_deref_mut().map(|x| &mut x._val)
+    }
+    //插入，key已存在时替换并返回旧值
+    pub fn insert(&mut self, key: K, val: V) -> Option<V> {
+        if self._splay(&key) {
+            let root = self._root.as_deref_mut().unwrap();
+            return Some(std::mem::replace(&mut root._val, val));
+        }
+        let mut node = Box::new(SplayNode::new(key, val));
+        //伸展后根为key的前驱或后继，从根处断开挂到新节点两侧
+        if let Some(mut root) = self._root.take() {
+            let pos = if node._key < root._key {
+                Pos::Left
+            } else {
+                Pos::Right
+            };
+            node._set_child(pos, root._take_child(pos));
+            root._pull_up();
+            node._set_child(pos.opposite(), Some(root));
+            node._pull_up();
+        }
+        self._root = Some(node);
+        None
+    }
+    //删除，返回删除的值
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        if !self._splay(key) {
+            return None;
+        }
+        let mut root = self._root.take().unwrap();
+        let right = root._take_child(Pos::Right);
+        //左子树的最大节点伸展到根后没有右子节点，右子树挂上去
+        self._root = root._take_child(Pos::Left);
+        self._splay_by(|_| Ordering::Greater);
+        match self._root.as_deref_mut() {
+            Some(x) => {
+                x._set_child(Pos::Right, right);
+                x._pull_up();
+            }
+            None => self._root = right,
+        }
+        Some(root._val)
+    }
+    //按key拆分为(小于key的部分, 不小于key的部分)，均摊O(log n)
+    //旋转次数只留在前一部分，合并回去时不会重复计数
+    pub fn split(mut self, key: &K) -> (Self, Self) {
+        self._splay(key);
+        let mut root = match self._root.take() {
+            Some(root) => root,
+            None => return (self, Self::default()),
+        };
+        let rotations = self._rotations;
+        let (mut l, mut r) = if root._key < *key {
+            let r = root._take_child(Pos::Right);
+            root._pull_up();
+            (Self::_from_root(Some(root)), Self::_from_root(r))
+        } else {
+            let l = root._take_child(Pos::Left);
+            root._pull_up();
+            (Self::_from_root(l), Self::_from_root(Some(root)))
+        };
+        l._rotations = rotations;
+        (l, r)
+    }
+    //合并，self中的key须全部小于other中的key，均摊O(log n)
+    pub fn merge(mut self, mut other: Self) -> Self {
+        //self的最大值伸展到根，other的最小值伸展到根
+        self._splay_by(|_| Ordering::Greater);
+        other._splay_by(|_| Ordering::Less);
+        self._rotations += other._rotations;
+        let right = other._root.take();
+        let root = match self._root.as_deref_mut() {
+            Some(root) => root,
+            None => {
+                self._root = right;
+                return self;
+            }
+        };
+        let ordered = right.as_ref().is_none_or(|r| root._key < r._key);
+        assert!(ordered, "{}", NOT_SORTED);
+        root._set_child(Pos::Right, right);
+        root._pull_up();
+        self
+    }
+}
+
+//逐个释放节点，避免树退化成链表时递归drop爆栈
+fn _drop_nodes<K, V>(root: InnerSplayNode<K, V>) {
+    let mut stack: Vec<Box<SplayNode<K, V>>> = root.into_iter().collect();
+    while let Some(mut x) = stack.pop() {
+        stack.extend(x._take_child(Pos::Left));
+        stack.extend(x._take_child(Pos::Right));
+    }
+}
+
+impl<K, V> Drop for SplayTree<K, V> {
+    fn drop(&mut self) {
+        _drop_nodes(self._root.take());
+    }
+}
+
+/// 中序遍历迭代器，栈中为左子树已访问完、自身尚未访问的祖先
+pub struct Iter<'a, K, V> {
+    _stack: Vec<&'a SplayNode<K, V>>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    //压入x及其左链
+    fn _push_left(&mut self, mut x: Option<&'a SplayNode<K, V>>) {
+        while let Some(y) = x {
+            self._stack.push(y);
+            x = y.child(Pos::Left);
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let x = self._stack.pop()?;
+        self._push_left(x.child(Pos::Right));
+        Some((&x._key, &x._val))
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SplayTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for SplayTree<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+//检查有序性和子树大小，返回子树大小
+fn _check<K: Ord, V>(node: Option<&SplayNode<K, V>>) -> usize {
+    let x = match node {
+        Some(x) => x,
+        None => return 0,
+    };
+    if let Some(l) = x.child(Pos::Left) {
+        assert!(l._key < x._key);
+    }
+    if let Some(r) = x.child(Pos::Right) {
+        assert!(x._key < r._key);
+    }
+    let size = 1 + _check(x.child(Pos::Left)) + _check(x.child(Pos::Right));
+    assert_eq!(x._size, size);
+    size
+}
+
+#[test]
+fn test() {
+    use crate::common::rand::XorShift;
+    use std::collections::BTreeMap;
+
+    let mut tree = SplayTree::default();
+    let mut map = BTreeMap::new();
+    let mut rng = XorShift::new(15);
+    for i in 0..5000 {
+        let k = rng.gen_range(1000);
+        match rng.gen_range(3) {
+            0 => assert_eq!(tree.remove(&k), map.remove(&k)),
+            1 => assert_eq!(tree.get(&k), map.get(&k)),
+            _ => assert_eq!(tree.insert(k, i), map.insert(k, i)),
+        }
+        assert_eq!(tree.len(), map.len());
+    }
+    _check(tree.root());
+    assert!(tree.iter().eq(map.iter()));
+    //访问过的key成为根
+    if let Some(v) = tree.get_mut(&500) {
+        *v += 1;
+        assert_eq!(tree.root().map(|x| *x.get_key()), Some(500));
+    }
+    assert!(tree.contains_key(&map.keys().next().copied().unwrap()));
+    assert_eq!(tree.root().map(|x| x.get_key()), map.keys().next());
+
+    let (l, r) = tree.split(&600);
+    _check(l.root());
+    _check(r.root());
+    assert!(l.iter().all(|(k, _)| *k < 600));
+    assert!(r.iter().all(|(k, _)| *k >= 600));
+    assert_eq!(l.len() + r.len(), map.len());
+    assert_eq!(r.rotations(), 0);
+    let rotations = l.rotations();
+    let tree = l.merge(r);
+    _check(tree.root());
+    //合并只多出两次伸展的旋转，拆分前的次数不会算两遍
+    assert!(tree.rotations() - rotations <= map.len());
+    assert!(tree.iter().map(|(k, _)| k).eq(map.keys()));
+}
+
+#[test]
+#[should_panic(expected = "not sorted")]
+fn test_merge_overlap() {
+    let l = SplayTree::new(5, ());
+    let r = SplayTree::new(3, ());
+    l.merge(r);
+}
+
+#[test]
+fn test_skewed() {
+    use crate::common::rand::XorShift;
+
+    let n = 1 << 14;
+    let mut rng = XorShift::new(16);
+    let mut tree = SplayTree::default();
+    for _ in 0..n {
+        tree.insert(rng.gen_range(n), ());
+    }
+    let n = tree.len();
+    let log_n = (n as f64).log2();
+
+    //顺序访问全部key，总旋转次数为O(n)
+    let start = tree.rotations();
+    let keys: Vec<usize> = tree.iter().map(|(k, _)| *k).collect();
+    for k in &keys {
+        assert!(tree.contains_key(k));
+    }
+    let per_access = (tree.rotations() - start) as f64 / n as f64;
+    println!("sequential: {per_access:.2} rotations per access");
+    assert!(per_access < 3.0);
+
+    //反复访问16个热点key，均摊代价只与热点集合大小有关
+    let hot: Vec<usize> = (0..16).map(|_| keys[rng.gen_range(n)]).collect();
+    for k in &hot {
+        tree.contains_key(k);
+    }
+    let start = tree.rotations();
+    let accesses = 100_000;
+    for _ in 0..accesses {
+        assert!(tree.contains_key(&hot[rng.gen_range(hot.len())]));
+    }
+    let per_access = (tree.rotations() - start) as f64 / accesses as f64;
+    println!("hot set: {per_access:.2} rotations per access, log n = {log_n:.2}");
+    assert!(per_access < log_n / 2.0);
+
+    //单调插入使树退化成链，再访问最小值，链的长度约减半
+    let mut tree = SplayTree::default();
+    for k in 0..n {
+        tree.insert(k, ());
+    }
+    assert!(tree.contains_key(&0));
+    let mut height = 0;
+    let mut stack: Vec<_> = tree.root().map(|x| (x, 1)).into_iter().collect();
+    while let Some((x, d)) = stack.pop() {
+        height = height.max(d);
+        stack.extend(x.child(Pos::Left).map(|c| (c, d + 1)));
+        stack.extend(x.child(Pos::Right).map(|c| (c, d + 1)));
+    }
+    assert!(height <= n / 2 + 2);
+}
@@ -0,0 +1,502 @@
+use crate::common::errs::{CAPACITY_OVERFLOW, OUT_OF_RANGE};
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hash},
+    mem,
+    ops::{Index, IndexMut},
+};
+
+//控制字节：最高位为1时是空槽或墓碑，为0时低7位是哈希的高7位(h2)
+const _EMPTY: u8 = 0xFF;
+const _DELETED: u8 = 0x80;
+//一组控制字节的个数，一次比较一组
+const _WIDTH: usize = 16;
+
+/// SwissTable风格的开放寻址哈希表(参考hashbrown)
+/// 每个槽对应一个控制字节，查找时一次比较一组16个控制字节，
+/// 只有h2相同的槽才去比较key，遇到含空槽的组即可停止
+/// 删除时若所在位置附近没有空槽则留下墓碑，墓碑过多时原地重建
+/// 开启simd feature时用SSE2比较一组，否则用u64按位运算(SWAR)
+#[derive(Debug, Clone)]
+pub struct SwissMap<K, V, S = RandomState> {
+    //长度为槽数+_WIDTH，末尾_WIDTH个字节是开头的镜像，从任意下标读一组都不必回绕
+    _ctrl: Vec<u8>,
+    _slots: Vec<Option<(K, V)>>,
+    _len: usize,
+    //还能占用多少个空槽(墓碑不算)
+    _growth_left: usize,
+    _hasher: S,
+}
+
+impl<K, V> SwissMap<K, V> {
+    //默认设置容量为4
+    pub fn new() -> Self {
+        Self::with_cap(4)
+    }
+    //new并设置容量
+    pub fn with_cap(cap: usize) -> Self {
+        Self::with_capacity_and_hasher(cap, RandomState::new())
+    }
+}
+
+impl<K, V, S> SwissMap<K, V, S> {
+    //指定哈希函数，容量为4
+    pub fn with_hasher(hasher: S) -> Self {
+        Self::with_capacity_and_hasher(4, hasher)
+    }
+    //指定容量和哈希函数，槽数为2的幂且至少为一组
+    pub fn with_capacity_and_hasher(cap: usize, hasher: S) -> Self {
+        let buckets = cap
+            .checked_mul(8)
+            .and_then(|x| (x / 7 + 1).max(_WIDTH).checked_next_power_of_two())
+            .expect(CAPACITY_OVERFLOW);
+        let mut map = SwissMap {
+            _ctrl: vec![],
+            _slots: vec![],
+            _len: 0,
+            _growth_left: 0,
+            _hasher: hasher,
+        };
+        map._reset(buckets);
+        map
+    }
+    //哈希函数
+    pub fn hasher(&self) -> &S {
+        &self._hasher
+    }
+    pub fn len(&self) -> usize {
+        self._len
+    }
+    pub fn is_empty(&self) -> bool {
+        self._len == 0
+    }
+    //清空，保留容量
+    pub fn remove_all(&mut self) {
+        self._reset(self._slots.len());
+    }
+
+    //槽数为buckets时最多存放的元素个数，负载上限7/8
+    fn _capacity(buckets: usize) -> usize {
+        buckets / 8 * 7
+    }
+    //重置为buckets个空槽，元素个数也清零
+    fn _reset(&mut self, buckets: usize) -> Vec<Option<(K, V)>> {
+        let mut slots = Vec::with_capacity(buckets);
+        slots.resize_with(buckets, || None);
+        self._ctrl = vec![_EMPTY; buckets + _WIDTH];
+        self._len = 0;
+        self._growth_left = Self::_capacity(buckets);
+        mem::replace(&mut self._slots, slots)
+    }
+    fn _mask(&self) -> usize {
+        self._slots.len() - 1
+    }
+    //设置控制字节，开头_WIDTH个同时写到末尾的镜像中
+    fn _set_ctrl(&mut self, i: usize, c: u8) {
+        let mirror = (i.wrapping_sub(_WIDTH) & self._mask()) + _WIDTH;
+        self._ctrl[i] = c;
+        self._ctrl[mirror] = c;
+    }
+    //哈希为hash的元素可以放入的第一个空槽或墓碑
+    fn _find_insert_slot(&self, hash: u64) -> usize {
+        let mut probe = Probe::new(hash, self._mask());
+        loop {
+            let group = Group::load(&self._ctrl[probe._pos..]);
+            if let Some(bit) = group.match_empty_or_deleted().lowest() {
+                return (probe._pos + bit) & self._mask();
+            }
+            probe.next();
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> SwissMap<K, V, S> {
+    //k所在的下标
+    fn _find(&self, hash: u64, k: &K) -> Option<usize> {
+        let mut probe = Probe::new(hash, self._mask());
+        loop {
+            let group = Group::load(&self._ctrl[probe._pos..]);
+            for bit in group.match_byte(_h2(hash)) {
+                let i = (probe._pos + bit) & self._mask();
+                if matches!(&self._slots[i], Some((key, _)) if key == k) {
+                    return Some(i);
+                }
+            }
+            //组内有空槽说明k从未越过这一组
+            if group.match_empty().any() {
+                return None;
+            }
+            probe.next();
+        }
+    }
+    //空槽用完时重建：元素超过容量一半则槽数翻倍，否则只是清掉墓碑
+    fn _rehash(&mut self) {
+        let buckets = self._slots.len();
+        let buckets = if self._len + 1 > Self::_capacity(buckets) / 2 {
+            buckets * 2
+        } else {
+            buckets
+        };
+        let old = self._reset(buckets);
+        for (k, v) in old.into_iter().flatten() {
+            let hash = self._hasher.hash_one(&k);
+            let i = self._find_insert_slot(hash);
+            self._set_ctrl(i, _h2(hash));
+            self._slots[i] = Some((k, v));
+            self._len += 1;
+            self._growth_left -= 1;
+        }
+    }
+    pub fn get(&self, k: &K) -> Option<&V> {
+        let i = self._find(self._hasher.hash_one(k), k)?;
+        self._slots[i].as_ref().map(|(_, v)| v)
+    }
+    pub fn get_mut(&mut self, k: &K) -> Option<&mut V> {
+        let i = self._find(self._hasher.hash_one(k), k)?;
+        self._slots[i].as_mut().map(|(_, v)| v)
+    }
+    //删除k，返回其对应的值
+    pub fn remove(&mut self, k: &K) -> Option<V> {
+        let i = self._find(self._hasher.hash_one(k), k)?;
+        //i前后连续的非空槽若能凑满一组，可能有查找曾在这一组没遇到空槽而继续探测，
+        //此时只能留下墓碑；否则直接置为空槽
+        let before = i.wrapping_sub(_WIDTH) & self._mask();
+        let empty_before = Group::load(&self._ctrl[before..]).match_empty();
+        let empty_after = Group::load(&self._ctrl[i..]).match_empty();
+        if empty_before.leading_zeros() + empty_after.trailing_zeros() >= _WIDTH {
+            self._set_ctrl(i, _DELETED);
+        } else {
+            self._set_ctrl(i, _EMPTY);
+            self._growth_left += 1;
+        }
+        self._len -= 1;
+        self._slots[i].take().map(|(_, v)| v)
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> SwissMap<K, V, S> {
+    //插入，k已存在时替换值
+    pub fn put(&mut self, k: &K, v: V) {
+        let hash = self._hasher.hash_one(k);
+        if let Some(i) = self._find(hash, k) {
+            if let Some(slot) = self._slots[i].as_mut() {
+                slot.1 = v;
+            }
+            return;
+        }
+        let mut i = self._find_insert_slot(hash);
+        //墓碑可以直接复用，占用空槽前要确认还有余量
+        if self._ctrl[i] == _EMPTY && self._growth_left == 0 {
+            self._rehash();
+            i = self._find_insert_slot(hash);
+        }
+        if self._ctrl[i] == _EMPTY {
+            self._growth_left -= 1;
+        }
+        self._set_ctrl(i, _h2(hash));
+        self._slots[i] = Some((k.clone(), v));
+        self._len += 1;
+    }
+}
+
+impl<K, V, S: Default> Default for SwissMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Index<&K> for SwissMap<K, V, S> {
+    type Output = V;
+
+    fn index(&self, index: &K) -> &Self::Output {
+        self.get(index).expect(OUT_OF_RANGE)
+    }
+}
+impl<K: Hash + Eq, V, S: BuildHasher> IndexMut<&K> for SwissMap<K, V, S> {
+    fn index_mut(&mut self, index: &K) -> &mut Self::Output {
+        self.get_mut(index).expect(OUT_OF_RANGE)
+    }
+}
+
+//哈希的高7位，存入控制字节
+fn _h2(hash: u64) -> u8 {
+    (hash >> 57) as u8
+}
+
+/// 按组的三角探测：依次跳过1、2、3...组，槽数为2的幂时能遍历所有组
+struct Probe {
+    _pos: usize,
+    _stride: usize,
+    _mask: usize,
+}
+
+impl Probe {
+    fn new(hash: u64, mask: usize) -> Self {
+        Probe {
+            _pos: hash as usize & mask,
+            _stride: 0,
+            _mask: mask,
+        }
+    }
+    fn next(&mut self) {
+        self._stride += _WIDTH;
+        self._pos = (self._pos + self._stride) & self._mask;
+    }
+}
+
+/// 一组控制字节的比较结果，第i位对应组内第i个槽
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BitMask(u16);
+
+impl BitMask {
+    fn any(self) -> bool {
+        self.0 != 0
+    }
+    fn lowest(self) -> Option<usize> {
+        self.any().then(|| self.0.trailing_zeros() as usize)
+    }
+    //组首连续的0个数
+    fn trailing_zeros(self) -> usize {
+        self.0.trailing_zeros() as usize
+    }
+    //组尾连续的0个数
+    fn leading_zeros(self) -> usize {
+        self.0.leading_zeros() as usize
+    }
+}
+
+impl Iterator for BitMask {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bit = self.lowest()?;
+        self.0 &= self.0 - 1;
+        Some(bit)
+    }
+}
+
+#[cfg(not(all(
+    feature = "simd",
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse2"
+)))]
+use generic::Group;
+#[cfg(all(
+    feature = "simd",
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse2"
+))]
+use sse2::Group;
+
+/// 可移植实现：一组16字节拆成两个u64，逐字节的比较用按位运算同时完成
+mod generic {
+    use super::{BitMask, _EMPTY};
+
+    const _LO: u64 = 0x0101_0101_0101_0101;
+    const _HI: u64 = 0x8080_8080_8080_8080;
+
+    #[derive(Clone, Copy)]
+    pub struct Group([u64; 2]);
+
+    impl Group {
+        //从ctrl开头读一组
+        pub fn load(ctrl: &[u8]) -> Self {
+            let half = |i: usize| {
+                let mut b = [0; 8];
+                b.copy_from_slice(&ctrl[i..i + 8]);
+                u64::from_le_bytes(b)
+            };
+            Group([half(0), half(8)])
+        }
+        //等于c的字节
+        pub fn match_byte(self, c: u8) -> BitMask {
+            self._mask(|x| {
+                let cmp = x ^ (_LO * c as u64);
+                //字节为0时最高位为1，不会向相邻字节进位
+                !(((cmp & !_HI) + !_HI) | cmp) & _HI
+            })
+        }
+        //空槽：0xFF是唯一次高位也为1的特殊值
+        pub fn match_empty(self) -> BitMask {
+            debug_assert_eq!(_EMPTY & (_EMPTY << 1) & 0x80, 0x80);
+            self._mask(|x| x & (x << 1) & _HI)
+        }
+        //空槽或墓碑：最高位为1
+        pub fn match_empty_or_deleted(self) -> BitMask {
+            self._mask(|x| x & _HI)
+        }
+
+        //f对每半组给出各字节最高位，收集成16位的掩码
+        fn _mask(self, f: impl Fn(u64) -> u64) -> BitMask {
+            let pack = |x: u64| ((f(x) >> 7).wrapping_mul(0x0102_0408_1020_4080) >> 56) as u16;
+            BitMask(pack(self.0[0]) | pack(self.0[1]) << 8)
+        }
+    }
+}
+
+/// SSE2实现：一条指令比较16个字节
+/// 调用std::arch的指令需要unsafe，所以只在开启simd feature时编译
+#[cfg(all(
+    feature = "simd",
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse2"
+))]
+mod sse2 {
+    use super::{BitMask, _EMPTY, _WIDTH};
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    #[derive(Clone, Copy)]
+    pub struct Group(__m128i);
+
+    //以下unsafe块的前提都是cfg中已确认目标平台支持sse2
+    impl Group {
+        //从ctrl开头读一组
+        pub fn load(ctrl: &[u8]) -> Self {
+            let bytes = &ctrl[.._WIDTH];
+            //SAFETY: 切片长度为16，非对齐读取
+            Group(unsafe { _mm_loadu_si128(bytes.as_ptr().cast()) })
+        }
+        //等于c的字节
+        pub fn match_byte(self, c: u8) -> BitMask {
+            //SAFETY: 只有寄存器运算
+            let mask = unsafe { _mm_movemask_epi8(_mm_cmpeq_epi8(self.0, _mm_set1_epi8(c as i8))) };
+            BitMask(mask as u16)
+        }
+        pub fn match_empty(self) -> BitMask {
+            self.match_byte(_EMPTY)
+        }
+        //空槽或墓碑：最高位为1，movemask直接取出
+        pub fn match_empty_or_deleted(self) -> BitMask {
+            //SAFETY: 只有寄存器运算
+            BitMask(unsafe { _mm_movemask_epi8(self.0) } as u16)
+        }
+    }
+}
+
+#[test]
+fn test() {
+    use crate::common::rand::XorShift;
+    use std::collections::HashMap;
+
+    let mut map = SwissMap::new();
+    let mut std = HashMap::new();
+    let mut rng = XorShift::new(11);
+    for _ in 0..50000 {
+        let k = rng.gen_range(2000) as u32;
+        match rng.gen_range(3) {
+            0 => assert_eq!(map.remove(&k), std.remove(&k)),
+            _ => {
+                map.put(&k, k * 2);
+                std.insert(k, k * 2);
+            }
+        }
+        assert_eq!(map.len(), std.len());
+    }
+    for k in 0..2000 {
+        assert_eq!(map.get(&k), std.get(&k));
+    }
+    //控制字节与槽一致，镜像与开头一致
+    let n = map._slots.len();
+    for i in 0..n {
+        assert_eq!(map._ctrl[i] & 0x80 == 0, map._slots[i].is_some());
+    }
+    assert_eq!(map._ctrl[..16], map._ctrl[n..]);
+
+    let k = *std.keys().next().unwrap();
+    map[&k] = 1;
+    assert_eq!(map.get(&k), Some(&1));
+    map.remove_all();
+    assert!(map.is_empty());
+    assert_eq!(map.get(&k), None);
+}
+
+#[test]
+fn test_group() {
+    use crate::common::rand::XorShift;
+
+    let mut rng = XorShift::new(3);
+    let naive = |ctrl: &[u8], f: &dyn Fn(u8) -> bool| {
+        BitMask((0..16).fold(0, |m, i| m | (f(ctrl[i]) as u16) << i))
+    };
+    for _ in 0..1000 {
+        let ctrl = (0..16)
+            .map(|_| match rng.gen_range(4) {
+                0 => _EMPTY,
+                1 => _DELETED,
+                _ => rng.gen_range(0x80) as u8,
+            })
+            .collect::<Vec<_>>();
+        let group = Group::load(&ctrl);
+        let h2 = ctrl[rng.gen_range(16)];
+        assert_eq!(group.match_byte(h2), naive(&ctrl, &|c| c == h2));
+        assert_eq!(group.match_empty(), naive(&ctrl, &|c| c == _EMPTY));
+        assert_eq!(
+            group.match_empty_or_deleted(),
+            naive(&ctrl, &|c| c & 0x80 != 0)
+        );
+    }
+    let mask = BitMask(0b1000_0000_0010_0100);
+    assert_eq!(mask.collect::<Vec<_>>(), [2, 5, 15]);
+    assert_eq!((mask.trailing_zeros(), mask.leading_zeros()), (2, 0));
+}
+
+#[test]
+fn test_tombstone() {
+    use crate::common::ident::BuildIdent;
+
+    //h1和h2都取key本身，可以精确控制位置
+    type Map = SwissMap<u64, u64, BuildIdent>;
+    let deleted = |m: &Map| {
+        m._ctrl[..m._slots.len()]
+            .iter()
+            .filter(|c| **c == _DELETED)
+            .count()
+    };
+
+    //16个槽放14个，删除后前后的组内都有空槽，直接置空
+    let mut map = Map::default();
+    for k in 0..14 {
+        map.put(&k, k);
+    }
+    assert_eq!(map.remove(&3), Some(3));
+    assert_eq!(map._ctrl[3], _EMPTY);
+
+    //32个槽放满28个，5前后连续非空的槽超过一组，只能留下墓碑
+    let mut map = Map::with_capacity_and_hasher(20, Default::default());
+    assert_eq!(map._slots.len(), 32);
+    for k in 0..28 {
+        map.put(&k, k);
+    }
+    assert_eq!(map._growth_left, 0);
+    assert_eq!(map.remove(&5), Some(5));
+    assert_eq!(map._ctrl[5], _DELETED);
+    //墓碑可以复用，不消耗空槽
+    map.put(&37, 37);
+    assert_eq!((map._ctrl[5], map._growth_left), (37, 0));
+    assert_eq!(map.remove(&37), Some(37));
+    for k in 2..22 {
+        map.remove(&k);
+    }
+    assert_eq!(deleted(&map), 20);
+    for k in (0..2).chain(22..28) {
+        assert_eq!(map.get(&k), Some(&k));
+    }
+    //空槽用完但元素不到容量一半，原地重建清掉墓碑
+    map.put(&29, 29);
+    assert_eq!(map._slots.len(), 32);
+    assert_eq!(deleted(&map), 0);
+    assert_eq!(map._growth_left, 28 - map.len());
+    for k in (0..2).chain(22..28).chain([29]) {
+        assert_eq!(map.get(&k), Some(&k));
+    }
+}
+
+#[test]
+#[should_panic(expected = "capacity overflow")]
+fn test_capacity_overflow() {
+    //换算槽数时溢出，不能回绕成很小的表
+    let _: SwissMap<u64, u64> = SwissMap::with_cap(usize::MAX / 2);
+}
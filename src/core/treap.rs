@@ -0,0 +1,680 @@
+use std::{cmp::Ordering, fmt::Debug, mem, ops::Range};
+
+use crate::common::{errs::OUT_OF_RANGE, rand::XorShift};
+
+/// 树堆，按key为二叉搜索树，按随机优先级为大根堆，期望高度O(log n)
+/// 插入、删除都由split(按key拆成两棵)和merge(按优先级合并两棵)完成
+#[derive(Clone)]
+pub struct Treap<K, V> {
+    _root: InnerTreapNode<K, V>,
+    _rng: XorShift,
+}
+
+type InnerTreapNode<K, V> = Option<Box<TreapNode<K, V>>>;
+
+#[derive(Debug, Clone)]
+pub struct TreapNode<K, V> {
+    _left: InnerTreapNode<K, V>,
+    _right: InnerTreapNode<K, V>,
+    _key: K,
+    _val: V,
+    _prio: u64,
+    //以该节点为根的子树的节点数
+    _size: usize,
+}
+
+impl<K, V> TreapNode<K, V> {
+    fn new(key: K, val: V, prio: u64) -> Self {
+        Self {
+            _left: None,
+            _right: None,
+            _key: key,
+            _val: val,
+            _prio: prio,
+            _size: 1,
+        }
+    }
+    fn _pull_up(&mut self) {
+        self._size = 1 + _size(&self._left) + _size(&self._right);
+    }
+}
+
+fn _size<K, V>(node: &InnerTreapNode<K, V>) -> usize {
+    node.as_ref().map_or(0, |x| x._size)
+}
+
+//按go_left拆分，go_left(key)为true的节点(须是一段前缀)分到左边
+fn _split<K, V, F: Fn(&K) -> bool>(
+    node: InnerTreapNode<K, V>,
+    go_left: &F,
+) -> (InnerTreapNode<K, V>, InnerTreapNode<K, V>) {
+    let mut x = match node {
+        Some(x) => x,
+        None => return (None, None),
+    };
+    if go_left(&x._key) {
+        let (l, r) = _split(x._right.take(), go_left);
+        x._right = l;
+        x._pull_up();
+        (Some(x), r)
+    } else {
+        let (l, r) = _split(x._left.take(), go_left);
+        x._left = r;
+        x._pull_up();
+        (l, Some(x))
+    }
+}
+
+//合并，a中的key全部小于b中的key，优先级高的作根
+fn _merge<K, V>(a: InnerTreapNode<K, V>, b: InnerTreapNode<K, V>) -> InnerTreapNode<K, V> {
+    match (a, b) {
+        (None, b) => b,
+        (a, None) => a,
+        (Some(mut a), Some(mut b)) => {
+            if a._prio > b._prio {
+                a._right = _merge(a._right.take(), Some(b));
+                a._pull_up();
+                Some(a)
+            } else {
+                b._left = _merge(Some(a), b._left.take());
+                b._pull_up();
+                Some(b)
+            }
+        }
+    }
+}
+
+//合并key可能交叉的两棵树，key相同时b_wins为true保留b的值
+fn _union<K: Ord, V>(
+    a: InnerTreapNode<K, V>,
+    b: InnerTreapNode<K, V>,
+    b_wins: bool,
+) -> InnerTreapNode<K, V> {
+    let (mut a, b, b_wins) = match (a, b) {
+        (None, b) => return b,
+        (a, None) => return a,
+        //优先级高的作根，另一棵按根的key拆开分别并入左右子树
+        (Some(a), Some(b)) if a._prio >= b._prio => (a, Some(b), b_wins),
+        (Some(a), Some(b)) => (b, Some(a), !b_wins),
+    };
+    let (l, r) = _split(b, &|k: &K| *k < a._key);
+    let (m, r) = _split(r, &|k: &K| *k == a._key);
+    if let (Some(m), true) = (m, b_wins) {
+        a._val = m._val;
+    }
+    a._left = _union(a._left.take(), l, b_wins);
+    a._right = _union(a._right.take(), r, b_wins);
+    a._pull_up();
+    Some(a)
+}
+
+impl<K, V> Default for Treap<K, V> {
+    fn default() -> Self {
+        Self {
+            _root: None,
+            _rng: XorShift::default(),
+        }
+    }
+}
+
+impl<K, V> Treap<K, V> {
+    pub fn new(key: K, val: V) -> Self {
+        let mut treap = Self::default();
+        let prio = treap._rng.next_u64();
+        treap._root = Some(Box::new(TreapNode::new(key, val, prio)));
+        treap
+    }
+    //设置随机种子，种子相同且操作相同则树的形状相同
+    pub fn seed(&mut self, seed: u64) {
+        self._rng = XorShift::new(seed);
+    }
+    //长度
+    pub fn len(&self) -> usize {
+        _size(&self._root)
+    }
+    //为空返回true
+    pub fn is_empty(&self) -> bool {
+        self._root.is_none()
+    }
+    //清空
+    pub fn clear(&mut self) {
+        self._root = None;
+    }
+    //按key从小到大遍历
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut it = Iter { _stack: vec![] };
+        it._push_left(self._root.as_deref());
+        it
+    }
+}
+
+impl<K: Ord, V> Treap<K, V> {
+    fn _get_node(&self, key: &K) -> Option<&TreapNode<K, V>> {
+        let mut cur = self._root.as_deref();
+        while let Some(x) = cur {
+            cur = match key.cmp(&x._key) {
+                Ordering::Equal => return Some(x),
+                Ordering::Less => x._left.as_deref(),
+                Ordering::Greater => x._right.as_deref(),
+            };
+        }
+        None
+    }
+    //是否包含key
+    pub fn contains_key(&self, key: &K) -> bool {
+        self._get_node(key).is_some()
+    }
+    //查找
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self._get_node(key).map(|x| &x._val)
+    }
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut cur = self._root.as_deref_mut();
+        while let Some(x) = cur {
+            cur = match key.cmp(&x._key) {
+                Ordering::Equal => return Some(&mut x._val),
+                Ordering::Less => x._left.as_deref_mut(),
+                Ordering::Greater => x._right.as_deref_mut(),
+            };
+        }
+        None
+    }
+    //拆成(小于key, 等于key, 大于key)三部分
+    fn _split3(
+        &mut self,
+        key: &K,
+    ) -> (
+        InnerTreapNode<K, V>,
+        InnerTreapNode<K, V>,
+        InnerTreapNode<K, V>,
+    ) {
+        let (l, r) = _split(self._root.take(), &|k: &K| k < key);
+        let (m, r) = _split(r, &|k: &K| k == key);
+        (l, m, r)
+    }
+    //插入，key已存在时替换并返回旧值
+    pub fn insert(&mut self, key: K, val: V) -> Option<V> {
+        let (l, m, r) = self._split3(&key);
+        let (m, old) = match m {
+            Some(mut m) => {
+                let old = mem::replace(&mut m._val, val);
+                (m, Some(old))
+            }
+            None => (
+                Box::new(TreapNode::new(key, val, self._rng.next_u64())),
+                None,
+            ),
+        };
+        self._root = _merge(_merge(l, Some(m)), r);
+        old
+    }
+    //删除，返回删除的值
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (l, m, r) = self._split3(key);
+        self._root = _merge(l, r);
+        m.map(|m| m._val)
+    }
+    //拆分，self保留小于key的部分，返回不小于key的部分，期望O(log n)
+    pub fn split_off(&mut self, key: &K) -> Self {
+        let (l, r) = _split(self._root.take(), &|k: &K| k < key);
+        self._root = l;
+        Self {
+            _root: r,
+            _rng: XorShift::new(self._rng.next_u64()),
+        }
+    }
+    //把other中的所有元素移入self，other变为空，key相同时保留other的值
+    //key不交叉时(如split_off的两部分)期望O(log n)
+    pub fn append(&mut self, other: &mut Self) {
+        let a = self._root.take();
+        let b = other._root.take();
+        let disjoint = |a: &InnerTreapNode<K, V>, b: &InnerTreapNode<K, V>| match (a, b) {
+            (Some(a), Some(b)) => _last_key(a) < _first_key(b),
+            _ => true,
+        };
+        self._root = if disjoint(&a, &b) {
+            _merge(a, b)
+        } else if disjoint(&b, &a) {
+            _merge(b, a)
+        } else {
+            _union(a, b, true)
+        };
+    }
+}
+
+fn _first_key<K, V>(mut x: &TreapNode<K, V>) -> &K {
+    while let Some(l) = x._left.as_deref() {
+        x = l;
+    }
+    &x._key
+}
+
+fn _last_key<K, V>(mut x: &TreapNode<K, V>) -> &K {
+    while let Some(r) = x._right.as_deref() {
+        x = r;
+    }
+    &x._key
+}
+
+/// 中序遍历迭代器，栈中为左子树已访问完、自身尚未访问的祖先
+pub struct Iter<'a, K, V> {
+    _stack: Vec<&'a TreapNode<K, V>>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    //压入x及其左链
+    fn _push_left(&mut self, mut x: Option<&'a TreapNode<K, V>>) {
+        while let Some(y) = x {
+            self._stack.push(y);
+            x = y._left.as_deref();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let x = self._stack.pop()?;
+        self._push_left(x._right.as_deref());
+        Some((&x._key, &x._val))
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a Treap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for Treap<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// 隐式树堆(序列树堆)，不存key，以中序位置为下标
+/// 按下标插入、删除O(log n)，区间翻转用懒标记，O(log n)
+#[derive(Clone)]
+pub struct ImplicitTreap<T> {
+    _root: InnerSeqNode<T>,
+    _rng: XorShift,
+}
+
+type InnerSeqNode<T> = Option<Box<SeqNode<T>>>;
+
+#[derive(Debug, Clone)]
+pub struct SeqNode<T> {
+    _left: InnerSeqNode<T>,
+    _right: InnerSeqNode<T>,
+    _val: T,
+    _prio: u64,
+    _size: usize,
+    //懒标记，子树需要翻转(左右子树互换)但还未下放
+    _rev: bool,
+}
+
+impl<T> SeqNode<T> {
+    fn new(val: T, prio: u64) -> Self {
+        Self {
+            _left: None,
+            _right: None,
+            _val: val,
+            _prio: prio,
+            _size: 1,
+            _rev: false,
+        }
+    }
+    fn _pull_up(&mut self) {
+        self._size = 1 + _seq_size(&self._left) + _seq_size(&self._right);
+    }
+    //下放翻转标记
+    fn _push_down(&mut self) {
+        if !self._rev {
+            return;
+        }
+        mem::swap(&mut self._left, &mut self._right);
+        for child in [&mut self._left, &mut self._right].into_iter().flatten() {
+            child._rev = !child._rev;
+        }
+        self._rev = false;
+    }
+    //考虑未下放的翻转标记后的左右子节点
+    fn _children(&self, flipped: bool) -> (Option<&Self>, Option<&Self>) {
+        if flipped {
+            (self._right.as_deref(), self._left.as_deref())
+        } else {
+            (self._left.as_deref(), self._right.as_deref())
+        }
+    }
+}
+
+fn _seq_size<T>(node: &InnerSeqNode<T>) -> usize {
+    node.as_ref().map_or(0, |x| x._size)
+}
+
+//拆成(前k个, 其余)
+fn _seq_split<T>(node: InnerSeqNode<T>, k: usize) -> (InnerSeqNode<T>, InnerSeqNode<T>) {
+    let mut x = match node {
+        Some(x) => x,
+        None => return (None, None),
+    };
+    x._push_down();
+    let left_size = _seq_size(&x._left);
+    if k <= left_size {
+        let (l, r) = _seq_split(x._left.take(), k);
+        x._left = r;
+        x._pull_up();
+        (l, Some(x))
+    } else {
+        let (l, r) = _seq_split(x._right.take(), k - left_size - 1);
+        x._right = l;
+        x._pull_up();
+        (Some(x), r)
+    }
+}
+
+//把b接在a后面
+fn _seq_merge<T>(a: InnerSeqNode<T>, b: InnerSeqNode<T>) -> InnerSeqNode<T> {
+    match (a, b) {
+        (None, b) => b,
+        (a, None) => a,
+        (Some(mut a), Some(mut b)) => {
+            if a._prio > b._prio {
+                a._push_down();
+                a._right = _seq_merge(a._right.take(), Some(b));
+                a._pull_up();
+                Some(a)
+            } else {
+                b._push_down();
+                b._left = _seq_merge(Some(a), b._left.take());
+                b._pull_up();
+                Some(b)
+            }
+        }
+    }
+}
+
+impl<T> Default for ImplicitTreap<T> {
+    fn default() -> Self {
+        Self {
+            _root: None,
+            _rng: XorShift::default(),
+        }
+    }
+}
+
+impl<T> ImplicitTreap<T> {
+    pub fn new(val: T) -> Self {
+        let mut treap = Self::default();
+        treap.push(val);
+        treap
+    }
+    //设置随机种子
+    pub fn seed(&mut self, seed: u64) {
+        self._rng = XorShift::new(seed);
+    }
+    //长度
+    pub fn len(&self) -> usize {
+        _seq_size(&self._root)
+    }
+    //为空返回true
+    pub fn is_empty(&self) -> bool {
+        self._root.is_none()
+    }
+    //清空
+    pub fn clear(&mut self) {
+        self._root = None;
+    }
+    //追加到末尾
+    pub fn push(&mut self, val: T) {
+        let len = self.len();
+        self.insert_at(len, val);
+    }
+    //插入到index处，index之后的元素后移，index大于长度时panic
+    pub fn insert_at(&mut self, index: usize, val: T) {
+        assert!(index <= self.len(), "{}", OUT_OF_RANGE);
+        let node = Box::new(SeqNode::new(val, self._rng.next_u64()));
+        let (l, r) = _seq_split(self._root.take(), index);
+        self._root = _seq_merge(_seq_merge(l, Some(node)), r);
+    }
+    //删除index处的元素，越界时返回None
+    pub fn remove_at(&mut self, index: usize) -> Option<T> {
+        if index >= self.len() {
+            return None;
+        }
+        let (l, r) = _seq_split(self._root.take(), index);
+        let (m, r) = _seq_split(r, 1);
+        self._root = _seq_merge(l, r);
+        m.map(|m| m._val)
+    }
+    //下标为index的元素
+    pub fn get(&self, mut index: usize) -> Option<&T> {
+        let mut cur = self._root.as_deref();
+        let mut flipped = false;
+        while let Some(x) = cur {
+            flipped ^= x._rev;
+            let (l, r) = x._children(flipped);
+            let left_size = l.map_or(0, |l| l._size);
+            cur = match index.cmp(&left_size) {
+                Ordering::Equal => return Some(&x._val),
+                Ordering::Less => l,
+                Ordering::Greater => {
+                    index -= left_size + 1;
+                    r
+                }
+            };
+        }
+        None
+    }
+    //翻转区间[range.start, range.end)，越界时panic
+    pub fn reverse(&mut self, range: Range<usize>) {
+        assert!(
+            range.start <= range.end && range.end <= self.len(),
+            "{}",
+            OUT_OF_RANGE
+        );
+        let (l, r) = _seq_split(self._root.take(), range.end);
+        let (l, mut m) = _seq_split(l, range.start);
+        if let Some(m) = m.as_mut() {
+            m._rev = !m._rev;
+        }
+        self._root = _seq_merge(_seq_merge(l, m), r);
+    }
+    //按下标顺序遍历
+    pub fn iter(&self) -> SeqIter<'_, T> {
+        let mut it = SeqIter { _stack: vec![] };
+        it._push_left(self._root.as_deref(), false);
+        it
+    }
+}
+
+impl<T> FromIterator<T> for ImplicitTreap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut treap = Self::default();
+        for val in iter {
+            treap.push(val);
+        }
+        treap
+    }
+}
+
+/// 中序遍历迭代器，同时记录每个节点所处的翻转状态
+pub struct SeqIter<'a, T> {
+    _stack: Vec<(&'a SeqNode<T>, bool)>,
+}
+
+impl<'a, T> SeqIter<'a, T> {
+    //压入x及其(考虑翻转后的)左链，flipped为祖先上累积的翻转标记
+    fn _push_left(&mut self, mut x: Option<&'a SeqNode<T>>, mut flipped: bool) {
+        while let Some(y) = x {
+            flipped ^= y._rev;
+            self._stack.push((y, flipped));
+            x = y._children(flipped).0;
+        }
+    }
+}
+
+impl<'a, T> Iterator for SeqIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (x, flipped) = self._stack.pop()?;
+        self._push_left(x._children(flipped).1, flipped);
+        Some(&x._val)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ImplicitTreap<T> {
+    type Item = &'a T;
+    type IntoIter = SeqIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Debug> Debug for ImplicitTreap<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+//检查二叉搜索树、堆序和子树大小，返回子树高度
+fn _check<K: Ord, V>(node: &InnerTreapNode<K, V>) -> usize {
+    let x = match node {
+        Some(x) => x,
+        None => return 0,
+    };
+    for child in [&x._left, &x._right].into_iter().flatten() {
+        assert!(child._prio <= x._prio);
+    }
+    if let Some(l) = &x._left {
+        assert!(*_last_key(l) < x._key);
+    }
+    if let Some(r) = &x._right {
+        assert!(x._key < *_first_key(r));
+    }
+    assert_eq!(x._size, 1 + _size(&x._left) + _size(&x._right));
+    1 + _check(&x._left).max(_check(&x._right))
+}
+
+#[test]
+fn test() {
+    use std::collections::BTreeMap;
+
+    let mut treap = Treap::default();
+    treap.seed(16);
+    let mut map = BTreeMap::new();
+    let mut rng = XorShift::new(17);
+    for i in 0..5000 {
+        let k = rng.gen_range(2000);
+        if rng.gen_range(3) == 0 {
+            assert_eq!(treap.remove(&k), map.remove(&k));
+        } else {
+            assert_eq!(treap.insert(k, i), map.insert(k, i));
+        }
+        assert_eq!(treap.len(), map.len());
+    }
+    let height = _check(&treap._root);
+    assert!(height < 4 * (treap.len() as f64).log2() as usize);
+    assert!(treap.iter().eq(map.iter()));
+    for k in 0..2000 {
+        assert_eq!(treap.get(&k), map.get(&k));
+    }
+    *treap.get_mut(map.keys().next().unwrap()).unwrap() = -1;
+    *map.values_mut().next().unwrap() = -1;
+
+    //拆分再按原顺序合并
+    let mut right = treap.split_off(&1000);
+    let map_right = map.split_off(&1000);
+    _check(&treap._root);
+    _check(&right._root);
+    assert!(treap.iter().eq(map.iter()));
+    assert!(right.iter().eq(map_right.iter()));
+    treap.append(&mut right);
+    assert!(right.is_empty());
+    _check(&treap._root);
+    assert_eq!(treap.len(), map.len() + map_right.len());
+
+    //key交叉时other中的值覆盖self中的值
+    let mut other: Treap<usize, i32> = (0..3000).step_by(3).fold(Treap::default(), |mut t, k| {
+        t.insert(k, -2);
+        t
+    });
+    map.extend(map_right);
+    let mut other_map: BTreeMap<usize, i32> = (0..3000).step_by(3).map(|k| (k, -2)).collect();
+    treap.append(&mut other);
+    map.append(&mut other_map);
+    _check(&treap._root);
+    assert!(treap.iter().eq(map.iter()));
+}
+
+#[test]
+fn test_seed() {
+    //相同种子、相同操作得到相同形状
+    let shape = |seed: u64| {
+        let mut treap = Treap::default();
+        treap.seed(seed);
+        for k in 0..1000 {
+            treap.insert(k, ());
+        }
+        let mut prios = vec![];
+        let mut stack: Vec<&TreapNode<i32, ()>> = treap._root.as_deref().into_iter().collect();
+        while let Some(x) = stack.pop() {
+            prios.push((x._key, x._prio));
+            stack.extend(x._left.as_deref());
+            stack.extend(x._right.as_deref());
+        }
+        prios
+    };
+    assert_eq!(shape(1), shape(1));
+    assert_ne!(shape(1), shape(2));
+}
+
+#[test]
+fn test_implicit() {
+    let mut seq: ImplicitTreap<usize> = (0..10).collect();
+    seq.reverse(2..6);
+    assert_eq!(
+        seq.iter().copied().collect::<Vec<_>>(),
+        [0, 1, 5, 4, 3, 2, 6, 7, 8, 9]
+    );
+    assert_eq!(seq.get(3), Some(&4));
+
+    let mut treap = ImplicitTreap::default();
+    treap.seed(18);
+    let mut vec = vec![];
+    let mut rng = XorShift::new(19);
+    for i in 0..3000 {
+        match rng.gen_range(4) {
+            0 if !vec.is_empty() => {
+                let index = rng.gen_range(vec.len());
+                assert_eq!(treap.remove_at(index), Some(vec.remove(index)));
+            }
+            1 => {
+                let a = rng.gen_range(vec.len() + 1);
+                let b = rng.gen_range(vec.len() + 1);
+                let range = a.min(b)..a.max(b);
+                treap.reverse(range.clone());
+                vec[range].reverse();
+            }
+            _ => {
+                let index = rng.gen_range(vec.len() + 1);
+                treap.insert_at(index, i);
+                vec.insert(index, i);
+            }
+        }
+        assert_eq!(treap.len(), vec.len());
+    }
+    assert!(treap.iter().eq(vec.iter()));
+    for (i, v) in vec.iter().enumerate() {
+        assert_eq!(treap.get(i), Some(v));
+    }
+    assert_eq!(treap.get(vec.len()), None);
+    assert_eq!(treap.remove_at(vec.len()), None);
+}